@@ -0,0 +1,273 @@
+//! Animated graphs: the equation may use a time parameter `t`, which sweeps
+//! a range while the graph redraws (`y = sin(x - t)` is a travelling wave).
+//!
+//! `t` is bound like any other value for the length of the animation, so
+//! the equation can still call the user's functions — and those functions
+//! can use `t` too. A binding the user already had named `t` is set aside
+//! and put back afterwards.
+
+use std::time::Duration;
+
+use crossterm::event::KeyCode;
+use rusty_maths::equation_analyzer::Definitions;
+
+use crate::modules::{
+    bindings::Shadow,
+    commands::{open_viewer, with_y_range},
+    common::GraphOptions,
+    error_render,
+    graphing::{graph, graph_with_plot, Plot},
+    inputs::{get_g_inputs, get_optional_numerical_input, read_user_input},
+    logger::Logger,
+    viewer::ViewerEvent,
+};
+
+/// The name the animated equation uses for time.
+const T: &str = "t";
+
+const DEFAULT_T_MAX: f32 = std::f32::consts::TAU;
+const DEFAULT_FRAMES: usize = 100;
+const DEFAULT_FPS: f32 = 20.0;
+const MAX_FPS: f32 = 120.0;
+
+const HELP: &[(&str, &str)] = &[
+    ("space", "pause / resume"),
+    ("← →", "step one frame (pauses)"),
+    ("r", "restart"),
+];
+
+/// What happens when `t` reaches the end of its range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Playback {
+    /// Stop on the last frame.
+    Once,
+    /// Jump back to the first frame.
+    Loop,
+    /// Run backwards to the first frame, then forwards again.
+    Bounce,
+}
+
+impl Playback {
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "once" => Some(Playback::Once),
+            "" | "loop" => Some(Playback::Loop),
+            "bounce" => Some(Playback::Bounce),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Playback::Once => "once",
+            Playback::Loop => "loop",
+            Playback::Bounce => "bounce",
+        }
+    }
+}
+
+/// The `t` sweep: `frames` evenly spaced values from `t_min` to `t_max`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Timeline {
+    pub t_min: f32,
+    pub t_max: f32,
+    pub frames: usize,
+    pub playback: Playback,
+}
+
+impl Timeline {
+    pub(crate) fn t_at(&self, frame: usize) -> f32 {
+        if self.frames < 2 {
+            return self.t_min;
+        }
+        self.t_min + (self.t_max - self.t_min) * frame as f32 / (self.frames - 1) as f32
+    }
+}
+
+/// Where the animation is: the current frame and which way it is running.
+#[derive(Debug, Default)]
+pub(crate) struct Clock {
+    pub frame: usize,
+    backwards: bool,
+}
+
+impl Clock {
+    /// Advances one frame by the timeline's playback rule. Returns false
+    /// once a `Once` timeline has reached its last frame.
+    pub(crate) fn tick(&mut self, timeline: &Timeline) -> bool {
+        let last = timeline.frames.saturating_sub(1);
+        if last == 0 {
+            return timeline.playback != Playback::Once;
+        }
+        match timeline.playback {
+            Playback::Once => {
+                if self.frame >= last {
+                    return false;
+                }
+                self.frame += 1;
+            }
+            Playback::Loop => self.frame = (self.frame + 1) % (last + 1),
+            Playback::Bounce => {
+                if self.frame >= last {
+                    self.backwards = true;
+                } else if self.frame == 0 {
+                    self.backwards = false;
+                }
+                if self.backwards {
+                    self.frame -= 1;
+                } else {
+                    self.frame += 1;
+                }
+            }
+        }
+        true
+    }
+
+    /// Moves one frame by hand. A looping timeline wraps around; the
+    /// others stop at either end.
+    pub(crate) fn step(&mut self, timeline: &Timeline, forward: bool) {
+        let last = timeline.frames.saturating_sub(1);
+        self.frame = match (forward, timeline.playback) {
+            (true, Playback::Loop) if self.frame >= last => 0,
+            (false, Playback::Loop) if self.frame == 0 => last,
+            (true, _) => (self.frame + 1).min(last),
+            (false, _) => self.frame.saturating_sub(1),
+        };
+    }
+}
+
+/// Runs the animation and returns the frame it was showing when closed.
+pub(crate) fn animate(
+    l: &mut impl Logger,
+    go: &GraphOptions,
+    defs: &mut Definitions,
+) -> Option<(String, Plot)> {
+    let (eq, x_min, x_max) = get_g_inputs(l);
+    let go = with_y_range(l, go);
+    let (timeline, fps) = get_timeline_inputs(l);
+
+    let shadow = Shadow::new(defs, T);
+    if let Err(e) = defs.define_value(T, timeline.t_min) {
+        l.eprint(&format!("Can't animate over '{T}': {}", e.message));
+        return None;
+    }
+
+    // Render once in the normal screen so a bad equation reports there.
+    let last = match graph(&eq, x_min, x_max, &go, defs) {
+        Ok(_) => run(&eq, (x_min, x_max), &go, &timeline, fps, defs, l),
+        Err(e) => {
+            l.eprint(&error_render::render_error_with_source(&eq, &e, defs));
+            None
+        }
+    };
+
+    shadow.restore(defs);
+    last
+}
+
+fn run(
+    eq: &str,
+    (x_min, x_max): (f32, f32),
+    go: &GraphOptions,
+    timeline: &Timeline,
+    fps: f32,
+    defs: &mut Definitions,
+    l: &mut impl Logger,
+) -> Option<(String, Plot)> {
+    let mut viewer = open_viewer(HELP, l)?;
+    let frame_delay = Duration::from_secs_f32(1.0 / fps);
+
+    let mut clock = Clock::default();
+    let mut playing = true;
+    let mut last = None;
+
+    loop {
+        let t = timeline.t_at(clock.frame);
+        // `t` was bound above, so rebinding it can't fail.
+        let _ = defs.define_value(T, t);
+
+        let view = viewer.fit(go);
+        if let Ok((g, plot)) = graph_with_plot(eq, x_min, x_max, &view, defs) {
+            let status = format!(
+                "{eq}   t = {t:.3}   frame {}/{}   {}   {}",
+                clock.frame + 1,
+                timeline.frames,
+                timeline.playback.name(),
+                if playing { "playing" } else { "paused" }
+            );
+            viewer.draw(&g, &status);
+            last = Some((g, plot));
+        }
+
+        match viewer.next_event(playing.then_some(frame_delay)) {
+            ViewerEvent::Quit => break,
+            ViewerEvent::Tick => playing = clock.tick(timeline),
+            ViewerEvent::Key(k) => match k.code {
+                KeyCode::Char(' ') => playing = !playing,
+                KeyCode::Right | KeyCode::Left => {
+                    playing = false;
+                    clock.step(timeline, k.code == KeyCode::Right);
+                }
+                KeyCode::Char('r') => clock = Clock::default(),
+                _ => {}
+            },
+            ViewerEvent::Redraw | ViewerEvent::Mouse(_) => {}
+        }
+    }
+    last
+}
+
+/// Prompts for the `t` range, frame count, frame rate and playback, each
+/// with a default for a blank answer.
+fn get_timeline_inputs(l: &mut impl Logger) -> (Timeline, f32) {
+    let (t_min, t_max) = loop {
+        let t_min = get_optional_numerical_input("t min (blank = 0): ", l).unwrap_or(0.0);
+        let t_max =
+            get_optional_numerical_input("t max (blank = 2π): ", l).unwrap_or(DEFAULT_T_MAX);
+        if t_min < t_max {
+            break (t_min, t_max);
+        }
+        l.eprint(&format!(
+            "t min `{t_min}` must be less than t max `{t_max}`"
+        ));
+    };
+
+    let frames = loop {
+        match get_optional_numerical_input::<usize>("frames (blank = 100): ", l) {
+            Some(0) => l.eprint("An animation needs at least one frame"),
+            Some(n) => break n,
+            None => break DEFAULT_FRAMES,
+        }
+    };
+
+    let fps = loop {
+        match get_optional_numerical_input::<f32>("frames per second (blank = 20): ", l) {
+            Some(f) if f > 0.0 && f <= MAX_FPS => break f,
+            Some(f) => l.eprint(&format!(
+                "frames per second `{f}` must be above 0 and at most {MAX_FPS}"
+            )),
+            None => break DEFAULT_FPS,
+        }
+    };
+
+    let playback = loop {
+        let answer =
+            read_user_input("playback once/loop/bounce (blank = loop): ").unwrap_or_default();
+        match Playback::parse(&answer) {
+            Some(p) => break p,
+            None => l.eprint(&format!(
+                "Unknown playback '{answer}'. Valid: once, loop, bounce"
+            )),
+        }
+    };
+
+    (
+        Timeline {
+            t_min,
+            t_max,
+            frames,
+            playback,
+        },
+        fps,
+    )
+}
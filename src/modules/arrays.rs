@@ -0,0 +1,761 @@
+//! Vectors and matrices at the prompt: `[1, 2, 3] . [4, 5, 6]`,
+//! `det([[1, 2], [3, 4]])`, `let v = [1, 2, 3]`.
+//!
+//! The calculator only knows numbers, so a line holding a bracket or an
+//! array binding is read here instead. This parser handles the array
+//! arithmetic itself and hands every scalar piece — a whole entry or
+//! argument with no arrays in it, a number, a name, a call like `sin(1)` —
+//! to the calculator, so those behave exactly as they do anywhere else.
+//! Around arrays it follows the calculator's syntax too: `|>` pipes into a
+//! function and `2m` multiplies.
+
+use rusty_maths::{
+    equation_analyzer::{calculator::calculate_with, Definitions, EquationError},
+    linear_algebra::Matrix,
+};
+
+use crate::modules::{
+    error_render,
+    logger::Logger,
+    matrix,
+    repl::Repl,
+    string_maker::{format_entry, make_matrix_string},
+};
+
+/// Functions that take or give vectors and matrices.
+pub(crate) const ARRAY_FUNCTIONS: &[(&str, &str)] = &[
+    ("det", "determinant of a square matrix"),
+    ("inv", "inverse of a square matrix"),
+    ("transpose", "rows swapped for columns"),
+    ("trace", "sum of a square matrix's diagonal"),
+    ("rank", "number of independent rows"),
+    ("norm", "length of a vector (Frobenius norm of a matrix)"),
+    ("dot", "dot product of two vectors, like a . b"),
+    ("cross", "cross product of two 3-vectors"),
+    ("solve", "x with A x = b, as solve(A, b)"),
+];
+
+// Largest whole-number power of a matrix, which is worked out by repeated
+// multiplication
+const MAX_MATRIX_POWER: f64 = 1000.0;
+
+/// What an array expression evaluates to.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Number(f64),
+    Vector(Vec<f64>),
+    Matrix(Matrix),
+}
+
+impl Value {
+    /// "a number", "a 3-vector", "a 2×3 matrix", for messages.
+    pub(crate) fn kind(&self) -> String {
+        match self {
+            Value::Number(_) => "a number".to_string(),
+            Value::Vector(v) => format!("a {}-vector", v.len()),
+            Value::Matrix(m) => {
+                let (rows, cols) = matrix::shape(m);
+                format!("a {rows}×{cols} matrix")
+            }
+        }
+    }
+
+    /// The value as it would be typed: `[1, 2]`, `[[1, 2], [3, 4]]`.
+    /// Entries keep full precision, so it reads back unchanged.
+    pub(crate) fn literal(&self) -> String {
+        let row = |r: &[f64]| {
+            let entries: Vec<String> = r.iter().map(f64::to_string).collect();
+            format!("[{}]", entries.join(", "))
+        };
+        match self {
+            Value::Number(n) => n.to_string(),
+            Value::Vector(v) => row(v),
+            Value::Matrix(m) => {
+                let rows: Vec<String> = m.iter().map(|r| row(r)).collect();
+                format!("[{}]", rows.join(", "))
+            }
+        }
+    }
+
+    /// The value for the terminal, at `precision` decimals.
+    pub(crate) fn display(&self, precision: usize) -> String {
+        match self {
+            Value::Number(n) => format_entry(*n, precision),
+            Value::Vector(v) => make_matrix_string(&vec![v.clone()], precision),
+            Value::Matrix(m) => make_matrix_string(m, precision),
+        }
+    }
+}
+
+/// Why an array expression failed.
+#[derive(Debug)]
+pub(crate) enum ArrayError {
+    /// From the calculator, with its span moved to where the scalar piece
+    /// sits in the whole expression
+    Equation(EquationError),
+    /// A value of the wrong kind or shape, and the char range it came from
+    Type {
+        message: String,
+        span: (usize, usize),
+    },
+}
+
+impl ArrayError {
+    fn at(message: impl Into<String>, start: usize, end: usize) -> Self {
+        ArrayError::Type {
+            message: message.into(),
+            span: (start, end),
+        }
+    }
+
+    /// Carets under the echoed prompt line.
+    pub(crate) fn render_at_prompt(&self, defs: &Definitions) -> String {
+        match self {
+            ArrayError::Equation(e) => error_render::render_repl_error(e, defs),
+            ArrayError::Type { message, span } => {
+                error_render::format_message_at(message, *span, error_render::REPL_PROMPT_WIDTH)
+            }
+        }
+    }
+
+    /// `source` reprinted with carets under it, for input read at a
+    /// sub-prompt.
+    pub(crate) fn render_with_source(&self, source: &str, defs: &Definitions) -> String {
+        match self {
+            ArrayError::Equation(e) => error_render::render_error_with_source(source, e, defs),
+            ArrayError::Type { message, span } => {
+                format!(
+                    "{source}\n{}",
+                    error_render::format_message_at(message, *span, 0)
+                )
+            }
+        }
+    }
+}
+
+/// Whether `line` needs this evaluator: it has a bracket, or names an
+/// array binding.
+pub(crate) fn is_array_expression(line: &str, arrays: &[(String, Value)]) -> bool {
+    line.contains('[') || words(line).any(|w| lookup(arrays, w).is_some())
+}
+
+pub(crate) fn lookup<'a>(arrays: &'a [(String, Value)], name: &str) -> Option<&'a Value> {
+    arrays.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+}
+
+fn words(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| w.starts_with(|c: char| c.is_alphabetic()))
+}
+
+/// Evaluates a line typed at the prompt, printing the result. A number
+/// becomes `ans`, as it would from the calculator.
+pub(crate) fn evaluate_line(line: &str, repl: &mut Repl, l: &mut impl Logger) {
+    match evaluate_array(line, &repl.defs, &repl.arrays) {
+        Ok(Value::Number(n)) => {
+            repl.set_ans(n as f32);
+            l.print(&format_entry(n, repl.precision));
+        }
+        Ok(value) => l.print(&value.display(repl.precision)),
+        Err(e) => l.eprint(&e.render_at_prompt(&repl.defs)),
+    }
+}
+
+pub(crate) fn evaluate_array(
+    s: &str,
+    defs: &Definitions,
+    arrays: &[(String, Value)],
+) -> Result<Value, ArrayError> {
+    let mut parser = Parser {
+        chars: s.chars().collect(),
+        pos: 0,
+        defs,
+        arrays,
+    };
+    let value = parser.expression()?;
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(value),
+        Some(c) => Err(ArrayError::at(
+            format!("Unexpected '{c}'"),
+            parser.pos,
+            parser.pos + 1,
+        )),
+    }
+}
+
+/// Recursive descent over the chars of the line, evaluating as it goes.
+/// Precedence, loosest first: `|>`, then `+ -`, then `* / .` and implicit
+/// multiplication, then unary `-`, then `^`.
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    defs: &'a Definitions,
+    arrays: &'a [(String, Value)],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// The next non-space char, if it is one of `options`, consumed.
+    fn operator(&mut self, options: &[char]) -> Option<(char, usize)> {
+        self.skip_whitespace();
+        let c = self.peek().filter(|c| options.contains(c))?;
+        self.pos += 1;
+        Some((c, self.pos - 1))
+    }
+
+    /// An entry, argument or bracketed group, up to the `,` or closing
+    /// bracket that ends it.
+    fn expression(&mut self) -> Result<Value, ArrayError> {
+        if let Some(value) = self.scalar_piece() {
+            return value;
+        }
+        let start = self.pos;
+        let mut value = self.sum()?;
+        loop {
+            let before = self.pos;
+            self.skip_whitespace();
+            if self.peek() != Some('|') || self.chars.get(self.pos + 1) != Some(&'>') {
+                self.pos = before;
+                return Ok(value);
+            }
+            let at = self.pos;
+            self.pos += 2;
+            self.skip_whitespace();
+            let name_start = self.pos;
+            while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                self.pos += 1;
+            }
+            if self.pos == name_start || self.chars[name_start].is_ascii_digit() {
+                return Err(ArrayError::at(
+                    "Expected a function name after '|>'",
+                    at,
+                    at + 2,
+                ));
+            }
+            let name: String = self.chars[name_start..self.pos].iter().collect();
+            let args = vec![(value, (start, at))];
+            let name_span = (name_start, self.pos);
+            value = if ARRAY_FUNCTIONS.iter().any(|(n, _)| *n == name) {
+                apply(&name, args, name_span)?
+            } else {
+                self.scalar_call(&name, args, name_span)?
+            };
+        }
+    }
+
+    /// The rest of the current expression, through the calculator whole,
+    /// when it holds no arrays.
+    fn scalar_piece(&mut self) -> Option<Result<Value, ArrayError>> {
+        let start = self.pos;
+        let mut depth = 0;
+        let end = (start..self.chars.len())
+            .find(|&i| match self.chars[i] {
+                '(' | '[' => {
+                    depth += 1;
+                    false
+                }
+                ')' | ']' if depth == 0 => true,
+                ')' | ']' => {
+                    depth -= 1;
+                    false
+                }
+                ',' => depth == 0,
+                _ => false,
+            })
+            .unwrap_or(self.chars.len());
+        let text: String = self.chars[start..end].iter().collect();
+        if text.trim().is_empty() || is_array_expression(&text, self.arrays) {
+            return None;
+        }
+        self.pos = end;
+        Some(self.scalar(start, end))
+    }
+
+    fn sum(&mut self) -> Result<Value, ArrayError> {
+        let mut left = self.term()?;
+        while let Some((op, at)) = self.operator(&['+', '-']) {
+            let right = self.term()?;
+            left = add(op, left, right, at)?;
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Value, ArrayError> {
+        let mut left = self.unary()?;
+        loop {
+            let before = self.pos;
+            if let Some((op, at)) = self.operator(&['*', '/', '.']) {
+                let right = self.unary()?;
+                left = match op {
+                    '*' => multiply(left, right, at)?,
+                    '/' => divide(left, right, at)?,
+                    _ => dot(left, right, (at, at + 1))?,
+                };
+                continue;
+            }
+            // A name, bracket or parenthesis right against a value
+            // multiplies it, as in `2m` or `2(m + n)`. Two rows side by
+            // side are a missing comma, not a product.
+            self.pos = before;
+            let previous = self.chars[before - 1];
+            match self.peek() {
+                _ if previous.is_whitespace() => return Ok(left),
+                Some(c) if c.is_alphabetic() || c == '(' || (c == '[' && previous != ']') => {
+                    let right = self.unary()?;
+                    left = multiply(left, right, before)?;
+                }
+                _ => return Ok(left),
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Value, ArrayError> {
+        if self.operator(&['-']).is_some() {
+            return Ok(scale(self.unary()?, -1.0));
+        }
+        let base = self.atom()?;
+        match self.operator(&['^']) {
+            Some((_, at)) => {
+                let exponent = self.unary()?;
+                power(base, exponent, at)
+            }
+            None => Ok(base),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Value, ArrayError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            Some('[') => self.literal(),
+            Some('(') => {
+                self.pos += 1;
+                let value = self.expression()?;
+                self.expect(')', start)?;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    self.pos += 1;
+                }
+                self.scalar(start, self.pos)
+            }
+            Some(c) if c.is_alphabetic() => {
+                while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                let name_end = self.pos;
+                self.skip_whitespace();
+                if self.peek() == Some('(') {
+                    self.call(&name, start, name_end)
+                } else {
+                    self.pos = name_end;
+                    match lookup(self.arrays, &name) {
+                        Some(value) => Ok(value.clone()),
+                        None => self.scalar(start, name_end),
+                    }
+                }
+            }
+            Some(c) => Err(ArrayError::at(
+                format!("Unexpected '{c}'"),
+                start,
+                start + 1,
+            )),
+            None => Err(ArrayError::at("Expected a value", start, start + 1)),
+        }
+    }
+
+    fn expect(&mut self, close: char, open: usize) -> Result<(), ArrayError> {
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(ArrayError::at(format!("Missing '{close}'"), open, open + 1))
+        }
+    }
+
+    /// `chars[start..end]` through the calculator.
+    fn scalar(&self, start: usize, end: usize) -> Result<Value, ArrayError> {
+        let text: String = self.chars[start..end].iter().collect();
+        calculate_with(&text, self.defs)
+            .map(|v| Value::Number(f64::from(v)))
+            .map_err(|e| ArrayError::Equation(e.offset(start)))
+    }
+
+    /// `[a, b, ...]`: a vector of numbers, or a matrix whose rows are
+    /// vectors of one length.
+    fn literal(&mut self) -> Result<Value, ArrayError> {
+        let open = self.pos;
+        self.pos += 1;
+        let mut entries = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            let value = self.expression()?;
+            entries.push((value, start, self.pos));
+            match self.operator(&[',', ']']) {
+                Some((',', _)) => continue,
+                Some(_) => break,
+                None if self.peek().is_none() => {
+                    return Err(ArrayError::at("Missing ']'", open, open + 1))
+                }
+                None => {
+                    return Err(ArrayError::at(
+                        "Expected ',' or ']'",
+                        self.pos,
+                        self.pos + 1,
+                    ))
+                }
+            }
+        }
+
+        match &entries[0].0 {
+            Value::Number(_) => {
+                let mut v = Vec::with_capacity(entries.len());
+                for (value, start, end) in entries {
+                    match value {
+                        Value::Number(n) => v.push(n),
+                        other => {
+                            return Err(ArrayError::at(
+                                format!(
+                                    "Expected a number like the first entry, not {}",
+                                    other.kind()
+                                ),
+                                start,
+                                end,
+                            ))
+                        }
+                    }
+                }
+                Ok(Value::Vector(v))
+            }
+            Value::Vector(first) => {
+                let width = first.len();
+                let mut m = Vec::with_capacity(entries.len());
+                for (i, (value, start, end)) in entries.into_iter().enumerate() {
+                    match value {
+                        Value::Vector(row) if row.len() == width => m.push(row),
+                        Value::Vector(row) => {
+                            return Err(ArrayError::at(
+                                format!(
+                                    "Row {} has {} entries but row 1 has {width}",
+                                    i + 1,
+                                    row.len()
+                                ),
+                                start,
+                                end,
+                            ))
+                        }
+                        other => {
+                            return Err(ArrayError::at(
+                                format!(
+                                    "Expected a row like the first entry, not {}",
+                                    other.kind()
+                                ),
+                                start,
+                                end,
+                            ))
+                        }
+                    }
+                }
+                Ok(Value::Matrix(m))
+            }
+            Value::Matrix(_) => {
+                let (_, start, end) = entries[0];
+                Err(ArrayError::at(
+                    "A matrix's rows must be vectors, not matrices",
+                    start,
+                    end,
+                ))
+            }
+        }
+    }
+
+    /// `name(...)`: an array function, or a calculator call. A calculator
+    /// call with no arrays in it goes over whole, so its errors point
+    /// inside it; otherwise its arguments are worked out here first and
+    /// must all come out as numbers.
+    fn call(&mut self, name: &str, start: usize, name_end: usize) -> Result<Value, ArrayError> {
+        let is_array_function = ARRAY_FUNCTIONS.iter().any(|(n, _)| *n == name);
+        if !is_array_function {
+            let mut depth = 0;
+            let close = (self.pos..self.chars.len()).find(|&i| {
+                match self.chars[i] {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            });
+            let end = close.map_or(self.chars.len(), |c| c + 1);
+            let inside: String = self.chars[self.pos..end].iter().collect();
+            if !is_array_expression(&inside, self.arrays) {
+                self.pos = end;
+                return self.scalar(start, end);
+            }
+        }
+
+        let open = self.pos;
+        self.pos += 1;
+        let mut args = Vec::new();
+        self.skip_whitespace();
+        if self.peek() != Some(')') {
+            loop {
+                self.skip_whitespace();
+                let arg_start = self.pos;
+                let value = self.expression()?;
+                args.push((value, (arg_start, self.pos)));
+                if self.operator(&[',']).is_none() {
+                    break;
+                }
+            }
+        }
+        self.expect(')', open)?;
+        if is_array_function {
+            apply(name, args, (start, name_end))
+        } else {
+            self.scalar_call(name, args, (start, name_end))
+        }
+    }
+
+    /// A calculator function applied to arguments already evaluated.
+    fn scalar_call(
+        &self,
+        name: &str,
+        args: Vec<Arg>,
+        name_span: (usize, usize),
+    ) -> Result<Value, ArrayError> {
+        let mut numbers = Vec::with_capacity(args.len());
+        for (value, (start, end)) in args {
+            match value {
+                Value::Number(n) => numbers.push(n.to_string()),
+                other => {
+                    return Err(ArrayError::at(
+                        format!("{name} takes numbers, not {}", other.kind()),
+                        start,
+                        end,
+                    ))
+                }
+            }
+        }
+        // The text is made up here, so its spans mean nothing on the line.
+        calculate_with(&format!("{name}({})", numbers.join(", ")), self.defs)
+            .map(|v| Value::Number(f64::from(v)))
+            .map_err(|e| ArrayError::at(e.message, name_span.0, name_span.1))
+    }
+}
+
+type Arg = (Value, (usize, usize));
+
+fn apply(name: &str, args: Vec<Arg>, name_span: (usize, usize)) -> Result<Value, ArrayError> {
+    let wanted = if matches!(name, "dot" | "cross" | "solve") {
+        2
+    } else {
+        1
+    };
+    if args.len() != wanted {
+        return Err(ArrayError::at(
+            format!(
+                "{name} takes {wanted} argument{} but was given {}",
+                if wanted == 1 { "" } else { "s" },
+                args.len()
+            ),
+            name_span.0,
+            name_span.1,
+        ));
+    }
+    let mut args = args.into_iter();
+    let (first, first_span) = args.next().unwrap_or((Value::Number(0.0), name_span));
+    let at = |message: String| ArrayError::at(message, first_span.0, first_span.1);
+    let needs_matrix = |v: &Value| at(format!("{name} needs a matrix, not {}", v.kind()));
+
+    match (name, first) {
+        ("det", Value::Matrix(m)) => matrix::determinant(&m).map(Value::Number).map_err(at),
+        ("inv", Value::Matrix(m)) => matrix::inverse(&m).map(Value::Matrix).map_err(at),
+        ("trace", Value::Matrix(m)) => matrix::trace(&m).map(Value::Number).map_err(at),
+        ("rank", Value::Matrix(m)) => Ok(Value::Number(matrix::rank(&m) as f64)),
+        ("transpose", Value::Matrix(m)) => Ok(Value::Matrix(matrix::transpose(&m))),
+        ("transpose", Value::Vector(v)) => {
+            Ok(Value::Matrix(v.into_iter().map(|x| vec![x]).collect()))
+        }
+        ("norm", Value::Vector(v)) => {
+            Ok(Value::Number(v.iter().map(|x| x * x).sum::<f64>().sqrt()))
+        }
+        ("norm", Value::Matrix(m)) => Ok(Value::Number(
+            m.iter().flatten().map(|x| x * x).sum::<f64>().sqrt(),
+        )),
+        ("dot", a) => {
+            let (b, b_span) = args.next().unwrap_or((Value::Number(0.0), name_span));
+            dot(a, b, (first_span.0, b_span.1))
+        }
+        ("cross", Value::Vector(a)) => {
+            let (b, b_span) = args.next().unwrap_or((Value::Number(0.0), name_span));
+            match b {
+                Value::Vector(b) if a.len() == 3 && b.len() == 3 => Ok(Value::Vector(vec![
+                    a[1] * b[2] - a[2] * b[1],
+                    a[2] * b[0] - a[0] * b[2],
+                    a[0] * b[1] - a[1] * b[0],
+                ])),
+                b => Err(ArrayError::at(
+                    format!(
+                        "cross needs two 3-vectors, not {} and {}",
+                        Value::Vector(a).kind(),
+                        b.kind()
+                    ),
+                    first_span.0,
+                    b_span.1,
+                )),
+            }
+        }
+        ("solve", Value::Matrix(m)) => {
+            let (b, b_span) = args.next().unwrap_or((Value::Number(0.0), name_span));
+            match b {
+                Value::Vector(b) => matrix::solve(&m, &b)
+                    .map(Value::Vector)
+                    .map_err(|e| ArrayError::at(e, first_span.0, b_span.1)),
+                other => Err(ArrayError::at(
+                    format!("solve needs a vector b, not {}", other.kind()),
+                    b_span.0,
+                    b_span.1,
+                )),
+            }
+        }
+        ("norm" | "cross", v) => Err(at(format!("{name} needs a vector, not {}", v.kind()))),
+        (_, v) => Err(needs_matrix(&v)),
+    }
+}
+
+fn add(op: char, left: Value, right: Value, at: usize) -> Result<Value, ArrayError> {
+    let sign = if op == '+' { 1.0 } else { -1.0 };
+    let combine =
+        |a: &[f64], b: &[f64]| -> Vec<f64> { a.iter().zip(b).map(|(x, y)| x + sign * y).collect() };
+    match (&left, &right) {
+        (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a + sign * b)),
+        (Value::Vector(a), Value::Vector(b)) if a.len() == b.len() => {
+            Ok(Value::Vector(combine(a, b)))
+        }
+        (Value::Matrix(a), Value::Matrix(b)) if matrix::shape(a) == matrix::shape(b) => Ok(
+            Value::Matrix(a.iter().zip(b).map(|(x, y)| combine(x, y)).collect()),
+        ),
+        _ => Err(ArrayError::at(
+            format!(
+                "Can't {} {} and {}",
+                if op == '+' { "add" } else { "subtract" },
+                left.kind(),
+                right.kind()
+            ),
+            at,
+            at + 1,
+        )),
+    }
+}
+
+fn scale(value: Value, by: f64) -> Value {
+    match value {
+        Value::Number(n) => Value::Number(n * by),
+        Value::Vector(v) => Value::Vector(v.iter().map(|x| x * by).collect()),
+        Value::Matrix(m) => Value::Matrix(
+            m.iter()
+                .map(|row| row.iter().map(|x| x * by).collect())
+                .collect(),
+        ),
+    }
+}
+
+fn multiply(left: Value, right: Value, at: usize) -> Result<Value, ArrayError> {
+    let error = |message: String| ArrayError::at(message, at, at + 1);
+    match (left, right) {
+        (Value::Number(n), v) | (v, Value::Number(n)) => Ok(scale(v, n)),
+        (Value::Matrix(a), Value::Matrix(b)) => {
+            matrix::multiply(&a, &b).map(Value::Matrix).map_err(error)
+        }
+        // A vector on the right is a column, on the left a row.
+        (Value::Matrix(a), Value::Vector(v)) => {
+            let column = v.iter().map(|&x| vec![x]).collect();
+            matrix::multiply(&a, &column)
+                .map(|m| Value::Vector(m.concat()))
+                .map_err(error)
+        }
+        (Value::Vector(v), Value::Matrix(b)) => matrix::multiply(&vec![v], &b)
+            .map(|m| Value::Vector(m.concat()))
+            .map_err(error),
+        (Value::Vector(_), Value::Vector(_)) => Err(error(
+            "Use . for the dot product of two vectors, or cross(a, b)".to_string(),
+        )),
+    }
+}
+
+fn divide(left: Value, right: Value, at: usize) -> Result<Value, ArrayError> {
+    match right {
+        Value::Number(n) => Ok(scale(left, 1.0 / n)),
+        other => Err(ArrayError::at(
+            format!("Can't divide by {}", other.kind()),
+            at,
+            at + 1,
+        )),
+    }
+}
+
+fn dot(left: Value, right: Value, span: (usize, usize)) -> Result<Value, ArrayError> {
+    match (&left, &right) {
+        (Value::Vector(a), Value::Vector(b)) if a.len() == b.len() => {
+            Ok(Value::Number(a.iter().zip(b).map(|(x, y)| x * y).sum()))
+        }
+        _ => Err(ArrayError::at(
+            format!(
+                "The dot product needs two vectors of one length, not {} and {}",
+                left.kind(),
+                right.kind()
+            ),
+            span.0,
+            span.1,
+        )),
+    }
+}
+
+/// Numbers to any power; square matrices to whole-number powers, negative
+/// ones through the inverse.
+fn power(base: Value, exponent: Value, at: usize) -> Result<Value, ArrayError> {
+    let error = |message: String| ArrayError::at(message, at, at + 1);
+    match (base, exponent) {
+        (Value::Number(b), Value::Number(e)) => Ok(Value::Number(b.powf(e))),
+        (Value::Matrix(m), Value::Number(e)) if e.fract() == 0.0 => {
+            let (rows, cols) = matrix::shape(&m);
+            if rows != cols {
+                return Err(error(format!(
+                    "Only a square matrix has powers, not a {rows}×{cols} one"
+                )));
+            }
+            if e.abs() > MAX_MATRIX_POWER {
+                return Err(error(format!("Matrix powers go up to {MAX_MATRIX_POWER}")));
+            }
+            let base = if e < 0.0 {
+                matrix::inverse(&m).map_err(error)?
+            } else {
+                m
+            };
+            let mut result = matrix::identity(rows);
+            for _ in 0..e.abs() as usize {
+                result = matrix::multiply(&result, &base).map_err(error)?;
+            }
+            Ok(Value::Matrix(result))
+        }
+        (base, exponent) => Err(error(format!(
+            "Can't raise {} to the power of {}",
+            base.kind(),
+            exponent.kind()
+        ))),
+    }
+}
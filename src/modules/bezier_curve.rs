@@ -1,125 +1,351 @@
+use rusty_maths::equation_analyzer::Definitions;
+
 use super::{
-    common::{get_braille, make_cell_matrix, CellMatrix, CharMatrix, GraphOptions, Point},
+    common::{GraphOptions, Point},
+    error_render,
+    graphing::{graph_with_overlay, Overlay, Plot},
+    inputs::get_optional_numerical_input,
     logger::Logger,
-    string_maker::make_curve_string,
 };
 
-const STEP_SIZE: f32 = 0.001;
-
-// Helper function to add control point markers with offset
-fn add_control_point_markers(
-    points: &mut Vec<Point>,
-    control_points: &[Point],
-    width: f32,
-    height: f32,
-) {
-    let offsets = [-1.0, 1.0];
-    for control_point in control_points {
-        for &ox in &offsets {
-            for &oy in &offsets {
-                let new_x = (control_point.x + ox).clamp(0.0, width - 1.0);
-                let new_y = (control_point.y + oy).clamp(0.0, height - 1.0);
-                points.push(Point::new(new_x, new_y));
-            }
+/// How far, in braille dots, a drawn line may stray from the true curve.
+/// A quarter dot is below what the eye can pick out.
+const FLATNESS: f32 = 0.25;
+/// Arc length is measured to this fraction of the curve's size.
+const LENGTH_FLATNESS: f32 = 1e-5;
+/// Halvings allowed per segment; 2^16 lines is far beyond any screen.
+const MAX_DEPTH: u32 = 16;
+
+/// Graphs `spline` in graph coordinates, with its control polygon and
+/// control points, over any `|`-separated equations in `eq`. Prints the
+/// graph and the curve's arc length.
+pub(crate) fn draw_bezier(
+    spline: &Spline,
+    eq: &str,
+    go: &GraphOptions,
+    defs: &Definitions,
+    l: &mut impl Logger,
+) -> Option<(String, Plot)> {
+    let (x_min, x_max) = x_window(&spline.points);
+    let curve = flatten_for_graph(spline, (x_min, x_max), go);
+    let overlay = Overlay {
+        paths: vec![
+            ("bezier".to_string(), curve),
+            ("control polygon".to_string(), spline.points.clone()),
+        ],
+        markers: spline.points.clone(),
+        dots: vec![],
+    };
+
+    match graph_with_overlay(eq, x_min, x_max, go, defs, &overlay) {
+        Ok((g, plot)) => {
+            l.print(&g);
+            l.print(&format!("arc length: {}", round4(spline.arc_length())));
+            Some((g, plot))
+        }
+        Err(e) => {
+            l.eprint(&error_render::render_error_with_source(eq, &e, defs));
+            None
         }
-        points.push(control_point.clone());
     }
 }
 
-// Helper function to render curve points to matrix
-fn render_curve(points: Vec<Point>, go: &GraphOptions, l: &mut impl Logger) {
-    let mut matrix: CellMatrix = make_cell_matrix(go);
+/// The spline flattened finely enough for a graph of `go`'s size. The
+/// curve stays inside its control points' hull, which the graph always
+/// shows whole, so that hull's height bounds the dots per unit of y.
+pub(crate) fn flatten_for_graph(
+    spline: &Spline,
+    (x_min, x_max): (f32, f32),
+    go: &GraphOptions,
+) -> Vec<Point> {
+    let (y_lo, y_hi) = if go.y_fixed {
+        (go.y_min, go.y_max)
+    } else {
+        let ys = spline.points.iter().map(|p| p.y);
+        (
+            ys.clone().fold(f32::MAX, f32::min),
+            ys.fold(f32::MIN, f32::max),
+        )
+    };
+    let x_dots = go.width as f32 / (x_max - x_min);
+    let y_dots = go.height as f32 / (y_hi - y_lo).max(f32::EPSILON);
 
-    for p in points {
-        if let Some(col) = matrix.get_mut(p.y as usize) {
-            if let Some(cell) = col.get_mut(p.x as usize) {
-                cell.value = true;
-            }
+    let mut curve = Vec::new();
+    for segment in spline.segments() {
+        let in_dots: Vec<Point> = segment
+            .iter()
+            .map(|p| Point::new(p.x * x_dots, p.y * y_dots))
+            .collect();
+        let flat = flatten(&in_dots, FLATNESS);
+        // Segments share endpoints; keep one copy of each.
+        let skip = usize::from(!curve.is_empty());
+        curve.extend(
+            flat.iter()
+                .skip(skip)
+                .map(|p| Point::new(p.x / x_dots, p.y / y_dots)),
+        );
+    }
+    curve
+}
+
+/// The control points' x extent with a margin, so markers at the edges
+/// stay clear of the frame.
+pub(crate) fn x_window(points: &[Point]) -> (f32, f32) {
+    let lo = points.iter().map(|p| p.x).fold(f32::MAX, f32::min);
+    let hi = points.iter().map(|p| p.x).fold(f32::MIN, f32::max);
+    let pad = ((hi - lo) * 0.1).max(0.5);
+    (lo - pad, hi + pad)
+}
+
+/// Control points typed as numbers in x, y order, with any commas,
+/// brackets or parentheses between them: `0 0, 1 2` and the editor's
+/// `[(0, 0), (1, 2)]` both work.
+pub(crate) fn parse_points(s: &str) -> Result<Vec<Point>, String> {
+    let numbers = s
+        .split(|c: char| c.is_whitespace() || ",()[]".contains(c))
+        .filter(|n| !n.is_empty())
+        .map(|n| {
+            n.parse::<f32>()
+                .map_err(|_| format!("'{n}' is not a number"))
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+    if !numbers.len().is_multiple_of(2) {
+        return Err("Each point needs an x and a y".to_string());
+    }
+    let points: Vec<Point> = numbers.chunks(2).map(|c| Point::new(c[0], c[1])).collect();
+    if points.len() < 2 {
+        return Err("A curve needs at least two points".to_string());
+    }
+    Ok(points)
+}
+
+/// Asks for the degree of a spline through `gaps + 1` points; it must
+/// divide the gaps evenly. Blank makes one curve through them all.
+pub(crate) fn ask_degree(gaps: usize, l: &mut impl Logger) -> usize {
+    loop {
+        match get_optional_numerical_input::<usize>(
+            &format!("degree (blank = {gaps}, one curve): "),
+            l,
+        ) {
+            Some(d) if d > 0 && gaps.is_multiple_of(d) => return d,
+            Some(d) => l.eprint(&format!(
+                "degree `{d}` must divide the {gaps} gaps between the points"
+            )),
+            None => return gaps,
+        }
+    }
+}
+
+/// Asks for t values until a blank answer, printing the point at each.
+pub(crate) fn query_points(spline: &Spline, l: &mut impl Logger) {
+    while let Some(t) = get_optional_numerical_input::<f32>("point at t (0-1, blank = done): ", l) {
+        if !(0.0..=1.0).contains(&t) {
+            l.eprint(&format!("t `{t}` must be from 0 to 1"));
+            continue;
         }
+        let p = spline.point_at(t);
+        l.print(&format!("t = {t}: ({}, {})", round4(p.x), round4(p.y)));
     }
+}
 
-    let braille_chars: CharMatrix = get_braille(go, &matrix);
-    l.print(&make_curve_string(
-        braille_chars,
-        0.0,
-        go.width as f32,
-        0.0,
-        go.height as f32,
-    ));
+/// `v` to four decimal places, without trailing zeros.
+pub(crate) fn round4(v: f32) -> f32 {
+    (v * 1e4).round() / 1e4
 }
 
-pub(crate) fn quadratic_bezier(
-    p1: Point,
-    p2: Point,
-    p3: Point,
-    go: &GraphOptions,
-    l: &mut impl Logger,
-) {
-    let width = go.width as f32;
-    let height = go.height as f32;
+fn interpolate(start: f32, end: f32, t: f32) -> f32 {
+    start + t * (end - start)
+}
 
-    let mut points = vec![p1.clone()];
-    add_control_point_markers(&mut points, std::slice::from_ref(&p2), width, height);
-    points.push(p3.clone());
+/// The point at `t` (0..=1) on the Bezier curve with these control points,
+/// of any degree, by de Casteljau's repeated interpolation.
+pub(crate) fn de_casteljau(control: &[Point], t: f32) -> Point {
+    let mut pts: Vec<Point> = control.to_vec();
+    while pts.len() > 1 {
+        pts = pts
+            .windows(2)
+            .map(|w| {
+                Point::new(
+                    interpolate(w[0].x, w[1].x, t),
+                    interpolate(w[0].y, w[1].y, t),
+                )
+            })
+            .collect();
+    }
+    pts.pop().unwrap_or_else(|| Point::new(0.0, 0.0))
+}
 
-    let mut n = 0.0;
-    while n <= 1.01 {
-        let a_x = interpolate(p1.x, p2.x, n);
-        let a_y = interpolate(p1.y, p2.y, n);
-        let b_x = interpolate(p2.x, p3.x, n);
-        let b_y = interpolate(p2.y, p3.y, n);
-        let z_x = interpolate(a_x, b_x, n);
-        let z_y = interpolate(a_y, b_y, n);
+/// The curve as a polyline that never strays more than `tolerance` from
+/// it. Pieces are halved by de Casteljau until their control points all
+/// lie within `tolerance` of the chord; the curve stays inside its control
+/// points' hull, so the chord is then as close as that.
+pub(crate) fn flatten(control: &[Point], tolerance: f32) -> Vec<Point> {
+    let Some(first) = control.first() else {
+        return vec![];
+    };
+    let mut line = vec![first.clone()];
+    subdivide(control, tolerance, MAX_DEPTH, &mut line);
+    line
+}
 
-        points.push(Point::new(z_x, z_y));
-        n += STEP_SIZE;
+fn subdivide(control: &[Point], tolerance: f32, depth: u32, line: &mut Vec<Point>) {
+    if depth == 0 || is_flat(control, tolerance) {
+        line.extend(control.last().cloned());
+        return;
     }
+    let (left, right) = split(control);
+    subdivide(&left, tolerance, depth - 1, line);
+    subdivide(&right, tolerance, depth - 1, line);
+}
 
-    render_curve(points, go, l);
+fn is_flat(control: &[Point], tolerance: f32) -> bool {
+    match control {
+        [a, inner @ .., b] => inner
+            .iter()
+            .all(|p| distance_to_segment(p, a, b) <= tolerance),
+        _ => true,
+    }
 }
 
-pub(crate) fn cubic_bezier(
-    p1: Point,
-    p2: Point,
-    p3: Point,
-    p4: Point,
-    go: &GraphOptions,
-    l: &mut impl Logger,
-) {
-    let width = go.width as f32;
-    let height = go.height as f32;
+/// The control points of the two halves of the curve, split at t = 0.5.
+fn split(control: &[Point]) -> (Vec<Point>, Vec<Point>) {
+    let mut left = Vec::with_capacity(control.len());
+    let mut right = Vec::with_capacity(control.len());
+    let mut pts = control.to_vec();
+    while let (Some(first), Some(last)) = (pts.first(), pts.last()) {
+        left.push(first.clone());
+        right.push(last.clone());
+        pts = pts
+            .windows(2)
+            .map(|w| {
+                Point::new(
+                    interpolate(w[0].x, w[1].x, 0.5),
+                    interpolate(w[0].y, w[1].y, 0.5),
+                )
+            })
+            .collect();
+    }
+    right.reverse();
+    (left, right)
+}
 
-    let mut points = vec![p1.clone()];
-    add_control_point_markers(&mut points, &[p2.clone(), p3.clone()], width, height);
-    points.push(p4.clone());
+fn distance_to_segment(p: &Point, a: &Point, b: &Point) -> f32 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq == 0.0 {
+        0.0
+    } else {
+        (((p.x - a.x) * dx + (p.y - a.y) * dy) / length_sq).clamp(0.0, 1.0)
+    };
+    (p.x - (a.x + t * dx)).hypot(p.y - (a.y + t * dy))
+}
 
-    let mut n = 0.0;
-    while n <= 1.01 {
-        let a_x = interpolate(p1.x, p2.x, n);
-        let a_y = interpolate(p1.y, p2.y, n);
+/// A composite curve: consecutive Bezier segments of one degree, each
+/// starting where the last ended. `points` holds `degree * segments + 1`
+/// control points, the shared endpoints appearing once.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Spline {
+    pub degree: usize,
+    pub points: Vec<Point>,
+}
 
-        let b_x = interpolate(p2.x, p3.x, n);
-        let b_y = interpolate(p2.y, p3.y, n);
+impl Spline {
+    /// `segments` segments laid out left to right across the x and y
+    /// ranges, control points zigzagging so the shape is easy to grab.
+    pub(crate) fn spread(
+        degree: usize,
+        segments: usize,
+        (x_min, x_max): (f32, f32),
+        (y_min, y_max): (f32, f32),
+    ) -> Self {
+        let n = degree * segments + 1;
+        let width = x_max - x_min;
+        let margin = width * 0.1;
+        let points = (0..n)
+            .map(|i| {
+                let x = x_min + margin + (width - 2.0 * margin) * i as f32 / (n - 1) as f32;
+                let y = y_min + if i.is_multiple_of(2) { 0.3 } else { 0.7 } * (y_max - y_min);
+                Point::new(round4(x), round4(y))
+            })
+            .collect();
+        Spline { degree, points }
+    }
 
-        let c_x = interpolate(p3.x, p4.x, n);
-        let c_y = interpolate(p3.y, p4.y, n);
+    pub(crate) fn segment_count(&self) -> usize {
+        (self.points.len() - 1) / self.degree
+    }
 
-        let d_x = interpolate(a_x, b_x, n);
-        let d_y = interpolate(a_y, b_y, n);
+    /// The point `t` (0..=1) of the way along the whole spline, each
+    /// segment taking an equal share of t.
+    pub(crate) fn point_at(&self, t: f32) -> Point {
+        let n = self.segment_count();
+        let along = t.clamp(0.0, 1.0) * n as f32;
+        let i = (along as usize).min(n.saturating_sub(1));
+        match self.segments().nth(i) {
+            Some(segment) => de_casteljau(segment, along - i as f32),
+            None => self.points.first().cloned().unwrap_or(Point::new(0.0, 0.0)),
+        }
+    }
 
-        let e_x = interpolate(b_x, c_x, n);
-        let e_y = interpolate(b_y, c_y, n);
+    /// Length along the curve, summed over a polyline flattened to a tiny
+    /// fraction of each segment's size.
+    pub(crate) fn arc_length(&self) -> f32 {
+        self.segments()
+            .map(|segment| {
+                let start = &segment[0];
+                let size = segment
+                    .iter()
+                    .map(|p| (p.x - start.x).hypot(p.y - start.y))
+                    .fold(0.0, f32::max);
+                flatten(segment, size * LENGTH_FLATNESS)
+                    .windows(2)
+                    .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+                    .sum::<f32>()
+            })
+            .sum()
+    }
 
-        let z_x = interpolate(d_x, e_x, n);
-        let z_y = interpolate(d_y, e_y, n);
-        points.push(Point::new(z_x, z_y));
+    /// Each segment's control points, endpoints included.
+    pub(crate) fn segments(&self) -> impl Iterator<Item = &[Point]> {
+        (0..self.segment_count())
+            .map(move |i| &self.points[i * self.degree..=(i + 1) * self.degree])
+    }
 
-        n += STEP_SIZE;
+    /// Appends a segment continuing from the last point, clamped to the
+    /// x and y ranges.
+    pub(crate) fn add_segment(&mut self, (x_min, x_max): (f32, f32), (y_min, y_max): (f32, f32)) {
+        let (last, before) = match self.points.as_slice() {
+            [.., a, b] => (b.clone(), a.clone()),
+            _ => return,
+        };
+        // Carry on in the direction the last control leg points
+        let (dx, dy) = (last.x - before.x, last.y - before.y);
+        for i in 1..=self.degree {
+            let k = i as f32;
+            self.points.push(Point::new(
+                round4((last.x + dx * k).clamp(x_min, x_max)),
+                round4(
+                    (last.y + dy * k * if i.is_multiple_of(2) { 1.0 } else { -1.0 })
+                        .clamp(y_min, y_max),
+                ),
+            ));
+        }
     }
 
-    render_curve(points, go, l);
-}
+    /// Drops the last segment; a spline keeps at least one.
+    pub(crate) fn remove_segment(&mut self) {
+        if self.segment_count() > 1 {
+            self.points.truncate(self.points.len() - self.degree);
+        }
+    }
 
-fn interpolate(start: f32, end: f32, t: f32) -> f32 {
-    start + t * (end - start)
+    /// The control points as `[(x, y), ...]`, ready to copy.
+    pub(crate) fn format_points(&self) -> String {
+        let pts: Vec<String> = self
+            .points
+            .iter()
+            .map(|p| format!("({}, {})", p.x, p.y))
+            .collect();
+        format!("[{}]", pts.join(", "))
+    }
 }
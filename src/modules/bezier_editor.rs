@@ -0,0 +1,201 @@
+//! The Bezier editor: drag control points around with the keyboard while
+//! the curve redraws. Any degree works, and a curve can be several
+//! segments joined end to end. Points are in graph coordinates, y up, as
+//! in `:bezier`: a curve typed there can be edited here, and the control
+//! points printed on the way out can be pasted back.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use rusty_maths::equation_analyzer::Definitions;
+
+use crate::modules::{
+    bezier_curve::{ask_degree, flatten_for_graph, parse_points, round4, x_window, Spline},
+    commands::open_viewer,
+    common::{GraphOptions, Point, Scale},
+    graphing::{graph_with_overlay, Overlay, Plot},
+    inputs::{get_optional_numerical_input, read_user_input},
+    logger::Logger,
+    viewer::ViewerEvent,
+};
+
+const DEFAULT_DEGREE: usize = 3;
+const MAX_DEGREE: usize = 10;
+const MAX_SEGMENTS: usize = 20;
+/// Where a new curve is laid out
+const DEFAULT_X: (f32, f32) = (-10.0, 10.0);
+const DEFAULT_Y: (f32, f32) = (-5.0, 5.0);
+/// Dots a shifted arrow moves a point.
+const BIG_STEP: f32 = 10.0;
+/// Half the side of the square around the selected point, in dots
+const RING_DOTS: f32 = 2.0;
+
+const HELP: &[(&str, &str)] = &[
+    ("tab / shift-tab", "select the next / previous point"),
+    ("arrows", "move the point a dot (shift: 10 dots)"),
+    ("a", "add a segment"),
+    ("x", "remove the last segment"),
+];
+
+/// The x and y ranges the editor shows. They stay put while points move,
+/// and points are kept inside them.
+pub(crate) type Window = ((f32, f32), (f32, f32));
+
+/// Runs the editor and returns the graph it was showing when closed.
+pub(crate) fn bezier_editor(l: &mut impl Logger, go: &GraphOptions) -> Option<(String, Plot)> {
+    let (mut spline, window) = starting_spline(l)?;
+
+    let mut viewer = open_viewer(HELP, l)?;
+    let mut selected = 0;
+
+    let last = loop {
+        for p in &mut spline.points {
+            *p = clamp_point(p, window);
+        }
+        let view = viewer.fit(go);
+        let Some((frame, plot)) = editor_frame(&spline, selected, window, &view) else {
+            break None;
+        };
+        let p = &spline.points[selected];
+        let status = format!(
+            "degree {}   {} segment{}   point {}/{} ({}, {})",
+            spline.degree,
+            spline.segment_count(),
+            if spline.segment_count() == 1 { "" } else { "s" },
+            selected + 1,
+            spline.points.len(),
+            round4(p.x),
+            round4(p.y)
+        );
+        viewer.draw(&frame, &status);
+        // A step is one dot of the graph as drawn.
+        let layout = plot.layout;
+        let dot_x = (layout.x_range.1 - layout.x_range.0) / layout.width as f32;
+        let dot_y = (layout.y_range.1 - layout.y_range.0) / layout.height as f32;
+        let drawn = Some((frame, plot));
+
+        let k = match viewer.next_event(None) {
+            ViewerEvent::Quit => break drawn,
+            ViewerEvent::Key(k) => k,
+            ViewerEvent::Redraw | ViewerEvent::Tick | ViewerEvent::Mouse(_) => continue,
+        };
+        let step = if k.modifiers.contains(KeyModifiers::SHIFT) {
+            BIG_STEP
+        } else {
+            1.0
+        };
+        let count = spline.points.len();
+        let p = &mut spline.points[selected];
+        match k.code {
+            KeyCode::Tab => selected = (selected + 1) % count,
+            KeyCode::BackTab => selected = (selected + count - 1) % count,
+            KeyCode::Up => p.y = round4(p.y + step * dot_y),
+            KeyCode::Down => p.y = round4(p.y - step * dot_y),
+            KeyCode::Left => p.x = round4(p.x - step * dot_x),
+            KeyCode::Right => p.x = round4(p.x + step * dot_x),
+            KeyCode::Char('a') if spline.segment_count() < MAX_SEGMENTS => {
+                spline.add_segment(window.0, window.1)
+            }
+            KeyCode::Char('x') => {
+                spline.remove_segment();
+                selected = selected.min(spline.points.len() - 1);
+            }
+            _ => {}
+        }
+    };
+    drop(viewer);
+
+    l.print(&format!(
+        "Control points (degree {}, {} segment{}):",
+        spline.degree,
+        spline.segment_count(),
+        if spline.segment_count() == 1 { "" } else { "s" }
+    ));
+    l.print(&spline.format_points());
+    last
+}
+
+/// Typed control points and their degree, shown with a margin around
+/// them, or a new curve of a chosen degree and length in the default
+/// window.
+fn starting_spline(l: &mut impl Logger) -> Option<(Spline, Window)> {
+    loop {
+        let answer =
+            read_user_input("control points (x y, x y, ..., blank = a new curve): ").ok()?;
+        if answer.is_empty() {
+            break;
+        }
+        match parse_points(&answer) {
+            Ok(points) => {
+                let degree = ask_degree(points.len() - 1, l);
+                // x_window's margin, applied to y as well
+                let flipped: Vec<Point> = points.iter().map(|p| Point::new(p.y, p.x)).collect();
+                let window = (x_window(&points), x_window(&flipped));
+                return Some((Spline { degree, points }, window));
+            }
+            Err(e) => l.eprint(&e),
+        }
+    }
+
+    let degree = loop {
+        match get_optional_numerical_input::<usize>("degree (blank = 3): ", l) {
+            Some(d) if (1..=MAX_DEGREE).contains(&d) => break d,
+            Some(d) => l.eprint(&format!("degree `{d}` must be from 1 to {MAX_DEGREE}")),
+            None => break DEFAULT_DEGREE,
+        }
+    };
+    let segments = loop {
+        match get_optional_numerical_input::<usize>("segments (blank = 1): ", l) {
+            Some(s) if (1..=MAX_SEGMENTS).contains(&s) => break s,
+            Some(s) => l.eprint(&format!("segments `{s}` must be from 1 to {MAX_SEGMENTS}")),
+            None => break 1,
+        }
+    };
+    let spline = Spline::spread(degree, segments, DEFAULT_X, DEFAULT_Y);
+    Some((spline, (DEFAULT_X, DEFAULT_Y)))
+}
+
+/// The editor's graph: the spline, its control polygon and points, and a
+/// square around the `selected` point, over exactly `window`.
+pub(crate) fn editor_frame(
+    spline: &Spline,
+    selected: usize,
+    ((x_min, x_max), (y_min, y_max)): Window,
+    go: &GraphOptions,
+) -> Option<(String, Plot)> {
+    let go = GraphOptions {
+        y_min,
+        y_max,
+        y_fixed: true,
+        x_scale: Scale::Linear,
+        y_scale: Scale::Linear,
+        equal_aspect: false,
+        ..*go
+    };
+    let mut paths = vec![
+        (
+            "bezier".to_string(),
+            flatten_for_graph(spline, (x_min, x_max), &go),
+        ),
+        ("control polygon".to_string(), spline.points.clone()),
+    ];
+    if let Some(p) = spline.points.get(selected) {
+        let rx = RING_DOTS * (x_max - x_min) / go.width as f32;
+        let ry = RING_DOTS * (y_max - y_min) / go.height as f32;
+        let corners = [(-rx, -ry), (rx, -ry), (rx, ry), (-rx, ry), (-rx, -ry)];
+        let ring = corners
+            .iter()
+            .map(|(dx, dy)| Point::new(p.x + dx, p.y + dy))
+            .collect();
+        paths.push(("selected".to_string(), ring));
+    }
+    let overlay = Overlay {
+        paths,
+        markers: spline.points.clone(),
+        dots: vec![],
+    };
+    graph_with_overlay("", x_min, x_max, &go, &Definitions::new(), &overlay).ok()
+}
+
+/// `p` kept inside the window.
+pub(crate) fn clamp_point(p: &Point, ((x_min, x_max), (y_min, y_max)): Window) -> Point {
+    Point::new(p.x.clamp(x_min, x_max), p.y.clamp(y_min, y_max))
+}
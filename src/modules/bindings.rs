@@ -4,26 +4,35 @@
 //! the current bindings, so `let k = ans` works) and the *result* is
 //! stored. `let g(x) = 2x^2` binds a function — the body is stored as
 //! source and resolves late: it sees other bindings as they stand when the
-//! function is called.
+//! function is called. `let v = [1, 2, 3]` binds a vector (or matrix),
+//! evaluated eagerly like a value.
 //!
 //! Bindings persist to a file (one `let` line each, in definition order)
 //! that replays at startup. `ans` is a binding too — auto-maintained after
 //! every successful evaluation — but is never persisted and can't be bound
 //! or removed by hand.
 
-use rusty_maths::equation_analyzer::{calculator::calculate_with, Definition};
+use rusty_maths::equation_analyzer::{calculator::calculate_with, Definition, Definitions};
 use std::fmt::Write;
 use std::path::PathBuf;
 
-use crate::modules::{error_render, logger::Logger, repl::Repl};
+use crate::modules::{
+    arrays::{self, ArrayError, Value},
+    error_render,
+    logger::Logger,
+    repl::Repl,
+};
 
 /// How a `let` line reached us: typed at the prompt (echoed above, errors
-/// caret into it) or replayed from the persistence file (not on screen,
-/// errors reprint the line and never abort the rest of the file).
+/// caret into it), replayed from the persistence file (not on screen,
+/// errors reprint the line and never abort the rest of the file), or built
+/// by a command like `:fit` (saved like a typed line, but never seen, so
+/// errors reprint it).
 #[derive(Clone, Copy, PartialEq)]
 pub(crate) enum LetSource {
     Interactive,
     Replay,
+    Generated,
 }
 
 pub(crate) fn is_let_line(line: &str) -> bool {
@@ -57,7 +66,7 @@ pub(crate) fn handle_let(
 ) -> bool {
     match parse_and_define(line, repl) {
         Ok(notice) => {
-            if source == LetSource::Interactive {
+            if source != LetSource::Replay {
                 l.print(&notice);
                 save(repl, l);
             }
@@ -66,7 +75,9 @@ pub(crate) fn handle_let(
         Err(rendered) => {
             match source {
                 LetSource::Interactive => l.eprint(&rendered.render_at_prompt()),
-                LetSource::Replay => l.eprint(&rendered.render_with_source(line)),
+                LetSource::Replay | LetSource::Generated => {
+                    l.eprint(&rendered.render_with_source(line))
+                }
             }
             false
         }
@@ -212,6 +223,9 @@ fn parse_and_define(line: &str, repl: &mut Repl) -> Result<String, LetError> {
     if is_function {
         define_function(name, rhs_trimmed, rhs_offset, repl)?;
         Ok(notice(format!("{name}(x) = {rhs_trimmed}"), previous))
+    } else if arrays::is_array_expression(rhs_trimmed, &repl.arrays) {
+        let value = define_array(name, rhs_trimmed, rhs_offset, repl)?;
+        Ok(notice(format!("{name} = {value}"), previous))
     } else {
         let value = define_value(name, rhs_trimmed, rhs_offset, repl)?;
         Ok(notice(format!("{name} = {value}"), previous))
@@ -226,7 +240,10 @@ fn define_value(
 ) -> Result<f32, LetError> {
     let value =
         calculate_with(rhs, &repl.defs).map_err(|e| from_equation_error(e, rhs_offset, repl))?;
+    bind_number(name, value, repl)
+}
 
+fn bind_number(name: &str, value: f32, repl: &mut Repl) -> Result<f32, LetError> {
     if !value.is_finite() {
         return Err(LetError::plain(format!(
             "Cannot bind '{name}' to {value} — the expression must produce a finite number"
@@ -236,9 +253,49 @@ fn define_value(
     repl.defs
         .define_value(name, value)
         .map_err(|e| LetError::plain(e.message))?;
+    repl.unbind_array(name);
     Ok(value)
 }
 
+/// Binds an expression with vectors or matrices in it, returning the
+/// bound value as typed. One that works out to a number binds like any
+/// value.
+fn define_array(
+    name: &str,
+    rhs: &str,
+    rhs_offset: usize,
+    repl: &mut Repl,
+) -> Result<String, LetError> {
+    let value = arrays::evaluate_array(rhs, &repl.defs, &repl.arrays).map_err(|e| match e {
+        ArrayError::Equation(e) => from_equation_error(e, rhs_offset, repl),
+        ArrayError::Type {
+            message,
+            span: (start, end),
+        } => LetError::spanned(message, rhs_offset + start, rhs_offset + end),
+    })?;
+    let entries: Vec<f64> = match &value {
+        Value::Number(n) => return bind_number(name, *n as f32, repl).map(|v| v.to_string()),
+        Value::Vector(v) => v.clone(),
+        Value::Matrix(m) => m.concat(),
+    };
+    // Like a number, every entry must be finite to be saved and read back.
+    if entries.iter().any(|e| !e.is_finite()) {
+        return Err(LetError::plain(format!(
+            "Cannot bind '{name}' to {} — every entry must be a finite number",
+            value.literal()
+        )));
+    }
+
+    // Vet the name as the calculator would, then clear whatever it meant.
+    repl.defs
+        .define_value(name, 0.0)
+        .map_err(|e| LetError::plain(e.message))?;
+    repl.defs.undefine(name);
+    let literal = value.literal();
+    repl.bind_array(name, value);
+    Ok(literal)
+}
+
 fn define_function(
     name: &str,
     body: &str,
@@ -291,6 +348,7 @@ fn define_function(
             })),
         });
     }
+    repl.unbind_array(name);
     Ok(())
 }
 
@@ -338,6 +396,9 @@ fn notice(binding: String, previous: Option<String>) -> String {
 
 /// A short description of what `name` is currently bound to, if anything.
 fn describe(repl: &Repl, name: &str) -> Option<String> {
+    if let Some(value) = arrays::lookup(&repl.arrays, name) {
+        return Some(value.literal());
+    }
     if let Some(v) = repl.defs.value(name) {
         return Some(v.to_string());
     }
@@ -356,7 +417,7 @@ pub(crate) fn undefine(name: &str, repl: &mut Repl, l: &mut impl Logger) {
         l.eprint("'ans' is maintained automatically and can't be removed");
         return;
     }
-    if repl.defs.undefine(name) {
+    if repl.unbind_array(name) || repl.defs.undefine(name) {
         l.print(&format!("{name} removed"));
         save(repl, l);
     } else {
@@ -364,6 +425,47 @@ pub(crate) fn undefine(name: &str, repl: &mut Repl, l: &mut impl Logger) {
     }
 }
 
+/// A binding set aside while a mode binds its own parameter under the same
+/// name (`t` while animating). [`Shadow::restore`] puts the user's binding
+/// back, or removes the parameter if there was none.
+pub(crate) struct Shadow {
+    name: String,
+    previous: Option<Shadowed>,
+}
+
+enum Shadowed {
+    Value(f32),
+    Function(String),
+}
+
+impl Shadow {
+    pub(crate) fn new(defs: &Definitions, name: &str) -> Self {
+        let previous = match defs.value(name) {
+            Some(v) => Some(Shadowed::Value(v)),
+            None => defs
+                .function_body(name)
+                .map(|body| Shadowed::Function(body.to_string())),
+        };
+        Shadow {
+            name: name.to_string(),
+            previous,
+        }
+    }
+
+    pub(crate) fn restore(self, defs: &mut Definitions) {
+        // The binding was valid when it was set aside, so putting it back
+        // can't fail.
+        let _ = match self.previous {
+            Some(Shadowed::Value(v)) => defs.define_value(&self.name, v),
+            Some(Shadowed::Function(body)) => defs.define_function(&self.name, &body),
+            None => {
+                defs.undefine(&self.name);
+                Ok(())
+            }
+        };
+    }
+}
+
 /// Where bindings live: `~/.rmr_bindings`, one `let` line per binding.
 pub(crate) fn default_bindings_path() -> Option<PathBuf> {
     std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".rmr_bindings"))
@@ -414,6 +516,9 @@ pub(crate) fn save(repl: &Repl, l: &mut impl Logger) {
             }
         }
     }
+    for (name, value) in &repl.arrays {
+        let _ = writeln!(out, "let {name} = {}", value.literal());
+    }
     if let Err(e) = std::fs::write(path, out) {
         l.eprint(&format!(
             "Warning: could not save bindings to {}: {e}",
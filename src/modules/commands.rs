@@ -1,28 +1,44 @@
+use std::time::Duration;
+
 use rusty_maths::{
     equation_analyzer::calculator::plot_with,
     equation_analyzer::catalog::{self, Category, Symbol, SymbolKind},
     equation_analyzer::{Definition, Definitions},
-    linear_algebra::{vector_mean, vector_sum},
+    linear_algebra::{vector_mean, vector_sum, Matrix},
 };
 
 use crate::modules::{
+    animate::animate,
+    arrays::ARRAY_FUNCTIONS,
     bindings,
     common::*,
     cube::cube,
-    error_render,
-    graphing::graph,
-    inputs::{get_g_inputs, get_matrix_input, get_numerical_input},
+    error_render, export,
+    fft::fft_command,
+    fit::fit_data,
+    graphing::{curve_values, graph, graph_with_plot, Plot},
+    inputs::{
+        confirm, get_g_inputs, get_matrix_input, get_numerical_input, get_optional_numerical_input,
+        get_y_range_inputs,
+    },
     logger::Logger,
-    repl::{Repl, MIN_GRAPH_WIDTH},
-    string_maker::make_table_string,
+    matrix::{self, Eigenvalue},
+    repl::{Repl, MIN_GRAPH_HEIGHT, MIN_GRAPH_WIDTH},
+    scatter::scatter,
+    sliders::sliders,
+    spline::spline,
+    stats::stats,
+    string_maker::{format_entry, make_matrix_string, make_table_string},
+    surface::{contour, surf},
+    viewer::{Viewer, ViewerEvent},
+    wireframe::wireframe,
 };
 
-use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
-use crossterm::{cursor, ExecutableCommand};
+use crossterm::event::{KeyCode, MouseButton, MouseEventKind};
 
 use super::{
-    bezier_curve::{cubic_bezier, quadratic_bezier},
+    bezier_curve::{ask_degree, draw_bezier, parse_points, query_points, Spline},
+    bezier_editor::bezier_editor,
     inputs::read_user_input,
 };
 
@@ -38,7 +54,12 @@ pub(crate) fn run_command(line: &str, l: &mut impl Logger, repl: &mut Repl) {
     // `:fns <name>` — describe a single symbol. Bare `:fns` falls through
     // to the match below and prints the whole catalog.
     if let Some(name) = line.strip_prefix("fns ") {
-        fns_one(name.trim(), l, &repl.defs);
+        fns_one(name.trim(), l, repl);
+        return;
+    }
+
+    if let Some(args) = line.strip_prefix("save ") {
+        export::save(args, repl, l);
         return;
     }
 
@@ -47,24 +68,84 @@ pub(crate) fn run_command(line: &str, l: &mut impl Logger, repl: &mut Repl) {
         return;
     }
 
+    if repl.auto_size {
+        repl.fit_to_terminal();
+    }
+    let (y_min, y_max) = repl.y_range.unwrap_or((-7., 7.));
     let go = GraphOptions {
-        y_min: -7.,
-        y_max: 7.,
+        y_min,
+        y_max,
+        y_fixed: repl.y_range.is_some(),
         width: repl.width,
         height: repl.height,
+        x_scale: Scale::Linear,
+        y_scale: Scale::Linear,
+        equal_aspect: repl.equal_aspect,
     };
+
+    // `:fft <path.wav> [logx]` — the file's frequencies over time.
+    if let Some(args) = line.strip_prefix("fft ") {
+        let graph = fft_command(args, l, &go);
+        repl.remember_graph(graph);
+        return;
+    }
+
+    // `:g logx`, `:g logy`, `:g logx logy` — graph with log-scaled axes.
+    if let Some(scales) = line.strip_prefix("g ") {
+        match parse_scales(scales.split_whitespace()) {
+            Ok((x_scale, y_scale)) => {
+                let go = GraphOptions {
+                    x_scale,
+                    y_scale,
+                    ..go
+                };
+                repl.remember_graph(g(l, &go, &repl.defs));
+            }
+            Err(e) => l.eprint(&e),
+        }
+        return;
+    }
+
     match line {
         "t" | "table" => t(l, &repl.defs),
-        "g" | "graph" => g(l, &go, &repl.defs),
+        "g" | "graph" => repl.remember_graph(g(l, &go, &repl.defs)),
         "o" | "graph options" => gos(l, repl),
-        "ag" | "animated graph" => ag(l, &go, &repl.defs),
-        "ig" | "interactive graph" => ig(l, &go, &repl.defs),
-        "la" | "linear algebra" => la(l),
-        "c" | "cube" | "3d" => c(l, &go),
-        "qbc" => qbc(l, &go),
-        "cbc" => cbc(l, &go),
-        "fns" | "functions" => fns_all(l, &repl.defs),
+        "ag" | "animated graph" => repl.remember_graph(ag(l, &go, &repl.defs)),
+        "ig" | "interactive graph" => repl.remember_graph(ig(l, &go, &repl.defs)),
+        "an" | "animate" => {
+            let graph = animate(l, &go, &mut repl.defs);
+            repl.remember_graph(graph);
+        }
+        "sl" | "sliders" => {
+            let graph = sliders(l, &go, repl);
+            repl.remember_graph(graph);
+        }
+        "la" | "linear algebra" => la(l, repl),
+        "c" | "cube" | "3d" => repl.remember_frame(c(l, &go)),
+        "wf" | "wireframe" => repl.remember_frame(wireframe(l, &go)),
+        "surf" | "surface" => {
+            let frame = surf(l, &go, &mut repl.defs);
+            repl.remember_frame(frame);
+        }
+        "contour" => {
+            let graph = contour(l, &go, &mut repl.defs);
+            repl.remember_graph(graph);
+        }
+        "qbc" => repl.remember_graph(qbc(l, &go, &repl.defs)),
+        "cbc" => repl.remember_graph(cbc(l, &go, &repl.defs)),
+        "bz" | "bezier" => repl.remember_graph(bz(l, &go, &repl.defs)),
+        "spline" => repl.remember_graph(spline(l, &go, &repl.defs)),
+        "scatter" => repl.remember_graph(scatter(l, &go, &repl.defs)),
+        "fit" => {
+            let graph = fit_data(l, &go, repl);
+            repl.remember_graph(graph);
+        }
+        "stats" | "statistics" => repl.remember_graph(stats(l, &go, repl.precision)),
+        "be" | "bezier editor" => repl.remember_graph(bezier_editor(l, &go)),
+        "fns" | "functions" => fns_all(l, repl),
         "undef" => l.eprint("Usage: :undef <name>"),
+        "fft" => l.eprint("Usage: :fft <path.wav> [logx]"),
+        "save" => export::save("", repl, l),
         "h" | "help" => h(l),
         _ => {
             l.eprint(&format!("Invalid command '{line}'. Type ':h' for help."));
@@ -75,17 +156,39 @@ pub(crate) fn run_command(line: &str, l: &mut impl Logger, repl: &mut Repl) {
 fn h(l: &mut impl Logger) {
     l.print("Available commands:");
     l.print(":g  | :graph -> graphing mode");
+    l.print(":g logx | :g logy -> graph with a log-scaled x and/or y axis");
     l.print(":t  | :table -> table mode");
-    l.print(":o  | :graph options -> graph options mode");
+    l.print(":o  | :graph options -> graph size (or auto), equal aspect, and a session y range");
     l.print(":ag | :animated graph -> animated graph mode");
-    l.print(":ig | :interactive graph -> interactive graph mode");
-    l.print(":la | :linear algebra -> linear algebra mode");
+    l.print(":an | :animate -> animate an equation over a time parameter t");
+    l.print(":sl | :sliders -> graph while adjusting value bindings with the arrow keys");
+    l.print(":ig | :interactive graph -> interactive graph mode (arrows/drag to pan, wheel to zoom, click for values)");
+    l.print(
+        ":la | :linear algebra -> matrices: multiply, det, inverse, solve, LU, QR, eigenvalues",
+    );
     l.print(":c  | :cube | :3d -> renders an animated cube to the terminal");
+    l.print(":wf | :wireframe -> 3D wireframe of a built-in solid or an OBJ file");
+    l.print(":surf | :surface -> 3D wireframe surface of z = f(x, y), turned with the arrow keys");
+    l.print(":contour -> level curves of z = f(x, y) on a graph");
     l.print(":qbc -> quadratic bezier curve");
     l.print(":cbc -> cubic bezier curve");
+    l.print(":bz | :bezier -> bezier curve or spline through any points, over optional equations");
+    l.print(":spline -> catmull-rom, b-spline or natural cubic spline through typed or CSV points");
+    l.print(
+        ":fit -> least-squares linear, polynomial, exp, log or power fit to typed or CSV points",
+    );
+    l.print(":stats -> summary statistics, histogram and box plot of typed, pasted or CSV data");
+    l.print(
+        ":be | :bezier editor -> edit a bezier curve or spline of any degree with the keyboard",
+    );
+    l.print(
+        ":fft <path.wav> [logx] -> dominant frequencies over time and the spectrum of a WAV file",
+    );
     l.print(":p  | :precision <n> -> set decimal precision (e.g. :p 4)");
     l.print(":fns [name] -> list every math function/operator/constant; with a name, show just that one");
     l.print(":undef <name> -> remove a let binding");
+    l.print(":save txt <path> -> save the last graph, curve or cube frame as text");
+    l.print(":save png <path> [WxH] -> save the last graph as a PNG image (default 800x600)");
     l.print(":q  | :quit -> exits the repl session");
     l.print("");
     l.print("Bindings (persist across sessions):");
@@ -143,11 +246,12 @@ fn label_for(sym: &Symbol) -> String {
     }
 }
 
-fn fns_all(l: &mut impl Logger, defs: &Definitions) {
+fn fns_all(l: &mut impl Logger, repl: &Repl) {
     // Compute the longest label so summaries line up in a column.
     let label_width = catalog::all()
         .iter()
         .map(|s| label_for(s).chars().count())
+        .chain(ARRAY_FUNCTIONS.iter().map(|(name, _)| name.chars().count()))
         .max()
         .unwrap_or(4)
         .max(4);
@@ -168,25 +272,35 @@ fn fns_all(l: &mut impl Logger, defs: &Definitions) {
             l.print("");
         }
     }
-    if !defs.is_empty() {
+    l.print("── Vectors and matrices ──");
+    for (name, summary) in ARRAY_FUNCTIONS {
+        let pad = label_width - name.chars().count();
+        l.print(&format!("  {name}{}  {summary}", " ".repeat(pad)));
+    }
+    l.print("");
+    if !repl.defs.is_empty() || !repl.arrays.is_empty() {
         l.print("── Your bindings ──");
-        for def in defs.iter() {
+        for def in repl.defs.iter() {
             match def {
                 Definition::Value { name, value } => l.print(&format!("  {name} = {value}")),
                 Definition::Function { name, body } => l.print(&format!("  {name}(x) = {body}")),
             }
         }
+        for (name, value) in &repl.arrays {
+            l.print(&format!("  {name} = {}", value.literal()));
+        }
         l.print("");
     }
     l.print("Use ':fns <name>' for details on any single symbol (e.g. ':fns atan2').");
 }
 
-fn fns_one(name: &str, l: &mut impl Logger, defs: &Definitions) {
+fn fns_one(name: &str, l: &mut impl Logger, repl: &Repl) {
     if name.is_empty() {
         l.eprint("Usage: :fns <name>   (or bare :fns to list everything)");
         return;
     }
     // Catalog names and binding names can't collide, so order is cosmetic.
+    let defs = &repl.defs;
     if let Some(v) = defs.value(name) {
         l.print("");
         l.print(&format!("  {name} = {v}"));
@@ -199,6 +313,25 @@ fn fns_one(name: &str, l: &mut impl Logger, defs: &Definitions) {
         l.print("  kind      function binding (yours — remove with :undef)");
         return;
     }
+    if let Some((_, value)) = repl.arrays.iter().find(|(n, _)| n == name) {
+        l.print("");
+        l.print(&format!("  {name} = {}", value.literal()));
+        l.print(&format!(
+            "  kind      {} binding (yours — remove with :undef)",
+            value.kind().trim_start_matches("a ")
+        ));
+        return;
+    }
+    // Array functions win over the catalog when an expression is evaluated,
+    // so they do here too.
+    if let Some((_, summary)) = ARRAY_FUNCTIONS.iter().find(|(n, _)| *n == name) {
+        l.print("");
+        l.print(&format!("  {name}"));
+        l.print("  kind      array function");
+        l.print("  category  vectors and matrices");
+        l.print(&format!("  summary   {summary}"));
+        return;
+    }
     match catalog::find(name) {
         Some(sym) => {
             l.print("");
@@ -224,61 +357,168 @@ fn set_precision(n: &str, repl: &mut Repl, l: &mut impl Logger) {
     }
 }
 
-fn cbc(l: &mut impl Logger, go: &GraphOptions) {
-    l.print(&format!(
-        "Lower Right Coordinates - x:{}, y:{}",
-        go.width, go.height
-    ));
+fn cbc(l: &mut impl Logger, go: &GraphOptions, defs: &Definitions) -> Option<(String, Plot)> {
+    let p1 = get_point("start", l);
+    let p2 = get_point("control1", l);
+    let p3 = get_point("control2", l);
+    let p4 = get_point("end", l);
 
-    let p1_x = get_numerical_input("start x: ", l);
-    let p1_y = get_numerical_input("start y: ", l);
+    let spline = Spline {
+        degree: 3,
+        points: vec![p1, p2, p3, p4],
+    };
+    bezier_with_queries(&spline, "", go, defs, l)
+}
+
+fn qbc(l: &mut impl Logger, go: &GraphOptions, defs: &Definitions) -> Option<(String, Plot)> {
+    let p1 = get_point("start", l);
+    let p2 = get_point("control", l);
+    let p3 = get_point("end", l);
+
+    let spline = Spline {
+        degree: 2,
+        points: vec![p1, p2, p3],
+    };
+    bezier_with_queries(&spline, "", go, defs, l)
+}
 
-    let p2_x = get_numerical_input("control1 x: ", l);
-    let p2_y = get_numerical_input("control1 y: ", l);
+fn get_point(name: &str, l: &mut impl Logger) -> Point {
+    let x = get_numerical_input(&format!("{name} x: "), l);
+    let y = get_numerical_input(&format!("{name} y: "), l);
+    Point::new(x, y)
+}
 
-    let p3_x = get_numerical_input("control2 x: ", l);
-    let p3_y = get_numerical_input("control2 y: ", l);
+/// Bezier mode for any number of control points: one curve of degree
+/// points - 1, or a spline of a chosen degree, optionally over equations.
+fn bz(l: &mut impl Logger, go: &GraphOptions, defs: &Definitions) -> Option<(String, Plot)> {
+    let points = loop {
+        let answer = read_user_input("control points (x y, x y, ...): ").ok()?;
+        match parse_points(&answer) {
+            Ok(points) => break points,
+            Err(e) => l.eprint(&e),
+        }
+    };
+    let degree = ask_degree(points.len() - 1, l);
+    let eq = read_user_input("overlay equations (blank = none): ").unwrap_or_default();
 
-    let p4_x = get_numerical_input("end x: ", l);
-    let p4_y = get_numerical_input("end y: ", l);
+    let spline = Spline { degree, points };
+    bezier_with_queries(&spline, &eq, go, defs, l)
+}
 
-    let p1 = Point::new(p1_x, p1_y);
-    let p2 = Point::new(p2_x, p2_y);
-    let p3 = Point::new(p3_x, p3_y);
-    let p4 = Point::new(p4_x, p4_y);
+fn bezier_with_queries(
+    spline: &Spline,
+    eq: &str,
+    go: &GraphOptions,
+    defs: &Definitions,
+    l: &mut impl Logger,
+) -> Option<(String, Plot)> {
+    let drawn = draw_bezier(spline, eq, go, defs, l)?;
+    query_points(spline, l);
+    Some(drawn)
+}
 
-    cubic_bezier(p1, p2, p3, p4, go, l);
+fn c(l: &mut impl Logger, go: &GraphOptions) -> Option<String> {
+    cube(l, go)
 }
 
-fn qbc(l: &mut impl Logger, go: &GraphOptions) {
-    l.print(&format!(
-        "Lower Right Coordinates - x:{}, y:{}",
-        go.width, go.height
-    ));
-    let p1_x = get_numerical_input("start x: ", l);
-    let p1_y = get_numerical_input("start y: ", l);
+fn gos(l: &mut impl Logger, repl: &mut Repl) {
+    let current = if repl.auto_size {
+        "auto".to_string()
+    } else {
+        repl.width.to_string()
+    };
+    match read_user_input(&format!(
+        "width (number or 'auto', blank = keep {current}): "
+    )) {
+        Ok(s) if s == "auto" => {
+            repl.auto_size = true;
+            repl.fit_to_terminal();
+        }
+        Ok(s) if !s.is_empty() => match s.parse::<usize>() {
+            Ok(width) if repl.update_dimensions(width) => repl.auto_size = false,
+            Ok(_) => l.eprint(&format!("Width must be at least {MIN_GRAPH_WIDTH}")),
+            Err(_) => l.eprint(&format!("'{s}' is not a valid number")),
+        },
+        _ => {}
+    }
 
-    let p2_x = get_numerical_input("control x: ", l);
-    let p2_y = get_numerical_input("control y: ", l);
+    // Auto sizing owns the height too; asking would only be overwritten.
+    if !repl.auto_size {
+        let prompt = format!("height (blank = keep {}): ", repl.height);
+        if let Some(height) = get_optional_numerical_input(&prompt, l) {
+            if !repl.update_height(height) {
+                l.eprint(&format!("Height must be at least {MIN_GRAPH_HEIGHT}"));
+            }
+        }
+    }
 
-    let p3_x = get_numerical_input("end x: ", l);
-    let p3_y = get_numerical_input("end y: ", l);
+    let keep = if repl.equal_aspect { "y" } else { "n" };
+    let prompt = format!("equal aspect (y/n, blank = keep {keep}): ");
+    if let Some(equal_aspect) = confirm(&prompt, repl.equal_aspect, l) {
+        repl.equal_aspect = equal_aspect;
+    }
 
-    let p1 = Point::new(p1_x, p1_y);
-    let p2 = Point::new(p2_x, p2_y);
-    let p3 = Point::new(p3_x, p3_y);
+    repl.y_range = get_session_y_range(repl.y_range, l);
 
-    quadratic_bezier(p1, p2, p3, go, l);
+    let size = if repl.auto_size { " (auto)" } else { "" };
+    l.print(&format!(
+        "Graph size {}x{}{size}, equal aspect {}, y range {}",
+        repl.width,
+        repl.height,
+        if repl.equal_aspect { "on" } else { "off" },
+        match repl.y_range {
+            Some((y_min, y_max)) => format!("locked to {y_min}..{y_max}"),
+            None => "auto".to_string(),
+        }
+    ));
 }
 
-fn c(l: &mut impl Logger, go: &GraphOptions) {
-    cube(l, go);
+/// The session y window for `:o`. Blank keeps `current`, like the rest of
+/// the dialog, and `auto` unlocks it.
+fn get_session_y_range(current: Option<(f32, f32)>, l: &mut impl Logger) -> Option<(f32, f32)> {
+    let keep = current.map_or("auto".to_string(), |(y_min, y_max)| {
+        format!("{y_min}..{y_max}")
+    });
+    loop {
+        let y_min =
+            match read_user_input(&format!("y min (number or 'auto', blank = keep {keep}): ")) {
+                Ok(s) if s == "auto" => return None,
+                Ok(s) if !s.is_empty() => match s.parse::<f32>() {
+                    Ok(y_min) => y_min,
+                    Err(_) => {
+                        l.eprint(&format!("'{s}' is not a valid number"));
+                        continue;
+                    }
+                },
+                _ => return current,
+            };
+        let prompt = format!("y max (blank = keep {keep}): ");
+        let Some(y_max) = get_optional_numerical_input::<f32>(&prompt, l) else {
+            return current;
+        };
+        if y_min < y_max {
+            return Some((y_min, y_max));
+        }
+        l.eprint(&format!(
+            "y min `{y_min}` must be less than y max `{y_max}`"
+        ));
+    }
 }
 
-fn gos(l: &mut impl Logger, repl: &mut Repl) {
-    let width = get_numerical_input("width: ", l);
-    if !repl.update_dimensions(width) {
-        l.eprint(&format!("Width must be at least {MIN_GRAPH_WIDTH}"));
+/// The y window for one graph: a session lock from `:o` wins, otherwise the
+/// user may type one (blank keeps it automatic).
+pub(crate) fn with_y_range(l: &mut impl Logger, go: &GraphOptions) -> GraphOptions {
+    if go.y_fixed {
+        return *go;
+    }
+    match get_y_range_inputs(l) {
+        Some((y_min, y_max)) => GraphOptions {
+            y_min,
+            y_max,
+            y_fixed: true,
+            ..*go
+        },
+        None => *go,
     }
 }
 
@@ -302,126 +542,401 @@ fn t(l: &mut impl Logger, defs: &Definitions) {
     }
 }
 
-fn g(l: &mut impl Logger, go: &GraphOptions, defs: &Definitions) {
+/// Reads axis scale words (`logx`, `logy`) into (x scale, y scale).
+pub(crate) fn parse_scales<'a>(
+    words: impl Iterator<Item = &'a str>,
+) -> Result<(Scale, Scale), String> {
+    let (mut x_scale, mut y_scale) = (Scale::Linear, Scale::Linear);
+    for word in words {
+        match word {
+            "logx" | "--logx" => x_scale = Scale::Log,
+            "logy" | "--logy" => y_scale = Scale::Log,
+            _ => return Err(format!("Unknown graph option '{word}'. Valid: logx, logy")),
+        }
+    }
+    Ok((x_scale, y_scale))
+}
+
+fn g(l: &mut impl Logger, go: &GraphOptions, defs: &Definitions) -> Option<(String, Plot)> {
     let (eq, x_min, x_max) = get_g_inputs(l);
-    let g = graph(&eq, x_min, x_max, go, defs);
+    if go.x_scale == Scale::Log && x_min <= 0.0 {
+        l.eprint(&format!("x min `{x_min}` must be positive on a log x axis"));
+        return None;
+    }
+    let go = &with_y_range(l, go);
+    if go.y_scale == Scale::Log && go.y_fixed && go.y_min <= 0.0 {
+        l.eprint(&format!(
+            "y min `{}` must be positive on a log y axis",
+            go.y_min
+        ));
+        return None;
+    }
+    let g = graph_with_plot(&eq, x_min, x_max, go, defs);
 
     match g {
-        Ok(g) => l.print(&g),
-        Err(e) => l.eprint(&error_render::render_error_with_source(&eq, &e, defs)),
+        Ok((g, plot)) => {
+            l.print(&g);
+            Some((g, plot))
+        }
+        Err(e) => {
+            l.eprint(&error_render::render_error_with_source(&eq, &e, defs));
+            None
+        }
     }
 }
 
-fn ag(l: &mut impl Logger, go: &GraphOptions, defs: &Definitions) {
-    let mut stdout = std::io::stdout();
+// Frames in the zoom-out animation, and the delay between them
+const AG_FRAMES: usize = 100;
+const AG_FRAME_DELAY: Duration = Duration::from_millis(90);
 
-    let (eq, x_min, x_max) = get_g_inputs(l);
-    let g = graph(&eq, x_min, x_max, go, defs);
+const AG_HELP: &[(&str, &str)] = &[];
 
-    if let Ok(g) = g {
-        l.print(&g);
-        let new_lines = frame_line_count(&g);
+fn ag(l: &mut impl Logger, go: &GraphOptions, defs: &Definitions) -> Option<(String, Plot)> {
+    let (eq, x_min, x_max) = get_g_inputs(l);
+    let go = with_y_range(l, go);
 
-        for n in 0..100 {
-            std::thread::sleep(std::time::Duration::from_millis(90));
+    // Render once in the normal screen so a bad equation reports there.
+    if let Err(e) = graph(&eq, x_min, x_max, &go, defs) {
+        l.eprint(&error_render::render_error_with_source(&eq, &e, defs));
+        return None;
+    }
+    let mut viewer = open_viewer(AG_HELP, l)?;
 
-            let _ = stdout.execute(cursor::MoveUp(new_lines as u16));
+    let mut last = None;
+    let mut n = 0;
+    loop {
+        let view = viewer.fit(&go);
+        let (lo, hi) = (x_min - n as f32, x_max + n as f32);
+        if let Ok((g, plot)) = graph_with_plot(&eq, lo, hi, &view, defs) {
+            viewer.draw(&g, &format!("{eq}   x: {lo}..{hi}   frame {n}/{AG_FRAMES}"));
+            last = Some((g, plot));
+        }
 
-            if let Ok(g) = graph(&eq, x_min - n as f32, x_max + n as f32, go, defs) {
-                l.print(&g);
-            }
+        // Once the animation ends the last frame stays up until closed.
+        let timeout = (n < AG_FRAMES).then_some(AG_FRAME_DELAY);
+        match viewer.next_event(timeout) {
+            ViewerEvent::Quit => break,
+            ViewerEvent::Tick => n += 1,
+            ViewerEvent::Redraw | ViewerEvent::Key(_) | ViewerEvent::Mouse(_) => {}
         }
-    } else if let Err(e) = g {
-        l.eprint(&error_render::render_error_with_source(&eq, &e, defs));
     }
+    last
 }
 
-fn ig(l: &mut impl Logger, go: &GraphOptions, defs: &Definitions) {
-    let mut stdout = std::io::stdout();
+const IG_HELP: &[(&str, &str)] = &[
+    ("← →", "pan left / right"),
+    ("drag", "pan"),
+    ("wheel", "zoom around the pointer"),
+    ("click", "show (x, y) and the curve values there"),
+];
+
+// Range scale per wheel notch
+const ZOOM_IN: f32 = 0.8;
+const ZOOM_OUT: f32 = 1.25;
 
+fn ig(l: &mut impl Logger, go: &GraphOptions, defs: &Definitions) -> Option<(String, Plot)> {
     let (eq, mut x_min, mut x_max) = get_g_inputs(l);
-    let g = graph(&eq, x_min, x_max, go, defs);
-
-    if let Ok(g) = g {
-        l.print(&g);
-
-        let new_lines = frame_line_count(&g);
-        // Enable raw mode for key capture - ignore errors, continue without interactive mode
-        let _ = enable_raw_mode();
-
-        loop {
-            match read() {
-                Ok(Event::Key(KeyEvent {
-                    code: KeyCode::Right,
-                    modifiers: KeyModifiers::NONE,
-                    kind: _,
-                    state: _,
-                })) => {
-                    let _ = disable_raw_mode();
-                    x_min += 1.0;
-                    x_max += 1.0;
-                    let _ = stdout.execute(cursor::MoveUp(new_lines as u16));
-
-                    if let Ok(g) = graph(&eq, x_min, x_max, go, defs) {
-                        l.print(&g);
-                    }
+    let mut go = with_y_range(l, go);
 
-                    let _ = enable_raw_mode();
+    // Render once in the normal screen so a bad equation reports there.
+    if let Err(e) = graph(&eq, x_min, x_max, &go, defs) {
+        l.eprint(&error_render::render_error_with_source(&eq, &e, defs));
+        return None;
+    }
+    let mut viewer = open_viewer(IG_HELP, l)?;
+    // Without the mouse the arrow keys still work.
+    let _ = viewer.capture_mouse();
+
+    let mut layout = None;
+    let mut last = None;
+    // Cell the left button went down on (or was last dragged to), and
+    // whether it has moved since it went down
+    let mut drag: Option<(u16, u16, bool)> = None;
+    let mut clicked = Vec::new();
+    // Every mouse movement is reported once the mouse is captured; only
+    // events that change the view or the status resample and redraw.
+    let mut changed = true;
+
+    loop {
+        if changed {
+            let view = viewer.fit(&go);
+            if let Ok((g, plot)) = graph_with_plot(&eq, x_min, x_max, &view, defs) {
+                let status = match clicked.last() {
+                    Some(c) => format!("{eq}   {c}"),
+                    None => format!("{eq}   x: {x_min}..{x_max}"),
+                };
+                viewer.draw(&g, &status);
+                layout = Some(plot.layout);
+                last = Some((g, plot));
+            }
+            changed = false;
+        }
+
+        let event = match viewer.next_event(None) {
+            ViewerEvent::Quit => break,
+            ViewerEvent::Key(k) => {
+                match k.code {
+                    KeyCode::Right => {
+                        x_min += 1.0;
+                        x_max += 1.0;
+                    }
+                    KeyCode::Left => {
+                        x_min -= 1.0;
+                        x_max -= 1.0;
+                    }
+                    _ => continue,
                 }
+                changed = true;
+                continue;
+            }
+            ViewerEvent::Mouse(m) => m,
+            ViewerEvent::Redraw => {
+                changed = true;
+                continue;
+            }
+            ViewerEvent::Tick => continue,
+        };
+        let Some(layout) = layout else {
+            continue;
+        };
+        let (col, row) = (event.column, event.row);
+
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => drag = Some((col, row, false)),
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some((from_col, from_row, _)) = drag {
+                    let (x0, y0) = layout.cell_to_axis(from_col, from_row);
+                    let (x1, y1) = layout.cell_to_axis(col, row);
+                    (x_min, x_max) = shift_range(go.x_scale, (x_min, x_max), x0 - x1);
+                    // An auto-fit y range follows the curve; only a locked one pans.
+                    if go.y_fixed {
+                        (go.y_min, go.y_max) =
+                            shift_range(go.y_scale, (go.y_min, go.y_max), y0 - y1);
+                    }
+                    drag = Some((col, row, true));
+                    changed = true;
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                if let Some((_, _, false)) = drag {
+                    if let Some((x, y)) = layout.cell_to_point(col, row) {
+                        clicked.push(describe_point(&eq, x, y, defs));
+                        changed = true;
+                    }
+                }
+                drag = None;
+            }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let factor = if event.kind == MouseEventKind::ScrollUp {
+                    ZOOM_IN
+                } else {
+                    ZOOM_OUT
+                };
+                let (x, y) = layout.cell_to_axis(col, row);
+                (x_min, x_max) = zoom_range(go.x_scale, (x_min, x_max), x, factor);
+                if go.y_fixed {
+                    (go.y_min, go.y_max) = zoom_range(go.y_scale, (go.y_min, go.y_max), y, factor);
+                }
+                changed = true;
+            }
+            // Moves and other buttons change nothing.
+            _ => {}
+        }
+    }
 
-                Ok(Event::Key(KeyEvent {
-                    code: KeyCode::Left,
-                    modifiers: KeyModifiers::NONE,
-                    kind: _,
-                    state: _,
-                })) => {
-                    let _ = disable_raw_mode();
-                    x_min -= 1.0;
-                    x_max -= 1.0;
+    // The viewer's screen is gone once it closes; keep the clicks.
+    drop(viewer);
+    for c in clicked {
+        l.print(&c);
+    }
+    last
+}
 
-                    let _ = stdout.execute(cursor::MoveUp(new_lines as u16));
+/// Moves a value range by `by` in axis space (decades on a log axis).
+pub(crate) fn shift_range(scale: Scale, (lo, hi): (f32, f32), by: f32) -> (f32, f32) {
+    (
+        scale.to_value(scale.to_axis(lo) + by),
+        scale.to_value(scale.to_axis(hi) + by),
+    )
+}
 
-                    if let Ok(g) = graph(&eq, x_min, x_max, go, defs) {
-                        l.print(&g);
-                    }
+/// Scales a value range by `factor` in axis space, keeping the axis-space
+/// point `around` where it is.
+pub(crate) fn zoom_range(
+    scale: Scale,
+    (lo, hi): (f32, f32),
+    around: f32,
+    factor: f32,
+) -> (f32, f32) {
+    let zoom = |v: f32| scale.to_value(around + (scale.to_axis(v) - around) * factor);
+    (zoom(lo), zoom(hi))
+}
 
-                    let _ = enable_raw_mode();
-                }
+/// "x = …, y = …" for a clicked point, then each curve's value at that x.
+fn describe_point(eq_str: &str, x: f32, y: f32, defs: &Definitions) -> String {
+    let values: Vec<String> = eq_str
+        .split('|')
+        .zip(curve_values(eq_str, x, defs))
+        .map(|(eq, v)| format!("{} -> {v:.4}", eq.trim()))
+        .collect();
+    format!("x = {x:.4}, y = {y:.4}   {}", values.join("   "))
+}
 
-                Ok(Event::Key(KeyEvent {
-                    code: KeyCode::Char('q'),
-                    modifiers: KeyModifiers::NONE,
-                    kind: _,
-                    state: _,
-                })) => break,
-                _ => continue,
-            }
+/// Opens the full-screen viewer, reporting (rather than failing on) a
+/// terminal that can't host one.
+pub(crate) fn open_viewer(
+    help: &'static [(&'static str, &'static str)],
+    l: &mut impl Logger,
+) -> Option<Viewer> {
+    match Viewer::open(help) {
+        Ok(viewer) => Some(viewer),
+        Err(e) => {
+            l.eprint(&format!("Could not open the full-screen viewer: {e}"));
+            None
         }
-        let _ = disable_raw_mode();
-    } else if let Err(e) = g {
-        l.eprint(&error_render::render_error_with_source(&eq, &e, defs));
     }
 }
 
-fn la(l: &mut impl Logger) {
+const LA_OPERATIONS: &str = "'mul' (multiply), 't' (transpose), 'det' (determinant), \
+'inv' (inverse), 'rank', 'tr' (trace), 'solve' (Ax = b), 'lu', 'qr', 'eig' (eigenvalues), \
+'vs' (vector sum), 'vm' (vector mean), 'b' (back)";
+
+fn la(l: &mut impl Logger, repl: &Repl) {
+    let precision = repl.precision;
+    let show = |m: &Matrix| make_matrix_string(m, precision);
     loop {
-        if let Ok(op_code) = read_user_input("operation: ") {
-            match op_code.as_str() {
-                "vs" | "vector sum" => {
-                    let m = get_matrix_input(l);
-                    let sum = vector_sum(&m);
-                    l.print(&format!("{sum:#?}"));
+        // End of input (Ctrl-D, or the end of piped input) leaves the mode.
+        let Ok(op_code) = read_user_input("operation: ") else {
+            break;
+        };
+        match op_code.as_str() {
+            "mul" | "multiply" => {
+                let Some(a) = get_matrix_input("A", repl, l) else {
+                    continue;
+                };
+                let Some(b) = get_matrix_input("B", repl, l) else {
+                    continue;
+                };
+                match matrix::multiply(&a, &b) {
+                    Ok(m) => l.print(&show(&m)),
+                    Err(e) => l.eprint(&e),
+                }
+            }
+            "t" | "transpose" => {
+                if let Some(m) = get_matrix_input("matrix", repl, l) {
+                    l.print(&show(&matrix::transpose(&m)));
+                }
+            }
+            "det" | "determinant" => {
+                if let Some(m) = get_matrix_input("matrix", repl, l) {
+                    print_scalar(matrix::determinant(&m), precision, l);
+                }
+            }
+            "inv" | "inverse" => {
+                if let Some(m) = get_matrix_input("matrix", repl, l) {
+                    match matrix::inverse(&m) {
+                        Ok(m) => l.print(&show(&m)),
+                        Err(e) => l.eprint(&e),
+                    }
+                }
+            }
+            "rank" => {
+                if let Some(m) = get_matrix_input("matrix", repl, l) {
+                    l.print(&matrix::rank(&m).to_string());
+                }
+            }
+            "tr" | "trace" => {
+                if let Some(m) = get_matrix_input("matrix", repl, l) {
+                    print_scalar(matrix::trace(&m), precision, l);
+                }
+            }
+            "solve" => {
+                let Some(a) = get_matrix_input("A", repl, l) else {
+                    continue;
+                };
+                let Some(b) = get_matrix_input("b", repl, l) else {
+                    continue;
+                };
+                // b may be typed as a row or a column.
+                let b: Vec<f64> = if b.len() == 1 {
+                    b[0].clone()
+                } else {
+                    matrix::transpose(&b).concat()
+                };
+                match matrix::solve(&a, &b) {
+                    Ok(x) => {
+                        l.print("x =");
+                        l.print(&show(&matrix::transpose(&vec![x])));
+                    }
+                    Err(e) => l.eprint(&e),
                 }
-                "vm" | "vector mean" => {
-                    let m = get_matrix_input(l);
-                    let sum = vector_mean(&m);
-                    l.print(&format!("{sum:#?}"));
+            }
+            "lu" => {
+                if let Some(m) = get_matrix_input("matrix", repl, l) {
+                    match matrix::lu(&m) {
+                        Ok((p, lower, upper)) => {
+                            for (name, m) in [("P", p), ("L", lower), ("U", upper)] {
+                                l.print(&format!("{name} =\n{}", show(&m)));
+                            }
+                        }
+                        Err(e) => l.eprint(&e),
+                    }
+                }
+            }
+            "qr" => {
+                if let Some(m) = get_matrix_input("matrix", repl, l) {
+                    let (q, r) = matrix::qr(&m);
+                    for (name, m) in [("Q", q), ("R", r)] {
+                        l.print(&format!("{name} =\n{}", show(&m)));
+                    }
+                }
+            }
+            "eig" | "eigenvalues" => {
+                if let Some(m) = get_matrix_input("matrix", repl, l) {
+                    match matrix::eigenvalues(&m) {
+                        Ok(values) => {
+                            let values: Vec<String> = values
+                                .iter()
+                                .map(|&v| format_eigenvalue(v, precision))
+                                .collect();
+                            l.print(&values.join(", "));
+                        }
+                        Err(e) => l.eprint(&e),
+                    }
+                }
+            }
+            "vs" | "vector sum" => {
+                if let Some(m) = get_matrix_input("vectors", repl, l) {
+                    l.print(&show(&vec![vector_sum(&m)]));
+                }
+            }
+            "vm" | "vector mean" => {
+                if let Some(m) = get_matrix_input("vectors", repl, l) {
+                    l.print(&show(&vec![vector_mean(&m)]));
                 }
-                "b" | "back" => break,
-                _ => l.eprint(
-                    "Invalid operation. Valid: 'vs' (vector sum), 'vm' (vector mean), 'b' (back)",
-                ),
             }
+            "b" | "back" => break,
+            _ => l.eprint(&format!("Invalid operation. Valid: {LA_OPERATIONS}")),
         }
     }
 }
+
+fn print_scalar(value: Result<f64, String>, precision: usize, l: &mut impl Logger) {
+    match value {
+        Ok(v) => l.print(&format_entry(v, precision)),
+        Err(e) => l.eprint(&e),
+    }
+}
+
+/// `2`, or `1 + 0.5i` for a complex eigenvalue.
+pub(crate) fn format_eigenvalue((re, im): Eigenvalue, precision: usize) -> String {
+    if im == 0.0 {
+        return format_entry(re, precision);
+    }
+    let sign = if im < 0.0 { "-" } else { "+" };
+    format!(
+        "{} {sign} {}i",
+        format_entry(re, precision),
+        format_entry(im.abs(), precision)
+    )
+}
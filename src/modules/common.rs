@@ -1,3 +1,5 @@
+use crate::modules::repl::{MIN_GRAPH_HEIGHT, MIN_GRAPH_WIDTH};
+
 /// Represents a point in 2D space for plotting equations.
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) struct Point {
@@ -35,17 +37,83 @@ pub(crate) struct NormalizedPoint {
     pub(crate) y_acc: f32,
 }
 
+/// A labelled tick on a graph frame: `pos` is the glyph column (x ticks) or
+/// glyph row (y ticks) it marks.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Tick {
+    pub(crate) pos: usize,
+    pub(crate) label: String,
+}
+
+/// How values map onto a graph axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Scale {
+    Linear,
+    /// Decades are evenly spaced; non-positive values have no position.
+    Log,
+}
+
+impl Scale {
+    /// Position of `v` along an axis with this scale (NaN if it has none).
+    pub(crate) fn to_axis(self, v: f32) -> f32 {
+        match self {
+            Scale::Linear => v,
+            Scale::Log if v > 0.0 => v.log10(),
+            Scale::Log => f32::NAN,
+        }
+    }
+
+    /// Inverse of [`Scale::to_axis`].
+    pub(crate) fn to_value(self, a: f32) -> f32 {
+        match self {
+            Scale::Linear => a,
+            Scale::Log => 10_f32.powf(a),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
 pub(crate) struct GraphOptions {
     pub(crate) y_min: f32,
     pub(crate) y_max: f32,
+    /// When set, `y_min..y_max` is the exact y window. Otherwise it is only
+    /// the default the graph fits away from when the curve lies nearby.
+    pub(crate) y_fixed: bool,
     pub(crate) height: usize,
     pub(crate) width: usize,
+    pub(crate) x_scale: Scale,
+    pub(crate) y_scale: Scale,
+    /// Match units-per-cell on both axes, so circles come out round.
+    pub(crate) equal_aspect: bool,
+}
+
+// Terminal columns a terminal-sized graph leaves for its frame and y labels
+const TERMINAL_RESERVED_COLS: u16 = 10;
+// Terminal rows a terminal-sized graph leaves for its frame, x labels and prompt
+const TERMINAL_RESERVED_ROWS: u16 = 5;
+
+impl GraphOptions {
+    /// Resizes to fill a `cols` × `rows` terminal, as reported by a
+    /// crossterm `Event::Resize`.
+    pub(crate) fn fit_terminal(&mut self, cols: u16, rows: u16) {
+        (self.width, self.height) = graph_size_for_terminal(cols, rows);
+    }
+}
+
+/// Graph cell dimensions that fit a `cols` × `rows` terminal: each glyph
+/// holds 2×4 cells, less room for the frame and labels.
+pub(crate) fn graph_size_for_terminal(cols: u16, rows: u16) -> (usize, usize) {
+    let width = usize::from(cols.saturating_sub(TERMINAL_RESERVED_COLS)) * 2;
+    let height = usize::from(rows.saturating_sub(TERMINAL_RESERVED_ROWS)) * 4;
+    (width.max(MIN_GRAPH_WIDTH), height.max(MIN_GRAPH_HEIGHT))
 }
 
-/// Terminal lines a rendered frame occupies: newline count plus the final
-/// unterminated line. Animated modes move the cursor up by this much.
-pub(crate) fn frame_line_count(frame: &str) -> usize {
-    frame.chars().filter(|c| *c == '\n').count() + 1
+/// Graph dimensions for the current terminal, or `None` without one
+/// (piped output, tests).
+pub(crate) fn terminal_graph_size() -> Option<(usize, usize)> {
+    crossterm::terminal::size()
+        .ok()
+        .map(|(cols, rows)| graph_size_for_terminal(cols, rows))
 }
 
 pub(crate) fn make_cell_matrix(go: &GraphOptions) -> CellMatrix {
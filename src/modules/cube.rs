@@ -1,22 +1,102 @@
-use crate::modules::common::*;
-use crossterm::{
-    cursor::{self, Hide, Show},
-    event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode},
-    ExecutableCommand,
-};
+use crate::modules::{commands::open_viewer, common::*, logger::Logger, viewer::ViewerEvent};
 use std::{fmt::Write, time::Duration};
 
-use crate::modules::logger::Logger;
+use crossterm::event::KeyCode;
+
+// Delay between animation frames
+const FRAME_DELAY: Duration = Duration::from_millis(20);
+// Degrees turned per arrow (or , .) press
+const TURN_STEP: f32 = 6.0;
+// Zoom change per + or - press
+const ZOOM_STEP: f32 = 1.25;
+// Zoom bounds; past the top the spinning corners leave the frame
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 1.6;
+
+const HELP: &[(&str, &str)] = &[
+    ("↑ ↓", "turn about x"),
+    ("← →", "turn about y"),
+    (", .", "turn about z"),
+    ("space", "pause / resume spinning"),
+    ("+ -", "zoom in / out"),
+    ("r", "reset"),
+];
+
+pub(crate) fn cube(l: &mut impl Logger, go: &GraphOptions) -> Option<String> {
+    let mut viewer = open_viewer(HELP, l)?;
 
-pub(crate) fn cube(l: &mut impl Logger, go: &GraphOptions) {
-    let mut stdout = std::io::stdout();
-    if execute!(stdout, Hide).is_err() {
-        l.eprint("Failed to hide cursor");
-        return;
+    let edges: Vec<[usize; 2]> = vec![
+        [0, 1],
+        [1, 3],
+        [3, 2],
+        [2, 0],
+        [4, 5],
+        [5, 7],
+        [7, 6],
+        [6, 4],
+        [0, 4],
+        [1, 5],
+        [2, 6],
+        [3, 7],
+    ];
+    let mut view = viewer.fit(go);
+    let mut points = cube_points(&view);
+    let mut spinning = true;
+    let mut zoom = 1.0;
+
+    loop {
+        let frame = make_cube(&view, &points, &edges);
+        let status = format!(
+            "cube   zoom {zoom:.2}x   {}",
+            if spinning { "spinning" } else { "paused" }
+        );
+        viewer.draw(&frame, &status);
+
+        match viewer.next_event(spinning.then_some(FRAME_DELAY)) {
+            ViewerEvent::Quit => return Some(frame),
+            ViewerEvent::Tick => rotate_points(&mut points, 1.2, 1.5, -1.8),
+            ViewerEvent::Redraw => {
+                let fitted = viewer.fit(go);
+                if (fitted.width, fitted.height) != (view.width, view.height) {
+                    // Rebuild at the new size; the spin restarts from square.
+                    view = fitted;
+                    points = cube_points(&view);
+                    scale_points(&mut points, zoom);
+                }
+            }
+            ViewerEvent::Key(k) => match k.code {
+                KeyCode::Up => rotate_points(&mut points, TURN_STEP, 0.0, 0.0),
+                KeyCode::Down => rotate_points(&mut points, -TURN_STEP, 0.0, 0.0),
+                KeyCode::Left => rotate_points(&mut points, 0.0, TURN_STEP, 0.0),
+                KeyCode::Right => rotate_points(&mut points, 0.0, -TURN_STEP, 0.0),
+                KeyCode::Char(',') => rotate_points(&mut points, 0.0, 0.0, TURN_STEP),
+                KeyCode::Char('.') => rotate_points(&mut points, 0.0, 0.0, -TURN_STEP),
+                KeyCode::Char(' ') => spinning = !spinning,
+                KeyCode::Char('+') | KeyCode::Char('=') | KeyCode::Char('-') => {
+                    let step = if k.code == KeyCode::Char('-') {
+                        1.0 / ZOOM_STEP
+                    } else {
+                        ZOOM_STEP
+                    };
+                    let zoomed = (zoom * step).clamp(MIN_ZOOM, MAX_ZOOM);
+                    scale_points(&mut points, zoomed / zoom);
+                    zoom = zoomed;
+                }
+                KeyCode::Char('r') => {
+                    points = cube_points(&view);
+                    zoom = 1.0;
+                    spinning = true;
+                }
+                _ => {}
+            },
+            ViewerEvent::Mouse(_) => {}
+        }
     }
+}
 
+/// The cube's 8 corners sized to the graph, plus its center (last), which
+/// `rotate_points` spins around.
+pub(crate) fn cube_points(go: &GraphOptions) -> Vec<[f32; 3]> {
     let ver_gap = (go.height / 4) as f32;
     let hor_gap = (go.width / 4) as f32;
 
@@ -44,56 +124,10 @@ pub(crate) fn cube(l: &mut impl Logger, go: &GraphOptions) {
 
     let origin = [(p0[0] + p3[0]) / 2.0, (p0[1] + p3[1]) / 2.0, 0.0];
 
-    let edges: Vec<[usize; 2]> = vec![
-        [0, 1],
-        [1, 3],
-        [3, 2],
-        [2, 0],
-        [4, 5],
-        [5, 7],
-        [7, 6],
-        [6, 4],
-        [0, 4],
-        [1, 5],
-        [2, 6],
-        [3, 7],
-    ];
-    let mut points = vec![p0, p1, p2, p3, p4, p5, p6, p7, origin];
-    let frame = make_cube(go, &points, &edges);
-
-    let new_lines = frame_line_count(&frame);
-
-    l.print(&frame);
-
-    loop {
-        let _ = enable_raw_mode();
-        let v = poll(Duration::from_millis(20));
-        let _ = disable_raw_mode();
-
-        if v.is_ok_and(|v| v) {
-            match read() {
-                Ok(Event::Key(KeyEvent {
-                    code: KeyCode::Char('q'),
-                    modifiers: KeyModifiers::NONE,
-                    kind: _,
-                    state: _,
-                })) => break,
-                _ => continue,
-            }
-        } else {
-            let _ = stdout.execute(cursor::MoveUp(new_lines as u16));
-
-            rotate_points(&mut points, 1.2, 1.5, -1.8);
-
-            let frame = make_cube(go, &points, &edges);
-            l.print(&frame);
-        }
-    }
-    // Try to show cursor again, ignore error if terminal is unavailable
-    let _ = execute!(stdout, Show);
+    vec![p0, p1, p2, p3, p4, p5, p6, p7, origin]
 }
 
-fn rotate_points(
+pub(crate) fn rotate_points(
     points: &mut [[f32; 3]],
     angle_degrees_x: f32,
     angle_degrees_y: f32,
@@ -130,6 +164,18 @@ fn rotate_points(
     }
 }
 
+/// Scales the corners by `factor` about the center (the last point).
+pub(crate) fn scale_points(points: &mut [[f32; 3]], factor: f32) {
+    let Some(&center) = points.last() else {
+        return;
+    };
+    for point in points.iter_mut() {
+        for (v, c) in point.iter_mut().zip(center) {
+            *v = c + (*v - c) * factor;
+        }
+    }
+}
+
 fn make_cube(go: &GraphOptions, p: &[[f32; 3]], edges: &[[usize; 2]]) -> String {
     let mut matrix: CellMatrix = make_cell_matrix(go);
 
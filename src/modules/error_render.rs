@@ -23,14 +23,24 @@ pub(crate) const CARET_END: &str = "\u{001b}[0m";
 pub(crate) fn format_error(err: &EquationError, echo_indent: Option<usize>) -> String {
     match (err.span, echo_indent) {
         (Some(span), Some(indent)) => {
-            let pad = " ".repeat(indent + span.start);
-            let carets = "^".repeat(span.len().max(1));
-            format!("{pad}{CARET_START}{carets}{CARET_END}\n{}", err.message)
+            format_message_at(&err.message, (span.start, span.end), indent)
         }
         _ => err.to_string(),
     }
 }
 
+/// Carets under chars `start..end` of input echoed at column `indent`,
+/// then `message` — for errors found outside the calculator.
+pub(crate) fn format_message_at(
+    message: &str,
+    (start, end): (usize, usize),
+    indent: usize,
+) -> String {
+    let pad = " ".repeat(indent + start);
+    let carets = "^".repeat(end.saturating_sub(start).max(1));
+    format!("{pad}{CARET_START}{carets}{CARET_END}\n{message}")
+}
+
 /// Reprints the offending input and points at it — for contexts where the
 /// echoed input has scrolled away (the `:g`/`:t` sub-prompt flows print
 /// several prompts after the equation) or came from argv rather than an
@@ -1,8 +1,12 @@
 use rusty_maths::equation_analyzer::calculator::{calculate, calculate_with};
 
-use crate::modules::{error_render, logger::Logger, repl::Repl};
+use crate::modules::{arrays, error_render, logger::Logger, repl::Repl};
 
 pub(crate) fn evaluate(line: &str, repl: &mut Repl, l: &mut impl Logger) {
+    if arrays::is_array_expression(line, &repl.arrays) {
+        arrays::evaluate_line(line, repl, l);
+        return;
+    }
     match calculate_with(line, &repl.defs) {
         Ok(v) => {
             repl.set_ans(v);
@@ -0,0 +1,100 @@
+//! Saving rendered frames to disk.
+//!
+//! The repl keeps the most recent frame (`Repl::last_frame`) so `:save`
+//! never needs the graph entered again; the CLI saves the graph it just
+//! drew.
+
+use std::path::Path;
+
+use crate::modules::{graphing::Plot, logger::Logger, png, repl::Repl};
+
+const SAVE_USAGE: &str = "Usage: :save txt <path> | :save png <path> [WIDTHxHEIGHT]";
+
+/// `frame` without ANSI escape sequences (the underlines in tables, or any
+/// styling), so the saved text reads the same in any editor.
+pub(crate) fn strip_ansi(frame: &str) -> String {
+    let mut out = String::with_capacity(frame.len());
+    let mut chars = frame.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        // CSI sequences run from `ESC [` to a final byte in '@'..='~'.
+        if chars.next_if_eq(&'[').is_some() {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Writes `frame` as plain text, ending with a newline.
+pub(crate) fn save_txt(frame: &str, path: &Path) -> std::io::Result<()> {
+    let mut text = strip_ansi(frame);
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    std::fs::write(path, text)
+}
+
+/// Handles `:save <format> <path>` against the last rendered frame. PNGs
+/// take an optional trailing size and need the frame to be a graph.
+pub(crate) fn save(args: &str, repl: &Repl, l: &mut impl Logger) {
+    let (format, path) = match args.trim().split_once(char::is_whitespace) {
+        Some((format, path)) if !path.trim().is_empty() => (format, path.trim()),
+        _ => {
+            l.eprint(SAVE_USAGE);
+            return;
+        }
+    };
+    let Some(frame) = &repl.last_frame else {
+        l.eprint("Nothing to save yet. Draw a graph, curve or cube first.");
+        return;
+    };
+
+    match format {
+        "txt" => match save_txt(frame, Path::new(path)) {
+            Ok(()) => l.print(&format!("Saved to {path}")),
+            Err(e) => l.eprint(&format!("Could not save to {path}: {e}")),
+        },
+        "png" => {
+            let Some(plot) = &repl.last_plot else {
+                l.eprint("Only graphs can be saved as PNG; the last frame was not a graph.");
+                return;
+            };
+            // A trailing word starting with a digit is the size.
+            let sized = path
+                .rsplit_once(char::is_whitespace)
+                .filter(|(_, size)| size.starts_with(|c: char| c.is_ascii_digit()));
+            let (path, size) = match sized {
+                Some((path, size)) => match png::parse_size(size) {
+                    Ok(size) => (path.trim_end(), size),
+                    Err(e) => {
+                        l.eprint(&e);
+                        return;
+                    }
+                },
+                None => (path, png::DEFAULT_SIZE),
+            };
+            write_png(plot, size, path, l);
+        }
+        _ => l.eprint(&format!("Unknown format '{format}'. {SAVE_USAGE}")),
+    }
+}
+
+/// Writes `plot` as a PNG and reports which colour is which equation.
+pub(crate) fn write_png(plot: &Plot, size: (u32, u32), path: &str, l: &mut impl Logger) {
+    match png::save_png(plot, size, Path::new(path)) {
+        Ok(()) => l.print(&format!(
+            "Saved {}x{} to {path} ({})",
+            size.0,
+            size.1,
+            png::colour_key(plot)
+        )),
+        Err(e) => l.eprint(&format!("Could not save to {path}: {e}")),
+    }
+}
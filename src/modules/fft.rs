@@ -0,0 +1,524 @@
+//! `:fft <path.wav>`: which frequencies a sound file holds, and when.
+//!
+//! Everything is done here, with no audio crates, so it works offline: the
+//! WAV file is parsed by hand and mixed down to mono, then cut into
+//! overlapping Hann-windowed frames, each run through a radix-2 FFT (a
+//! short-time Fourier transform). Frames are averaged per time segment for
+//! the table of dominant frequencies, and over the whole file for the
+//! graphed spectrum.
+
+use std::{f64::consts::PI, path::Path};
+
+use rusty_maths::equation_analyzer::Definitions;
+
+use crate::modules::{
+    common::{GraphOptions, Point, Scale},
+    graphing::{graph_with_overlay, Overlay, Plot},
+    inputs::get_optional_numerical_input,
+    logger::Logger,
+    string_maker::format_entry,
+};
+
+/// Frequencies listed per segment, at most
+const PEAKS_PER_SEGMENT: usize = 3;
+/// A peak is listed only within this many dB of the segment's strongest.
+const PEAK_RANGE_DB: f64 = 20.0;
+/// Segments whose strongest frequency is quieter than this are silence.
+const SILENCE_DB: f64 = -80.0;
+/// dB shown below the top of the spectrum graph
+const GRAPH_RANGE_DB: f32 = 90.0;
+/// Automatic segment lengths, in seconds: the shortest that gives at most
+/// [`MAX_AUTO_SEGMENTS`] rows is used.
+const SEGMENT_LENGTHS: [f64; 10] = [0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0, 300.0];
+const MAX_AUTO_SEGMENTS: usize = 20;
+/// Most rows a chosen segment length may give
+const MAX_SEGMENTS: usize = 1000;
+/// Lowest frequency shown on a log axis, in Hz
+const LOG_AXIS_MIN_HZ: f32 = 20.0;
+
+/// A frequency in Hz and its amplitude (1 = full scale)
+pub(crate) type Peak = (f64, f64);
+
+/// One row of the table: a stretch of time and what was loudest in it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Segment {
+    /// Seconds from the start
+    pub start: f64,
+    pub end: f64,
+    /// Loudest first; empty when no frame is centred in the segment
+    pub peaks: Vec<Peak>,
+}
+
+/// A WAV file's sound, mixed down to one channel.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Wav {
+    pub sample_rate: u32,
+    /// Channels in the file, before mixing
+    pub channels: u16,
+    /// Samples from -1 to 1
+    pub samples: Vec<f64>,
+}
+
+impl Wav {
+    pub(crate) fn duration(&self) -> f64 {
+        self.samples.len() as f64 / f64::from(self.sample_rate)
+    }
+}
+
+/// Sample encodings this reader understands
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    /// Integer PCM: 8-bit unsigned, or 16, 24 or 32-bit signed
+    Pcm,
+    /// IEEE float, 32 or 64-bit
+    Float,
+}
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+/// WAVE_FORMAT_EXTENSIBLE: the real format is in the sub-format GUID.
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Reads a RIFF WAVE file of integer PCM or float samples.
+pub(crate) fn parse_wav(bytes: &[u8]) -> Result<Wav, String> {
+    if bytes.get(0..4) != Some(b"RIFF") || bytes.get(8..12) != Some(b"WAVE") {
+        return Err("Not a WAV file (no RIFF/WAVE header)".to_string());
+    }
+
+    let mut format: Option<(u16, u16, u32, u16)> = None;
+    let mut data: Option<&[u8]> = None;
+    let mut at = 12;
+    while let (Some(id), Some(size)) = (bytes.get(at..at + 4), u32_at(bytes, at + 4)) {
+        let start = at + 8;
+        // Streaming writers may leave a placeholder size; take what's there.
+        let end = start.saturating_add(size as usize).min(bytes.len());
+        let body = &bytes[start..end];
+        match id {
+            b"fmt " => {
+                let tag = u16_at(body, 0).ok_or("The WAV fmt chunk is cut short")?;
+                let channels = u16_at(body, 2).ok_or("The WAV fmt chunk is cut short")?;
+                let rate = u32_at(body, 4).ok_or("The WAV fmt chunk is cut short")?;
+                let bits = u16_at(body, 14).ok_or("The WAV fmt chunk is cut short")?;
+                let tag = if tag == FORMAT_EXTENSIBLE {
+                    u16_at(body, 24).ok_or("The WAV fmt chunk is cut short")?
+                } else {
+                    tag
+                };
+                format = Some((tag, channels, rate, bits));
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+        // Chunks are padded to an even length.
+        at = end + (end - start) % 2;
+    }
+
+    let (tag, channels, sample_rate, bits) = format.ok_or("The WAV file has no fmt chunk")?;
+    let data = data.ok_or("The WAV file has no data chunk")?;
+    let encoding = match (tag, bits) {
+        (FORMAT_PCM, 8 | 16 | 24 | 32) => Encoding::Pcm,
+        (FORMAT_FLOAT, 32 | 64) => Encoding::Float,
+        _ => {
+            return Err(format!(
+                "Only PCM and float WAV files can be read, not format {tag} at {bits} bits"
+            ))
+        }
+    };
+    if channels == 0 || sample_rate == 0 {
+        return Err("The WAV file has no channels or no sample rate".to_string());
+    }
+
+    let width = usize::from(bits / 8);
+    let frame = width * usize::from(channels);
+    let samples: Vec<f64> = data
+        .chunks_exact(frame)
+        .map(|frame| {
+            let sum: f64 = frame
+                .chunks_exact(width)
+                .map(|s| decode_sample(s, encoding))
+                .sum();
+            sum / f64::from(channels)
+        })
+        .collect();
+    if samples.is_empty() {
+        return Err("The WAV file has no samples".to_string());
+    }
+    Ok(Wav {
+        sample_rate,
+        channels,
+        samples,
+    })
+}
+
+/// One little-endian sample, scaled to -1..1.
+fn decode_sample(s: &[u8], encoding: Encoding) -> f64 {
+    match (encoding, s.len()) {
+        (Encoding::Pcm, 1) => (f64::from(s[0]) - 128.0) / 128.0,
+        (Encoding::Pcm, 2) => f64::from(i16::from_le_bytes([s[0], s[1]])) / 32_768.0,
+        // Put the 3 bytes at the top of an i32 so the sign comes along.
+        (Encoding::Pcm, 3) => {
+            f64::from(i32::from_le_bytes([0, s[0], s[1], s[2]])) / 2_147_483_648.0
+        }
+        (Encoding::Pcm, _) => {
+            f64::from(i32::from_le_bytes([s[0], s[1], s[2], s[3]])) / 2_147_483_648.0
+        }
+        (Encoding::Float, 4) => f64::from(f32::from_le_bytes([s[0], s[1], s[2], s[3]])),
+        (Encoding::Float, _) => f64::from_le_bytes(s.try_into().unwrap_or([0; 8])),
+    }
+}
+
+/// In-place radix-2 FFT of `re` + i·`im`, whose length is a power of two.
+pub(crate) fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_im, w_re) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+fn hann(n: usize) -> Vec<f64> {
+    (0..n)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / n as f64).cos())
+        .collect()
+}
+
+/// Frame length for a sample rate: the power of two up to a tenth of a
+/// second, which resolves frequencies 10 to 20 Hz apart.
+pub(crate) fn frame_length(sample_rate: u32) -> usize {
+    ((sample_rate as usize / 10 + 1).next_power_of_two() / 2).clamp(256, 16_384)
+}
+
+/// The short-time Fourier transform: the amplitude spectrum of each
+/// half-overlapping Hann-windowed frame, from 0 Hz up to the Nyquist
+/// frequency. Amplitudes are scaled so a full-scale sine peaks near 1.
+/// The last frame is padded with silence. Frames are worked out as they
+/// are taken, so a long file's spectrogram is never held whole.
+pub(crate) fn stft(samples: &[f64], frame_len: usize) -> impl Iterator<Item = Vec<f64>> + '_ {
+    let window = hann(frame_len);
+    let gain: f64 = window.iter().sum::<f64>() / 2.0;
+    let hop = frame_len / 2;
+    let frames = samples.len().saturating_sub(frame_len).div_ceil(hop) + 1;
+
+    (0..frames).map(move |f| {
+        let mut re: Vec<f64> = window
+            .iter()
+            .enumerate()
+            .map(|(i, w)| w * samples.get(f * hop + i).copied().unwrap_or(0.0))
+            .collect();
+        let mut im = vec![0.0; frame_len];
+        fft(&mut re, &mut im);
+        re.iter()
+            .zip(&im)
+            .take(frame_len / 2 + 1)
+            .map(|(r, i)| r.hypot(*i) / gain)
+            .collect()
+    })
+}
+
+/// A running average of spectra.
+#[derive(Default)]
+struct SpectrumSum {
+    sum: Vec<f64>,
+    count: usize,
+}
+
+impl SpectrumSum {
+    fn add(&mut self, spectrum: &[f64]) {
+        if self.sum.is_empty() {
+            self.sum = vec![0.0; spectrum.len()];
+        }
+        for (s, v) in self.sum.iter_mut().zip(spectrum) {
+            *s += v;
+        }
+        self.count += 1;
+    }
+
+    fn average(&self) -> Vec<f64> {
+        self.sum.iter().map(|s| s / self.count as f64).collect()
+    }
+}
+
+pub(crate) fn to_db(amplitude: f64) -> f64 {
+    20.0 * amplitude.max(1e-12).log10()
+}
+
+/// The strongest peaks of an amplitude spectrum as (Hz, amplitude),
+/// loudest first: local maxima within [`PEAK_RANGE_DB`] of the loudest.
+/// Each is placed between bins by fitting a parabola to the log
+/// amplitudes around it, which is close to exact for a Hann window.
+pub(crate) fn dominant_frequencies(spectrum: &[f64], bin_hz: f64) -> Vec<Peak> {
+    let mut peaks: Vec<Peak> = (1..spectrum.len().saturating_sub(1))
+        .filter(|&k| spectrum[k] > spectrum[k - 1] && spectrum[k] >= spectrum[k + 1])
+        .map(|k| {
+            let (a, b, c) = (
+                spectrum[k - 1].max(1e-12).ln(),
+                spectrum[k].ln(),
+                spectrum[k + 1].max(1e-12).ln(),
+            );
+            let denominator = a - 2.0 * b + c;
+            let shift = if denominator == 0.0 {
+                0.0
+            } else {
+                0.5 * (a - c) / denominator
+            };
+            let amplitude = (b - 0.25 * (a - c) * shift).exp();
+            ((k as f64 + shift) * bin_hz, amplitude)
+        })
+        .collect();
+    peaks.sort_by(|x, y| y.1.total_cmp(&x.1));
+    let Some(&(_, loudest)) = peaks.first() else {
+        return vec![];
+    };
+    peaks
+        .into_iter()
+        .take_while(|&(_, a)| to_db(a) >= to_db(loudest) - PEAK_RANGE_DB)
+        .take(PEAKS_PER_SEGMENT)
+        .collect()
+}
+
+/// The shortest segment with a frame centred in every one: frames start
+/// half a frame apart.
+pub(crate) fn shortest_segment(sample_rate: u32, frame_len: usize) -> f64 {
+    (frame_len / 2) as f64 / f64::from(sample_rate)
+}
+
+/// The shortest of [`SEGMENT_LENGTHS`], no shorter than `shortest`, that
+/// splits `duration` into at most [`MAX_AUTO_SEGMENTS`].
+pub(crate) fn auto_segment_length(duration: f64, shortest: f64) -> f64 {
+    SEGMENT_LENGTHS
+        .into_iter()
+        .filter(|&s| s >= shortest)
+        .find(|&s| (duration / s).ceil() as usize <= MAX_AUTO_SEGMENTS)
+        .unwrap_or(SEGMENT_LENGTHS[SEGMENT_LENGTHS.len() - 1].max(shortest))
+}
+
+/// Why a typed segment length can't be used, if it can't.
+pub(crate) fn check_segment_length(
+    segment: f64,
+    duration: f64,
+    shortest: f64,
+) -> Result<(), String> {
+    if !(segment.is_finite() && segment >= shortest) {
+        return Err(format!(
+            "The segment length must be at least {} s, the time between frames",
+            format_entry(shortest, 3)
+        ));
+    }
+    if (duration / segment).ceil() as usize > MAX_SEGMENTS {
+        return Err(format!(
+            "That gives over {MAX_SEGMENTS} rows; use at least {} s",
+            format_entry((duration / MAX_SEGMENTS as f64 * 1000.0).ceil() / 1000.0, 3)
+        ));
+    }
+    Ok(())
+}
+
+/// One pass of the STFT over the file: the table's rows, each segment's
+/// peaks from the frames centred in it, and the average spectrum of the
+/// whole file. Frames are summed as they come, per segment and overall.
+pub(crate) fn analyse(wav: &Wav, frame_len: usize, segment: f64) -> (Vec<Segment>, Vec<f64>) {
+    let rate = f64::from(wav.sample_rate);
+    let duration = wav.duration();
+    let count = ((duration / segment).ceil() as usize).clamp(1, MAX_SEGMENTS);
+    let hop = frame_len / 2;
+    let bin_hz = rate / frame_len as f64;
+
+    let mut groups: Vec<SpectrumSum> = (0..count).map(|_| SpectrumSum::default()).collect();
+    let mut whole = SpectrumSum::default();
+    for (f, spectrum) in stft(&wav.samples, frame_len).enumerate() {
+        let centre = (f * hop + frame_len / 2) as f64 / rate;
+        let i = ((centre / segment) as usize).min(count - 1);
+        groups[i].add(&spectrum);
+        whole.add(&spectrum);
+    }
+    let segments = groups
+        .iter()
+        .enumerate()
+        .map(|(i, group)| {
+            let start = i as f64 * segment;
+            let end = (start + segment).min(duration);
+            let peaks = if group.count == 0 {
+                vec![]
+            } else {
+                dominant_frequencies(&group.average(), bin_hz)
+            };
+            Segment { start, end, peaks }
+        })
+        .collect();
+    (segments, whole.average())
+}
+
+/// Runs `:fft <path.wav> [logx]` and returns the spectrum graph it drew.
+pub(crate) fn fft_command(
+    args: &str,
+    l: &mut impl Logger,
+    go: &GraphOptions,
+) -> Option<(String, Plot)> {
+    let args = args.trim();
+    let (path, log_x) = match args.strip_suffix("logx") {
+        Some(path) if path.ends_with(' ') => (path.trim(), true),
+        _ => (args, false),
+    };
+    if path.is_empty() {
+        l.eprint("Usage: :fft <path.wav> [logx]");
+        return None;
+    }
+    let wav = match std::fs::read(Path::new(path))
+        .map_err(|e| format!("Could not read {path}: {e}"))
+        .and_then(|bytes| parse_wav(&bytes))
+    {
+        Ok(wav) => wav,
+        Err(e) => {
+            l.eprint(&e);
+            return None;
+        }
+    };
+
+    let frame_len = frame_length(wav.sample_rate);
+    let bin_hz = f64::from(wav.sample_rate) / frame_len as f64;
+    l.print(&format!(
+        "{path}: {} s at {} Hz, {} channel{}{}; {frame_len}-sample Hann frames ({} Hz apart)",
+        format_entry(wav.duration(), 2),
+        wav.sample_rate,
+        wav.channels,
+        if wav.channels == 1 { "" } else { "s" },
+        if wav.channels == 1 {
+            ""
+        } else {
+            " mixed to mono"
+        },
+        format_entry(bin_hz, 1),
+    ));
+
+    let shortest = shortest_segment(wav.sample_rate, frame_len);
+    let segment = loop {
+        match get_optional_numerical_input::<f64>("segment length in seconds (blank = auto): ", l) {
+            Some(s) => match check_segment_length(s, wav.duration(), shortest) {
+                Ok(()) => break s,
+                Err(e) => l.eprint(&e),
+            },
+            None => break auto_segment_length(wav.duration(), shortest),
+        }
+    };
+
+    let (segments, spectrum) = analyse(&wav, frame_len, segment);
+    let rows: Vec<(String, String)> = segments
+        .into_iter()
+        .map(|segment| {
+            let time = format!("{:.2} - {:.2}", segment.start, segment.end);
+            let loud: Vec<String> = segment
+                .peaks
+                .iter()
+                .filter(|&&(_, a)| to_db(a) >= SILENCE_DB)
+                .map(|&(hz, a)| format!("{} Hz ({} dB)", format_entry(hz, 1), to_db(a).round()))
+                .collect();
+            let heard = if loud.is_empty() {
+                "silence".to_string()
+            } else {
+                loud.join(", ")
+            };
+            (time, heard)
+        })
+        .collect();
+    let width = rows
+        .iter()
+        .map(|(time, _)| time.len())
+        .chain(["time (s)".len()])
+        .max()
+        .unwrap_or(0);
+    l.print(&format!("{:<width$}  dominant frequencies", "time (s)"));
+    for (time, heard) in &rows {
+        l.print(&format!("{time:<width$}  {heard}"));
+    }
+
+    draw_spectrum(&spectrum, bin_hz, log_x, go, l)
+}
+
+/// The file's average spectrum in dB against frequency, the top of the
+/// window at the loudest bin rounded up to 10 dB.
+fn draw_spectrum(
+    spectrum: &[f64],
+    bin_hz: f64,
+    log_x: bool,
+    go: &GraphOptions,
+    l: &mut impl Logger,
+) -> Option<(String, Plot)> {
+    let peak_db = spectrum
+        .iter()
+        .skip(1)
+        .map(|&a| to_db(a))
+        .fold(f64::MIN, f64::max) as f32;
+    let y_max = (peak_db / 10.0).ceil() * 10.0;
+    let y_min = y_max - GRAPH_RANGE_DB;
+    // The 0 Hz bin has no place on a log axis, and is only the offset anyway.
+    let path: Vec<Point> = spectrum
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(k, &a)| Point::new((k as f64 * bin_hz) as f32, (to_db(a) as f32).max(y_min)))
+        .collect();
+    let nyquist = path.last().map_or(1.0, |p| p.x);
+    let x_min = if log_x {
+        LOG_AXIS_MIN_HZ.min(path[0].x)
+    } else {
+        0.0
+    };
+
+    let go = GraphOptions {
+        y_min,
+        y_max,
+        y_fixed: true,
+        x_scale: if log_x { Scale::Log } else { Scale::Linear },
+        y_scale: Scale::Linear,
+        equal_aspect: false,
+        ..*go
+    };
+    let overlay = Overlay {
+        paths: vec![("spectrum (dB)".to_string(), path)],
+        markers: vec![],
+        dots: vec![],
+    };
+    let drawn = graph_with_overlay("", x_min, nyquist, &go, &Definitions::new(), &overlay).ok()?;
+    l.print(&drawn.0);
+    l.print(&format!(
+        "average spectrum: dB (0 = full scale) against Hz{}",
+        if log_x { ", log frequency" } else { "" }
+    ));
+    Some(drawn)
+}
@@ -0,0 +1,313 @@
+//! `:fit`: least-squares curve fitting to x, y data, typed or from a CSV
+//! file.
+//!
+//! Every model is linear in its coefficients once x and/or y are logged,
+//! so they all come down to [`matrix::least_squares`]. The fitted curve is
+//! graphed as an ordinary equation with the data as markers, and can be
+//! kept as a `let` function.
+
+use crate::modules::{
+    bezier_curve::x_window,
+    bindings::{self, LetSource},
+    common::{GraphOptions, Point},
+    error_render,
+    graphing::{graph_with_overlay, Overlay, Plot},
+    inputs::read_user_input,
+    logger::Logger,
+    matrix,
+    repl::Repl,
+    spline::get_points,
+    string_maker::format_entry,
+};
+
+/// Highest polynomial degree offered
+pub(crate) const MAX_DEGREE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Model {
+    /// y = a + b·x
+    Linear,
+    /// y = c0 + c1·x + ... + cn·xⁿ
+    Polynomial(usize),
+    /// y = a·e^(b·x), fitted to ln y
+    Exponential,
+    /// y = a + b·ln x
+    Logarithmic,
+    /// y = a·x^b, fitted to ln y against ln x
+    Power,
+}
+
+impl Model {
+    pub(crate) fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim().to_ascii_lowercase();
+        match s.as_str() {
+            "" | "linear" | "lin" => return Ok(Model::Linear),
+            "exp" | "exponential" => return Ok(Model::Exponential),
+            "log" | "logarithmic" => return Ok(Model::Logarithmic),
+            "power" | "pow" => return Ok(Model::Power),
+            _ => {}
+        }
+        let degree = s
+            .strip_prefix("polynomial")
+            .or_else(|| s.strip_prefix("poly"))
+            .ok_or(format!(
+                "Unknown model '{s}'. Valid: linear, poly <n>, exp, log, power"
+            ))?
+            .trim();
+        match degree.parse::<usize>() {
+            Ok(n) if (1..=MAX_DEGREE).contains(&n) => Ok(Model::Polynomial(n)),
+            _ => Err(format!(
+                "A polynomial needs a degree from 1 to {MAX_DEGREE}, like poly 2"
+            )),
+        }
+    }
+
+    /// The model's general form, as printed above its coefficients.
+    pub(crate) fn form(self) -> String {
+        match self {
+            Model::Linear => "y = a + b*x".to_string(),
+            Model::Polynomial(1) => "y = c0 + c1*x".to_string(),
+            Model::Polynomial(n) => format!("y = c0 + c1*x + ... + c{n}*x^{n}"),
+            Model::Exponential => "y = a*exp(b*x)".to_string(),
+            Model::Logarithmic => "y = a + b*ln(x)".to_string(),
+            Model::Power => "y = a*x^b".to_string(),
+        }
+    }
+
+    fn coefficient_names(self) -> Vec<String> {
+        match self {
+            Model::Polynomial(n) => (0..=n).map(|i| format!("c{i}")).collect(),
+            _ => vec!["a".to_string(), "b".to_string()],
+        }
+    }
+}
+
+/// A fitted model.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Fit {
+    pub model: Model,
+    /// In the order of [`Model::form`]: a, b, or c0 up to cn
+    pub coefficients: Vec<f64>,
+    /// How much of the variation in y the model explains, from the fitted
+    /// curve itself (not its logged form)
+    pub r_squared: f64,
+}
+
+impl Fit {
+    pub(crate) fn value_at(&self, x: f64) -> f64 {
+        let c = &self.coefficients;
+        match self.model {
+            Model::Linear | Model::Polynomial(_) => c.iter().rev().fold(0.0, |acc, c| acc * x + c),
+            Model::Exponential => c[0] * (c[1] * x).exp(),
+            Model::Logarithmic => c[0] + c[1] * x.ln(),
+            Model::Power => c[0] * x.powf(c[1]),
+        }
+    }
+
+    /// The fitted curve as an expression in x, with each coefficient
+    /// written by `number` (given its magnitude; the sign is handled here).
+    pub(crate) fn expression(&self, number: impl Fn(f64) -> String) -> String {
+        let c = &self.coefficients;
+        let signed = |v: f64| {
+            if v < 0.0 {
+                format!("-{}", number(-v))
+            } else {
+                number(v)
+            }
+        };
+        match self.model {
+            Model::Linear | Model::Polynomial(_) => {
+                let terms: Vec<(f64, String)> = c
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|&(_, &v)| v != 0.0)
+                    .map(|(i, &v)| {
+                        let power = match i {
+                            0 => String::new(),
+                            1 => "*x".to_string(),
+                            _ => format!("*x^{i}"),
+                        };
+                        (v, format!("{}{power}", number(v.abs())))
+                    })
+                    .collect();
+                join_terms(&terms)
+            }
+            Model::Exponential => format!("{}*exp({}*x)", signed(c[0]), signed(c[1])),
+            Model::Logarithmic => join_terms(&[
+                (c[0], number(c[0].abs())),
+                (c[1], format!("{}*ln(x)", number(c[1].abs()))),
+            ]),
+            Model::Power => format!("{}*x^({})", signed(c[0]), signed(c[1])),
+        }
+    }
+}
+
+/// `a + b - c` from signed terms, written without their signs.
+fn join_terms(terms: &[(f64, String)]) -> String {
+    let mut s = String::new();
+    for (i, (v, term)) in terms.iter().enumerate() {
+        match (i, *v < 0.0) {
+            (0, true) => s.push('-'),
+            (0, false) => {}
+            (_, true) => s.push_str(" - "),
+            (_, false) => s.push_str(" + "),
+        }
+        s.push_str(term);
+    }
+    if s.is_empty() {
+        s.push('0');
+    }
+    s
+}
+
+/// Fits `model` to the points by least squares.
+pub(crate) fn fit(model: Model, points: &[Point]) -> Result<Fit, String> {
+    let xs: Vec<f64> = points.iter().map(|p| f64::from(p.x)).collect();
+    let ys: Vec<f64> = points.iter().map(|p| f64::from(p.y)).collect();
+    let positive = |vs: &[f64]| vs.iter().all(|&v| v > 0.0);
+
+    // Each model as a straight fit of some function of y to columns of
+    // some function of x.
+    let (columns, targets): (Vec<Vec<f64>>, Vec<f64>) = match model {
+        Model::Linear | Model::Polynomial(_) => {
+            let degree = match model {
+                Model::Polynomial(n) => n,
+                _ => 1,
+            };
+            if points.len() <= degree {
+                return Err(format!(
+                    "A degree-{degree} fit needs at least {} points",
+                    degree + 1
+                ));
+            }
+            let rows = xs
+                .iter()
+                .map(|&x| (0..=degree).map(|i| x.powi(i as i32)).collect())
+                .collect();
+            (rows, ys.clone())
+        }
+        Model::Exponential => {
+            if !positive(&ys) {
+                return Err("An exponential fit needs every y above 0".to_string());
+            }
+            let rows = xs.iter().map(|&x| vec![1.0, x]).collect();
+            (rows, ys.iter().map(|y| y.ln()).collect())
+        }
+        Model::Logarithmic => {
+            if !positive(&xs) {
+                return Err("A logarithmic fit needs every x above 0".to_string());
+            }
+            let rows = xs.iter().map(|&x| vec![1.0, x.ln()]).collect();
+            (rows, ys.clone())
+        }
+        Model::Power => {
+            if !positive(&xs) || !positive(&ys) {
+                return Err("A power fit needs every x and y above 0".to_string());
+            }
+            let rows = xs.iter().map(|&x| vec![1.0, x.ln()]).collect();
+            (rows, ys.iter().map(|y| y.ln()).collect())
+        }
+    };
+
+    let mut coefficients = matrix::least_squares(&columns, &targets)
+        .map_err(|_| "There aren't enough distinct x values for that model".to_string())?;
+    if matches!(model, Model::Exponential | Model::Power) {
+        coefficients[0] = coefficients[0].exp();
+    }
+
+    let mut fitted = Fit {
+        model,
+        coefficients,
+        r_squared: 0.0,
+    };
+    let mean = ys.iter().sum::<f64>() / ys.len() as f64;
+    let total: f64 = ys.iter().map(|y| (y - mean).powi(2)).sum();
+    let residual: f64 = xs
+        .iter()
+        .zip(&ys)
+        .map(|(&x, y)| (y - fitted.value_at(x)).powi(2))
+        .sum();
+    // Flat data is explained perfectly by any model that's flat there.
+    fitted.r_squared = if total == 0.0 {
+        1.0
+    } else {
+        1.0 - residual / total
+    };
+    Ok(fitted)
+}
+
+/// Runs `:fit` and returns the graph it drew.
+pub(crate) fn fit_data(
+    l: &mut impl Logger,
+    go: &GraphOptions,
+    repl: &mut Repl,
+) -> Option<(String, Plot)> {
+    let points = loop {
+        let answer = read_user_input("points (x y, x y, ... or a .csv file): ").ok()?;
+        match get_points(&answer) {
+            Ok(points) => break points,
+            Err(e) => l.eprint(&e),
+        }
+    };
+    let fitted = loop {
+        let answer =
+            read_user_input("model linear/poly <n>/exp/log/power (blank = linear): ").ok()?;
+        match Model::parse(&answer).and_then(|model| fit(model, &points)) {
+            Ok(fitted) => break fitted,
+            Err(e) => l.eprint(&e),
+        }
+    };
+
+    let precision = repl.precision;
+    l.print(&fitted.model.form());
+    for (name, v) in fitted
+        .model
+        .coefficient_names()
+        .iter()
+        .zip(&fitted.coefficients)
+    {
+        l.print(&format!("{name} = {}", format_entry(*v, precision)));
+    }
+    l.print(&format!(
+        "y = {}",
+        fitted.expression(|v| format_entry(v, precision))
+    ));
+    l.print(&format!(
+        "R² = {}",
+        format_entry(fitted.r_squared, precision.max(4))
+    ));
+
+    // Graphed and bound at the engine's full (f32) precision, so the curve
+    // is the fit itself without f64 digits the points never had.
+    let body = fitted.expression(|v| (v as f32).to_string());
+    let (x_min, x_max) = x_window(&points);
+    let overlay = Overlay {
+        paths: vec![],
+        markers: points,
+        dots: vec![],
+    };
+    let drawn = match graph_with_overlay(&body, x_min, x_max, go, &repl.defs, &overlay) {
+        Ok(drawn) => drawn,
+        Err(e) => {
+            l.eprint(&error_render::render_error_with_source(
+                &body, &e, &repl.defs,
+            ));
+            return None;
+        }
+    };
+    l.print(&drawn.0);
+
+    let name =
+        read_user_input("bind as a function (e.g. fit, blank = don't): ").unwrap_or_default();
+    let name = name.strip_suffix("(x)").unwrap_or(&name).trim();
+    if !name.is_empty() {
+        bindings::handle_let(
+            &format!("let {name}(x) = {body}"),
+            repl,
+            l,
+            LetSource::Generated,
+        );
+    }
+    Some(drawn)
+}
@@ -1,9 +1,9 @@
 use crate::modules::{
     common::{
-        get_braille, make_cell_matrix, CellMatrix, CharMatrix, GraphOptions, NormalizedPoint,
-        Point, PointMatrix,
+        draw_line, get_braille, make_cell_matrix, CellMatrix, CharMatrix, GraphOptions,
+        NormalizedPoint, Point, PointMatrix, Scale, Tick,
     },
-    string_maker::make_graph_string,
+    string_maker::{gutter_width, make_graph_string},
 };
 
 use rusty_maths::{
@@ -18,15 +18,61 @@ const Y_RANGE_TOLERANCE: f32 = 50.0;
 // Padding added to y-axis bounds
 const Y_AXIS_PADDING: f32 = 0.5;
 
-// Tick mark display thresholds based on graph width
-const TICK_WIDTH_SMALL: usize = 76;
-const TICK_WIDTH_MEDIUM: usize = 151;
-const TICK_WIDTH_LARGE: usize = 301;
-// Maximum tick marks to display based on graph width
-const TICK_MAX_SMALL: usize = 80;
-const TICK_MAX_MEDIUM: usize = 160;
-const TICK_MAX_LARGE: usize = 300;
-const TICK_MAX_XLARGE: usize = 400;
+// Roughly one labelled x tick per this many glyph columns
+const X_TICK_SPACING: usize = 10;
+// Roughly one labelled y tick per this many glyph rows
+const Y_TICK_SPACING: usize = 3;
+// Ticks beyond this are a degenerate range, not something worth drawing
+const MAX_TICKS: usize = 1000;
+
+/// Where a rendered graph's plot area sits and what it shows, so terminal
+/// cells can be mapped back onto graph coordinates.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GraphLayout {
+    /// x range shown, in axis space
+    pub x_range: (f32, f32),
+    /// y range shown, in axis space
+    pub y_range: (f32, f32),
+    /// Columns of y labels left of the frame
+    pub gutter: usize,
+    pub width: usize,
+    pub height: usize,
+    pub x_scale: Scale,
+    pub y_scale: Scale,
+}
+
+impl GraphLayout {
+    /// Axis-space coordinate at the middle of the braille glyph in terminal
+    /// cell (`col`, `row`), for a frame drawn from the top-left corner.
+    /// Not bounds-checked, so a drag can carry on past the frame.
+    pub(crate) fn cell_to_axis(&self, col: u16, row: u16) -> (f32, f32) {
+        // One glyph is 2 dots wide and 4 tall; the frame's top border and
+        // left edge come before the first glyph.
+        let glyph_x = col as f32 - (self.gutter + 1) as f32;
+        let glyph_y = row as f32 - 1.0;
+        let dot_x = glyph_x * 2.0 + 0.5;
+        let dot_y = self.height as f32 - (glyph_y * 4.0 + 1.5);
+
+        let (x_min, x_max) = self.x_range;
+        let (y_min, y_max) = self.y_range;
+        (
+            x_min + dot_x / self.width as f32 * (x_max - x_min),
+            y_min + dot_y / self.height as f32 * (y_max - y_min),
+        )
+    }
+
+    /// The (x, y) value under terminal cell (`col`, `row`), or `None` when
+    /// the cell is outside the plot area.
+    pub(crate) fn cell_to_point(&self, col: u16, row: u16) -> Option<(f32, f32)> {
+        let glyph_x = (col as usize).checked_sub(self.gutter + 1)?;
+        let glyph_y = (row as usize).checked_sub(1)?;
+        if glyph_x >= self.width / 2 || glyph_y >= self.height / 4 {
+            return None;
+        }
+        let (x, y) = self.cell_to_axis(col, row);
+        Some((self.x_scale.to_value(x), self.y_scale.to_value(y)))
+    }
+}
 
 pub(crate) fn graph(
     eq_str: &str,
@@ -35,80 +81,162 @@ pub(crate) fn graph(
     go: &GraphOptions,
     defs: &Definitions,
 ) -> Result<String, EquationError> {
-    let mut y_min: f32 = go.y_min;
-    let mut y_max: f32 = go.y_max;
-
-    let mut master_y_min: f32 = f32::MAX;
-    let mut master_y_max: f32 = f32::MIN;
-
-    let sampling_factor: f32 = (go.width as f32) / SAMPLING_DIVISOR;
-
-    let x_step: f32 = (x_max - x_min) / ((go.width as f32) * sampling_factor);
+    graph_with_plot(eq_str, x_min, x_max, go, defs).map(|(g, _)| g)
+}
 
-    let eqs: Vec<&str> = eq_str.split('|').collect();
+/// What a graph shows, kept alongside its braille frame so it can be drawn
+/// again another way (a PNG) without resampling.
+#[derive(Debug, Clone)]
+pub(crate) struct Plot {
+    pub layout: GraphLayout,
+    /// One run of sampled points per equation, in axis space
+    pub curves: PointMatrix,
+    /// The `|`-separated equations, in the same order as `curves`
+    pub equations: Vec<String>,
+    /// The overlay's markers and dots, in axis space
+    pub points: Vec<Point>,
+}
 
-    let mut points_collection: PointMatrix = Vec::with_capacity(eqs.len());
+/// [`graph`], also returning what was plotted and where.
+pub(crate) fn graph_with_plot(
+    eq_str: &str,
+    x_min: f32,
+    x_max: f32,
+    go: &GraphOptions,
+    defs: &Definitions,
+) -> Result<(String, Plot), EquationError> {
+    graph_with_overlay(eq_str, x_min, x_max, go, defs, &Overlay::default())
+}
 
-    // Char offset of the current sub-equation within `eq_str`, so error
-    // spans map back onto the full entered text.
-    let mut segment_start = 0;
+/// Shapes drawn on a graph as well as its equations, in value space.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Overlay {
+    /// Named polylines, joined point to point. They are kept in the
+    /// [`Plot`] after the equations, so a PNG draws them too.
+    pub paths: Vec<(String, Vec<Point>)>,
+    /// Points picked out with a small diamond
+    pub markers: Vec<Point>,
+    /// Points drawn as a single dot each, for a scatter plot
+    pub dots: Vec<Point>,
+}
 
-    for eq in eqs {
-        let rm_points =
-            plot_with(eq, x_min, x_max, x_step, defs).map_err(|e| e.offset(segment_start))?;
-        segment_start += eq.chars().count() + 1; // +1 for the '|' separator
-        let points: Vec<Point> = rm_points
-            .into_iter()
-            .map(|p| Point::new(p.x, p.y))
-            .collect();
+impl Overlay {
+    fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.markers.is_empty() && self.dots.is_empty()
+    }
 
-        let y_min_actual: f32 = get_y_min(&points);
-        let y_max_actual: f32 = get_y_max(&points);
+    /// Every point, in axis space.
+    fn axis_points(&self, go: &GraphOptions) -> Vec<Point> {
+        self.paths
+            .iter()
+            .flat_map(|(_, path)| path.iter())
+            .chain(&self.markers)
+            .chain(&self.dots)
+            .map(|p| Point::new(go.x_scale.to_axis(p.x), go.y_scale.to_axis(p.y)))
+            .filter(|p| p.x.is_finite() && p.y.is_finite())
+            .collect()
+    }
+}
 
-        y_max = if abs_f32(y_max - y_max_actual) < Y_RANGE_TOLERANCE {
-            y_max_actual
-        } else {
-            y_max
-        };
+/// [`graph_with_plot`] with an overlay drawn on top. With an overlay the
+/// equations may be left blank; the y range then fits the overlay alone.
+pub(crate) fn graph_with_overlay(
+    eq_str: &str,
+    x_min: f32,
+    x_max: f32,
+    go: &GraphOptions,
+    defs: &Definitions,
+    overlay: &Overlay,
+) -> Result<(String, Plot), EquationError> {
+    let overlay_only = eq_str.trim().is_empty() && !overlay.is_empty();
+    let mut points_collection = if overlay_only {
+        vec![]
+    } else {
+        sample_all(eq_str, x_min, x_max, go, defs)?
+    };
+    let overlay_points = overlay.axis_points(go);
 
-        y_min = if abs_f32(y_min - y_min_actual) < Y_RANGE_TOLERANCE {
-            y_min_actual
+    let (mut master_y_min, mut master_y_max) = if go.y_fixed {
+        // A locked window is used exactly as given: no fitting, no padding.
+        (go.y_scale.to_axis(go.y_min), go.y_scale.to_axis(go.y_max))
+    } else {
+        let (mut lo, mut hi) = if overlay_only {
+            (f32::MAX, f32::MIN)
         } else {
-            y_min
+            fit_y_range(&points_collection, go)
         };
+        // The overlay is always shown whole, however far it strays from
+        // the default window.
+        lo = lo.min(get_y_min(&overlay_points));
+        hi = hi.max(get_y_max(&overlay_points));
+        (lo - Y_AXIS_PADDING, hi + Y_AXIS_PADDING)
+    };
 
-        if y_min < master_y_min {
-            master_y_min = y_min;
-        }
-        if y_max > master_y_max {
-            master_y_max = y_max;
+    // Everything below works in axis space — log10 of the value on a log
+    // axis — so the rest of the pipeline stays linear.
+    let mut ax_min = go.x_scale.to_axis(x_min);
+    let mut ax_max = go.x_scale.to_axis(x_max);
+
+    if go.equal_aspect {
+        // Braille dots are square, so equal aspect means units-per-cell
+        // match on both axes. Grow whichever span is short; a locked y
+        // window always wins, so then x follows it.
+        let cells_ratio = go.height as f32 / go.width as f32;
+        let x_span = ax_max - ax_min;
+        let y_span = master_y_max - master_y_min;
+
+        if (x_span * y_span).is_finite() {
+            if !go.y_fixed && y_span < x_span * cells_ratio {
+                let mid = (master_y_min + master_y_max) / 2.0;
+                let half = x_span * cells_ratio / 2.0;
+                (master_y_min, master_y_max) = (mid - half, mid + half);
+            } else {
+                let mid = (ax_min + ax_max) / 2.0;
+                let half = y_span / cells_ratio / 2.0;
+                (ax_min, ax_max) = (mid - half, mid + half);
+                if !overlay_only {
+                    points_collection = sample_all(
+                        eq_str,
+                        go.x_scale.to_value(ax_min),
+                        go.x_scale.to_value(ax_max),
+                        go,
+                        defs,
+                    )?;
+                }
+            }
         }
-
-        // NaN points (domain holes like sqrt of a negative) fall out in the
-        // y_acc range filter below — NaN fails every comparison.
-        points_collection.push(points);
     }
-
-    master_y_max += Y_AXIS_PADDING;
-    master_y_min -= Y_AXIS_PADDING;
-
     let mut matrix: CellMatrix = make_cell_matrix(go);
 
-    check_add_tick_marks(&mut matrix, x_min, x_max, master_y_min, master_y_max, go);
-
-    for points in points_collection {
+    let x_ticks = axis_ticks(
+        ax_min,
+        ax_max,
+        tick_target(go.width / 2, X_TICK_SPACING),
+        go.x_scale,
+    );
+    let y_ticks = axis_ticks(
+        master_y_min,
+        master_y_max,
+        tick_target(go.height / 4, Y_TICK_SPACING),
+        go.y_scale,
+    );
+
+    add_tick_marks(
+        &mut matrix,
+        &x_ticks,
+        &y_ticks,
+        (ax_min, ax_max),
+        (master_y_min, master_y_max),
+        go,
+    );
+
+    for points in &points_collection {
         // Within a column the curve sweeps a contiguous band of rows, so
         // record each column's min/max row and fill the span between them.
         let mut col_lo = vec![usize::MAX; go.width + 1];
         let mut col_hi = vec![0usize; go.width + 1];
-        for np in get_normalized_points(
-            go.height,
-            master_y_min,
-            master_y_max,
-            &points,
-            sampling_factor,
-        )
-        .filter(|np| np.y_acc <= master_y_max && np.y_acc >= master_y_min)
+        for np in get_normalized_points(go, (ax_min, ax_max), (master_y_min, master_y_max), points)
+            .filter(|np| np.y_acc <= master_y_max && np.y_acc >= master_y_min)
         {
             if np.x <= go.width {
                 col_lo[np.x] = col_lo[np.x].min(np.y);
@@ -124,89 +252,377 @@ pub(crate) fn graph(
         }
     }
 
-    check_add_x_axis(master_y_min, master_y_max, go.height, &mut matrix);
+    draw_overlay(
+        &mut matrix,
+        overlay,
+        (ax_min, ax_max),
+        (master_y_min, master_y_max),
+        go,
+    );
+
+    // A log axis has no zero, so the opposite axis line has nowhere to sit.
+    if go.y_scale == Scale::Linear {
+        check_add_x_axis(master_y_min, master_y_max, go.height, &mut matrix);
+    }
 
     matrix.reverse();
 
-    check_add_y_axis(x_min, x_max, go.width, &mut matrix);
+    if go.x_scale == Scale::Linear {
+        check_add_y_axis(ax_min, ax_max, go.width, &mut matrix);
+    }
 
     let braille_chars: CharMatrix = get_braille(go, &matrix);
 
-    Ok(make_graph_string(
-        braille_chars,
-        x_min,
-        x_max,
-        master_y_min,
-        master_y_max,
-    ))
+    let x_labels = x_tick_labels(&x_ticks, ax_min, ax_max, go.width);
+    let y_labels = y_tick_labels(&y_ticks, master_y_min, master_y_max, go.height);
+
+    let layout = GraphLayout {
+        x_range: (ax_min, ax_max),
+        y_range: (master_y_min, master_y_max),
+        gutter: gutter_width(&y_labels),
+        width: go.width,
+        height: go.height,
+        x_scale: go.x_scale,
+        y_scale: go.y_scale,
+    };
+
+    let mut equations: Vec<String> = if overlay_only {
+        vec![]
+    } else {
+        eq_str.split('|').map(|eq| eq.trim().to_string()).collect()
+    };
+    for (name, path) in &overlay.paths {
+        equations.push(name.clone());
+        points_collection.push(
+            path.iter()
+                .map(|p| Point::new(go.x_scale.to_axis(p.x), go.y_scale.to_axis(p.y)))
+                .collect(),
+        );
+    }
+
+    let plot = Plot {
+        layout,
+        curves: points_collection,
+        equations,
+        points: overlay
+            .markers
+            .iter()
+            .chain(&overlay.dots)
+            .map(|p| Point::new(go.x_scale.to_axis(p.x), go.y_scale.to_axis(p.y)))
+            .collect(),
+    };
+
+    Ok((make_graph_string(braille_chars, &x_labels, &y_labels), plot))
 }
 
-fn check_add_tick_marks(
-    matrix: &mut CellMatrix,
+/// Each `|`-separated equation's value at `x` (NaN where it is undefined).
+pub(crate) fn curve_values(eq_str: &str, x: f32, defs: &Definitions) -> Vec<f32> {
+    eq_str
+        .split('|')
+        .map(|eq| {
+            plot_with(eq, x, x, 1.0, defs)
+                .ok()
+                .and_then(|points| points.first().map(|p| p.y))
+                .unwrap_or(f32::NAN)
+        })
+        .collect()
+}
+
+/// Samples every `|`-separated equation in `eq_str`.
+fn sample_all(
+    eq_str: &str,
     x_min: f32,
     x_max: f32,
-    y_min: f32,
-    y_max: f32,
     go: &GraphOptions,
-) {
-    let max = if go.width < TICK_WIDTH_SMALL {
-        TICK_MAX_SMALL
-    } else if go.width < TICK_WIDTH_MEDIUM {
-        TICK_MAX_MEDIUM
-    } else if go.width < TICK_WIDTH_LARGE {
-        TICK_MAX_LARGE
-    } else {
-        TICK_MAX_XLARGE
-    };
+    defs: &Definitions,
+) -> Result<PointMatrix, EquationError> {
+    let eqs: Vec<&str> = eq_str.split('|').collect();
 
-    let x_range = x_min.ceil() as isize..=x_max.floor() as isize;
+    let mut points_collection: PointMatrix = Vec::with_capacity(eqs.len());
 
-    let y_start = y_min.ceil() as isize;
-    let y_end = y_max.floor() as isize;
+    // Char offset of the current sub-equation within `eq_str`, so error
+    // spans map back onto the full entered text.
+    let mut segment_start = 0;
 
-    let x_scale = (x_max - x_min) / (go.width as f32);
-    let y_scale = (y_max - y_min) / (go.height as f32);
+    for eq in eqs {
+        let points = sample(eq, x_min, x_max, go, defs).map_err(|e| e.offset(segment_start))?;
+        segment_start += eq.chars().count() + 1; // +1 for the '|' separator
 
-    let mut points: Vec<(usize, usize)> = vec![];
-    for x in x_range {
-        let x_normalized = ((x as f32 - x_min) / x_scale).round() as usize;
+        // NaN points (domain holes like sqrt of a negative, or anything
+        // non-positive on a log axis) fall out in the y_acc range filter
+        // in graph() — NaN fails every comparison.
+        points_collection.push(points);
+    }
 
-        for y in y_start..=y_end {
-            let y_normalized = ((y as f32 - y_min) / y_scale).round() as usize;
+    Ok(points_collection)
+}
 
-            if let Some(row) = matrix.get_mut(y_normalized) {
-                if row.get_mut(x_normalized).is_some() {
-                    points.push((x_normalized, y_normalized));
-                }
+/// The y range (axis space, unpadded) that fits every curve, following the
+/// `Y_RANGE_TOLERANCE` rule: a curve's own extent is used when it lies near
+/// the default window, otherwise the default bound is kept.
+fn fit_y_range(points_collection: &PointMatrix, go: &GraphOptions) -> (f32, f32) {
+    let mut y_min: f32 = go.y_min;
+    let mut y_max: f32 = go.y_max;
+
+    let mut master_y_min: f32 = f32::MAX;
+    let mut master_y_max: f32 = f32::MIN;
+
+    for points in points_collection {
+        let y_min_actual: f32 = get_y_min(points);
+        let y_max_actual: f32 = get_y_max(points);
+
+        if go.y_scale == Scale::Log {
+            // The -7..7 default means nothing in decades; always fit.
+            y_max = y_max_actual;
+            y_min = y_min_actual;
+        } else {
+            y_max = if abs_f32(y_max - y_max_actual) < Y_RANGE_TOLERANCE {
+                y_max_actual
+            } else {
+                y_max
+            };
+
+            y_min = if abs_f32(y_min - y_min_actual) < Y_RANGE_TOLERANCE {
+                y_min_actual
+            } else {
+                y_min
+            };
+        }
+
+        if y_min < master_y_min {
+            master_y_min = y_min;
+        }
+        if y_max > master_y_max {
+            master_y_max = y_max;
+        }
+    }
+
+    (master_y_min, master_y_max)
+}
+
+/// Samples one equation across the x window and maps the points into axis
+/// space. A log x axis is sampled geometrically: each output column covers
+/// an equal slice of decades and gets its own evenly spaced run of samples.
+fn sample(
+    eq: &str,
+    x_min: f32,
+    x_max: f32,
+    go: &GraphOptions,
+    defs: &Definitions,
+) -> Result<Vec<Point>, EquationError> {
+    let sampling_factor: f32 = (go.width as f32) / SAMPLING_DIVISOR;
+
+    let rm_points = match go.x_scale {
+        Scale::Linear => {
+            let x_step: f32 = (x_max - x_min) / ((go.width as f32) * sampling_factor);
+            plot_with(eq, x_min, x_max, x_step, defs)?
+        }
+        Scale::Log => {
+            let lo = x_min.log10();
+            let decades_per_col = (x_max.log10() - lo) / go.width as f32;
+            let mut rm_points = Vec::new();
+            for col in 0..go.width {
+                let a = 10_f32.powf(lo + col as f32 * decades_per_col);
+                let b = 10_f32.powf(lo + (col + 1) as f32 * decades_per_col);
+                rm_points.extend(plot_with(eq, a, b, (b - a) / sampling_factor, defs)?);
             }
+            rm_points
         }
+    };
+
+    Ok(rm_points
+        .into_iter()
+        .map(|p| Point::new(go.x_scale.to_axis(p.x), go.y_scale.to_axis(p.y)))
+        .collect())
+}
+
+/// Tick positions (in axis space) and their labels for one axis.
+pub(crate) fn axis_ticks(lo: f32, hi: f32, target: usize, scale: Scale) -> Vec<(f32, String)> {
+    match scale {
+        Scale::Linear => {
+            let ticks = nice_ticks(lo, hi, target);
+            let step = tick_step(&ticks);
+            ticks
+                .into_iter()
+                .map(|v| (v, format_tick(v, step)))
+                .collect()
+        }
+        Scale::Log => decade_ticks(lo, hi, target),
     }
+}
 
-    if points.len() <= max {
-        for (x, y) in points {
-            if let Some(row) = matrix.get_mut(y) {
-                if let Some(cell) = row.get_mut(x) {
-                    cell.value = true;
-                }
+/// Ticks for a log axis spanning `lo..=hi` decades: one per decade (or per
+/// every few decades on long ranges). Windows narrower than two decades
+/// fall back to round values placed logarithmically.
+fn decade_ticks(lo: f32, hi: f32, target: usize) -> Vec<(f32, String)> {
+    if !(hi - lo).is_finite() || hi <= lo {
+        return vec![];
+    }
+    let first = lo.ceil() as i64;
+    let last = hi.floor() as i64;
+
+    if last - first < 1 {
+        let ticks = nice_ticks(10_f32.powf(lo), 10_f32.powf(hi), target);
+        let step = tick_step(&ticks);
+        return ticks
+            .into_iter()
+            .filter(|v| *v > 0.0)
+            .map(|v| (v.log10(), format_tick(v, step)))
+            .collect();
+    }
+
+    let stride = (nice_step(hi - lo, target).ceil() as i64).max(1);
+    (first..=last)
+        .filter(|k| k.rem_euclid(stride) == 0)
+        .map(|k| (k as f32, decade_label(k)))
+        .collect()
+}
+
+/// `10^k` as a label: plain digits near 1, `1e<k>` beyond that.
+fn decade_label(k: i64) -> String {
+    if (-3..=4).contains(&k) {
+        let v = 10_f32.powi(k as i32);
+        format_tick(v, v)
+    } else {
+        format!("1e{k}")
+    }
+}
+
+/// How many ticks to aim for along an edge `glyphs` characters long.
+fn tick_target(glyphs: usize, spacing: usize) -> usize {
+    (glyphs / spacing).max(2)
+}
+
+/// Tick spacing of the form 1, 2 or 5 × 10^n that splits `range` into
+/// roughly `target` steps.
+pub(crate) fn nice_step(range: f32, target: usize) -> f32 {
+    let raw = range / target.max(1) as f32;
+    let magnitude = 10_f32.powf(raw.log10().floor());
+    let normalized = raw / magnitude;
+
+    let nice = if normalized < 1.5 {
+        1.0
+    } else if normalized < 3.0 {
+        2.0
+    } else if normalized < 7.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+/// Every multiple of the nice step inside `min..=max`. Empty when the range
+/// is degenerate (inverted, non-finite, or absurdly many ticks).
+pub(crate) fn nice_ticks(min: f32, max: f32, target: usize) -> Vec<f32> {
+    let range = max - min;
+    if !range.is_finite() || range <= 0.0 {
+        return vec![];
+    }
+    let step = nice_step(range, target);
+    // Index by multiple rather than accumulating, so 0.1 + 0.1 + ... drift
+    // can't push a label off its value.
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    if last < first || (last - first) as usize > MAX_TICKS {
+        return vec![];
+    }
+    (first..=last).map(|k| k as f32 * step).collect()
+}
+
+/// Formats a tick value with just enough decimals for its step, so a 0.25
+/// step reads `0.25` and a 50 step reads `50`, never `50.00` or `-0`.
+pub(crate) fn format_tick(value: f32, step: f32) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    let s = format!("{value:.decimals$}");
+    let unsigned = s.trim_start_matches('-');
+    if unsigned.chars().all(|c| c == '0' || c == '.') {
+        unsigned.to_string()
+    } else {
+        s
+    }
+}
+
+/// Step between consecutive ticks, for label formatting.
+fn tick_step(ticks: &[f32]) -> f32 {
+    match ticks {
+        [a, b, ..] => b - a,
+        _ => 1.0,
+    }
+}
+
+/// Cell column (from the left) that `x` lands in.
+fn x_to_col(x: f32, x_min: f32, x_max: f32, width: usize) -> usize {
+    ((x - x_min) / (x_max - x_min) * width as f32).round() as usize
+}
+
+/// Cell row (from the bottom, before the matrix is flipped) that `y` lands in.
+fn y_to_row(y: f32, y_min: f32, y_max: f32, height: usize) -> usize {
+    ((y - y_min) / (y_max - y_min) * height as f32).round() as usize
+}
+
+/// Puts a dot at every tick intersection — a light grid that lines curve
+/// points up with the labels on the frame.
+fn add_tick_marks(
+    matrix: &mut CellMatrix,
+    x_ticks: &[(f32, String)],
+    y_ticks: &[(f32, String)],
+    (x_min, x_max): (f32, f32),
+    (y_min, y_max): (f32, f32),
+    go: &GraphOptions,
+) {
+    for &(y, _) in y_ticks {
+        let row = y_to_row(y, y_min, y_max, go.height);
+        for &(x, _) in x_ticks {
+            let col = x_to_col(x, x_min, x_max, go.width);
+            if let Some(cell) = matrix.get_mut(row).and_then(|r| r.get_mut(col)) {
+                cell.value = true;
             }
         }
     }
 }
 
-pub(crate) fn get_normalized_points(
-    height: usize,
-    y_min: f32,
-    y_max: f32,
-    points: &[Point],
-    sampling_factor: f32,
-) -> impl Iterator<Item = NormalizedPoint> + '_ {
-    let y_step = (y_max - y_min) / height as f32;
+fn x_tick_labels(ticks: &[(f32, String)], x_min: f32, x_max: f32, width: usize) -> Vec<Tick> {
+    ticks
+        .iter()
+        .map(|(x, label)| Tick {
+            pos: x_to_col(*x, x_min, x_max, width) / 2,
+            label: label.clone(),
+        })
+        .filter(|t| t.pos < width / 2)
+        .collect()
+}
 
-    let inverse_samp_factor = 1.0 / sampling_factor;
+fn y_tick_labels(ticks: &[(f32, String)], y_min: f32, y_max: f32, height: usize) -> Vec<Tick> {
+    ticks
+        .iter()
+        .filter_map(|(y, label)| {
+            // Rows count up from the bottom; glyph rows count down from the top.
+            let from_top = height.checked_sub(y_to_row(*y, y_min, y_max, height))?;
+            Some(Tick {
+                pos: from_top / 4,
+                label: label.clone(),
+            })
+        })
+        .filter(|t| t.pos < height / 4)
+        .collect()
+}
+
+/// Maps axis-space points onto cells. Columns come from each point's own x
+/// (not its sample index) so geometric log-x sampling lands correctly.
+pub(crate) fn get_normalized_points<'a>(
+    go: &GraphOptions,
+    (x_min, x_max): (f32, f32),
+    (y_min, y_max): (f32, f32),
+    points: &'a [Point],
+) -> impl Iterator<Item = NormalizedPoint> + 'a {
+    let height = go.height;
+    let x_scale = go.width as f32 / (x_max - x_min);
+    let y_step = (y_max - y_min) / height as f32;
 
     // Lazy: the caller filters and drains this straight into the matrix.
-    points.iter().enumerate().map(move |(i, point)| {
-        let x = ((i as f32) * inverse_samp_factor) as usize;
+    points.iter().map(move |point| {
+        let x = ((point.x - x_min) * x_scale) as usize;
 
         // Rows are evenly spaced (y_min + n*y_step), so map y to its nearest row directly.
         let y = if y_step == 0.0 {
@@ -225,6 +641,98 @@ pub(crate) fn get_normalized_points(
     })
 }
 
+/// Draws the overlay's paths, markers and dots. Points are placed on the same
+/// unrounded grid the tick marks use, and rows count from the bottom, as
+/// the matrix hasn't been flipped yet.
+fn draw_overlay(
+    matrix: &mut CellMatrix,
+    overlay: &Overlay,
+    (x_min, x_max): (f32, f32),
+    (y_min, y_max): (f32, f32),
+    go: &GraphOptions,
+) {
+    let to_cell = |p: &Point| {
+        let x = go.x_scale.to_axis(p.x);
+        let y = go.y_scale.to_axis(p.y);
+        (
+            (x - x_min) / (x_max - x_min) * go.width as f32,
+            (y - y_min) / (y_max - y_min) * go.height as f32,
+        )
+    };
+    let bounds = (go.width as f32, go.height as f32);
+
+    for (_, path) in &overlay.paths {
+        for pair in path.windows(2) {
+            if let Some((a, b)) = clip_segment(to_cell(&pair[0]), to_cell(&pair[1]), bounds) {
+                line_between(matrix, a, b);
+            }
+        }
+    }
+
+    for marker in &overlay.markers {
+        let (x, y) = to_cell(marker);
+        let corners = [(x - 2.0, y), (x, y + 2.0), (x + 2.0, y), (x, y - 2.0)];
+        for i in 0..4 {
+            if let Some((a, b)) = clip_segment(corners[i], corners[(i + 1) % 4], bounds) {
+                line_between(matrix, a, b);
+            }
+        }
+    }
+
+    for dot in &overlay.dots {
+        let (x, y) = to_cell(dot);
+        if (0.0..=bounds.0).contains(&x) && (0.0..=bounds.1).contains(&y) {
+            matrix[y.round() as usize][x.round() as usize].value = true;
+        }
+    }
+}
+
+fn line_between(matrix: &mut CellMatrix, (x1, y1): (f32, f32), (x2, y2): (f32, f32)) {
+    let cell = |v: f32| v.round() as usize;
+    draw_line(matrix, cell(x1), cell(y1), cell(x2), cell(y2));
+}
+
+/// The part of the segment `a`–`b` inside `0..=width` × `0..=height`
+/// (Liang–Barsky), or `None` when none of it is.
+pub(crate) fn clip_segment(
+    a: (f32, f32),
+    b: (f32, f32),
+    (width, height): (f32, f32),
+) -> Option<((f32, f32), (f32, f32))> {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    if ![a.0, a.1, dx, dy].iter().all(|v| v.is_finite()) {
+        return None;
+    }
+    let (mut t0, mut t1) = (0.0_f32, 1.0_f32);
+    // Each edge as (-direction, distance inside it).
+    for (p, q) in [
+        (-dx, a.0),
+        (dx, width - a.0),
+        (-dy, a.1),
+        (dy, height - a.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = q / p;
+        if p < 0.0 {
+            t0 = t0.max(t);
+        } else {
+            t1 = t1.min(t);
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+    Some((
+        (a.0 + t0 * dx, a.1 + t0 * dy),
+        (a.0 + t1 * dx, a.1 + t1 * dy),
+    ))
+}
+
 fn check_add_x_axis(y_min: f32, y_max: f32, height: usize, matrix: &mut CellMatrix) {
     let (x_axis_in_view, x_axis_row): (bool, usize) = x_y_axis_setup(y_min, y_max, height);
 
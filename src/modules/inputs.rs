@@ -2,39 +2,126 @@ use rusty_maths::{
     equation_analyzer::{calculator::calculate_with, Definitions},
     linear_algebra::Matrix,
 };
-use std::error::Error;
+use std::{error::Error, path::Path};
 
-use crate::modules::logger::Logger;
+use crate::modules::{
+    arrays::{evaluate_array, is_array_expression, Value},
+    logger::Logger,
+    matrix,
+    repl::Repl,
+    string_maker::make_matrix_string,
+};
 
 use linefeed::{Interface, ReadResult};
 
-pub(crate) fn get_matrix_input(l: &mut impl Logger) -> Matrix {
-    let vec_amount: usize = get_numerical_input("vector count: ", l);
-    let mut m: Matrix = Vec::with_capacity(vec_amount);
-    if let Ok(entry_fn) = read_user_input("entry fn: ") {
-        // Run the entry fn through the engine as a real function of x (the
-        // entry's index) — textual substitution would corrupt names that
-        // contain an 'x', like max or exp.
-        let mut defs = Definitions::new();
-        if defs.define_function("f", &entry_fn).is_err() || defs.validate_function("f").is_err() {
-            l.eprint(&format!("'{entry_fn}' is not a valid entry fn"));
-            return m;
+/// Asks for a matrix: an array expression like `[[1, 2], [3, 4]]` or a
+/// bound `m`, rows of numbers pasted a line at a time, a .csv file, or `fn`
+/// to build it from an entry fn. Anything but an expression is previewed
+/// before it's used. Blank cancels.
+pub(crate) fn get_matrix_input(name: &str, repl: &Repl, l: &mut impl Logger) -> Option<Matrix> {
+    loop {
+        let answer = read_user_input(&format!(
+            "{name} ([[1, 2], [3, 4]], rows, a .csv file or fn): "
+        ))
+        .ok()?;
+        if answer.is_empty() {
+            return None;
+        }
+        if is_array_expression(&answer, &repl.arrays) {
+            match typed_matrix(&answer, repl) {
+                Ok(m) => return Some(m),
+                Err(e) => l.eprint(&e),
+            }
+            continue;
         }
-        for i in 0..vec_amount {
-            let vec_size: usize = get_numerical_input(&format!("vector {i} size: "), l);
-            m.push(Vec::with_capacity(vec_size));
-            for x in 0..vec_size {
-                match calculate_with(&format!("f({x})"), &defs) {
-                    Ok(v) => m[i].push(f64::from(v)),
-                    Err(e) => {
-                        l.eprint(&format!("Entry fn failed at x = {x}: {}", e.message));
-                        return m;
+
+        let m = if answer.to_ascii_lowercase().ends_with(".csv") {
+            std::fs::read_to_string(Path::new(&answer))
+                .map_err(|e| format!("Could not read {answer}: {e}"))
+                .and_then(|text| matrix::read_rows(&text))
+        } else if answer == "fn" {
+            entry_fn_matrix(l)
+        } else {
+            let mut rows = answer;
+            for n in 2.. {
+                match read_user_input(&format!("row {n} (blank = done): ")) {
+                    Ok(row) if !row.is_empty() => {
+                        rows.push('\n');
+                        rows.push_str(&row);
                     }
+                    _ => break,
+                }
+            }
+            matrix::read_rows(&rows)
+        };
+
+        match m {
+            Ok(m) => {
+                let (rows, cols) = matrix::shape(&m);
+                l.print(&format!("{name}: {rows}×{cols}"));
+                l.print(&make_matrix_string(&m, repl.precision));
+                if confirm("use it? (y/n, blank = y): ", true, l)? {
+                    return Some(m);
                 }
             }
+            Err(e) => l.eprint(&e),
         }
     }
-    m
+}
+
+/// An array expression typed for a matrix, evaluated with the session's
+/// bindings. A vector is one row.
+pub(crate) fn typed_matrix(answer: &str, repl: &Repl) -> Result<Matrix, String> {
+    match evaluate_array(answer, &repl.defs, &repl.arrays) {
+        Ok(Value::Matrix(m)) => Ok(m),
+        Ok(Value::Vector(v)) => Ok(vec![v]),
+        Ok(n @ Value::Number(_)) => Err(format!("'{answer}' is {}, not a matrix", n.kind())),
+        Err(e) => Err(e.render_with_source(answer, &repl.defs)),
+    }
+}
+
+/// Asks until the answer is `y` or `yes` (true), `n` or `no` (false), or
+/// blank (`blank`). `None` when input ends.
+pub(crate) fn confirm(prompt: &str, blank: bool, l: &mut impl Logger) -> Option<bool> {
+    loop {
+        let answer = read_user_input(prompt).ok()?.to_ascii_lowercase();
+        match answer.as_str() {
+            "" => return Some(blank),
+            "y" | "yes" => return Some(true),
+            "n" | "no" => return Some(false),
+            _ => l.eprint(&format!("'{answer}' is not y or n")),
+        }
+    }
+}
+
+/// Builds a matrix row by row from an entry fn of x, the entry's index.
+fn entry_fn_matrix(l: &mut impl Logger) -> Result<Matrix, String> {
+    let vec_amount: usize = get_numerical_input("vector count: ", l);
+    let entry_fn = read_user_input("entry fn: ").map_err(|e| e.to_string())?;
+    // Run the entry fn through the engine as a real function of x (the
+    // entry's index) — textual substitution would corrupt names that
+    // contain an 'x', like max or exp.
+    let mut defs = Definitions::new();
+    if defs.define_function("f", &entry_fn).is_err() || defs.validate_function("f").is_err() {
+        return Err(format!("'{entry_fn}' is not a valid entry fn"));
+    }
+    let mut m: Matrix = Vec::with_capacity(vec_amount);
+    for i in 0..vec_amount {
+        let vec_size: usize = get_numerical_input(&format!("vector {i} size: "), l);
+        let row = (0..vec_size)
+            .map(|x| {
+                calculate_with(&format!("f({x})"), &defs)
+                    .map(f64::from)
+                    .map_err(|e| format!("Entry fn failed at x = {x}: {}", e.message))
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        m.push(row);
+    }
+    if m.iter().all(Vec::is_empty) {
+        return Err("The matrix is empty".to_string());
+    }
+    matrix::check_rows(&m)?;
+    Ok(m)
 }
 
 pub(crate) fn get_numerical_input<T>(msg: &str, l: &mut impl Logger) -> T
@@ -53,6 +140,28 @@ where
     }
 }
 
+/// Like [`get_numerical_input`], but a blank line (or unreadable input)
+/// means "no value".
+pub(crate) fn get_optional_numerical_input<T>(msg: &str, l: &mut impl Logger) -> Option<T>
+where
+    T: std::str::FromStr,
+    <T as std::str::FromStr>::Err: std::fmt::Display,
+{
+    loop {
+        match read_user_input(msg) {
+            Ok(s) if s.is_empty() => return None,
+            Ok(s) => match s.parse::<T>() {
+                Ok(x) => return Some(x),
+                Err(_) => l.eprint(&format!("'{s}' is not a valid number")),
+            },
+            Err(e) => {
+                l.eprint(&format!("Failed to read input: {e}"));
+                return None;
+            }
+        }
+    }
+}
+
 pub fn read_user_input(prompt: &str) -> Result<String, Box<dyn Error>> {
     let interface = Interface::new("rmr-input")?;
     interface.set_prompt(prompt)?;
@@ -90,3 +199,19 @@ pub(crate) fn get_g_inputs(l: &mut impl Logger) -> (String, f32, f32) {
         }
     }
 }
+
+/// Optional y window for a graph. Leaving either prompt blank keeps the
+/// range automatic.
+pub(crate) fn get_y_range_inputs(l: &mut impl Logger) -> Option<(f32, f32)> {
+    loop {
+        let y_min: f32 = get_optional_numerical_input("y min (blank = auto): ", l)?;
+        let y_max: f32 = get_optional_numerical_input("y max (blank = auto): ", l)?;
+
+        if y_min < y_max {
+            return Some((y_min, y_max));
+        }
+        l.eprint(&format!(
+            "y min `{y_min}` must be less than y max `{y_max}`"
+        ));
+    }
+}
@@ -0,0 +1,471 @@
+//! Dense matrix arithmetic for `:la`, on rusty-maths' row-major `Matrix`.
+//! Everything here is for the small matrices people type at a prompt, so
+//! the algorithms favour being short and stable over being fast.
+
+use rusty_maths::linear_algebra::Matrix;
+
+/// Largest matrix `eigenvalues` will try.
+pub(crate) const MAX_EIGEN_SIZE: usize = 10;
+// QR sweeps allowed per eigenvalue before giving up
+const MAX_EIGEN_SWEEPS: usize = 200;
+// Entries this small next to the matrix's largest count as zero
+const RELATIVE_TOLERANCE: f64 = 1e-10;
+
+/// A real or complex eigenvalue, as (real part, imaginary part).
+pub(crate) type Eigenvalue = (f64, f64);
+
+fn parse_row(s: &str) -> Result<Vec<f64>, String> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            t.parse::<f64>()
+                .map_err(|_| format!("'{t}' is not a number"))
+        })
+        .collect()
+}
+
+/// Every row as long as the first.
+pub(crate) fn check_rows(m: &Matrix) -> Result<(), String> {
+    let width = m.first().map_or(0, Vec::len);
+    match m.iter().position(|row| row.len() != width) {
+        Some(i) => Err(format!(
+            "Row {} has {} entries but row 1 has {width}",
+            i + 1,
+            m[i].len()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// A matrix from lines of numbers split on commas and/or spaces, one row a
+/// line — pasted rows or a CSV file. Blank lines are skipped, and so is a
+/// first line without a single number in it (a header).
+pub(crate) fn read_rows(text: &str) -> Result<Matrix, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .peekable();
+    if let Some((_, first)) = lines.peek() {
+        let is_header = first
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .all(|t| t.parse::<f64>().is_err());
+        if is_header {
+            lines.next();
+        }
+    }
+
+    let mut m: Matrix = Vec::new();
+    let mut first: Option<(usize, usize)> = None;
+    for (n, line) in lines {
+        let row = parse_row(line).map_err(|e| format!("line {n}: {e}"))?;
+        match first {
+            None => first = Some((n, row.len())),
+            Some((f, width)) if row.len() != width => {
+                return Err(format!(
+                    "line {n} has {} numbers but line {f} has {width}",
+                    row.len()
+                ))
+            }
+            Some(_) => {}
+        }
+        m.push(row);
+    }
+    if m.is_empty() {
+        return Err("The matrix is empty".to_string());
+    }
+    Ok(m)
+}
+
+/// (rows, columns)
+pub(crate) fn shape(m: &Matrix) -> (usize, usize) {
+    (m.len(), m.first().map_or(0, Vec::len))
+}
+
+fn square(m: &Matrix, what: &str) -> Result<usize, String> {
+    match shape(m) {
+        (r, c) if r == c => Ok(r),
+        (r, c) => Err(format!(
+            "Only a square matrix has {what}, not a {r}×{c} one"
+        )),
+    }
+}
+
+pub(crate) fn identity(n: usize) -> Matrix {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect()
+}
+
+pub(crate) fn transpose(m: &Matrix) -> Matrix {
+    let (rows, cols) = shape(m);
+    (0..cols)
+        .map(|j| (0..rows).map(|i| m[i][j]).collect())
+        .collect()
+}
+
+pub(crate) fn multiply(a: &Matrix, b: &Matrix) -> Result<Matrix, String> {
+    let (ar, ac) = shape(a);
+    let (br, bc) = shape(b);
+    if ac != br {
+        return Err(format!(
+            "A {ar}×{ac} matrix can't multiply a {br}×{bc} one: {ac} columns but {br} rows"
+        ));
+    }
+    Ok((0..ar)
+        .map(|i| {
+            (0..bc)
+                .map(|j| (0..ac).map(|k| a[i][k] * b[k][j]).sum())
+                .collect()
+        })
+        .collect())
+}
+
+pub(crate) fn trace(m: &Matrix) -> Result<f64, String> {
+    let n = square(m, "a trace")?;
+    Ok((0..n).map(|i| m[i][i]).sum())
+}
+
+/// The size below which an entry of `m` counts as zero.
+fn tolerance(m: &Matrix) -> f64 {
+    let largest = m.iter().flatten().fold(0.0_f64, |acc, v| acc.max(v.abs()));
+    largest * RELATIVE_TOLERANCE
+}
+
+/// `PA = LU` with partial pivoting, as (P, L, U). L has a unit diagonal.
+pub(crate) fn lu(m: &Matrix) -> Result<(Matrix, Matrix, Matrix), String> {
+    let n = square(m, "an LU decomposition")?;
+    let (perm, _, l, u) = lu_parts(m, n);
+    let p = perm
+        .iter()
+        .map(|&from| (0..n).map(|j| if j == from { 1.0 } else { 0.0 }).collect())
+        .collect();
+    Ok((p, l, u))
+}
+
+/// The row order, the number of swaps, L and U.
+fn lu_parts(m: &Matrix, n: usize) -> (Vec<usize>, usize, Matrix, Matrix) {
+    let tol = tolerance(m);
+    let mut u = m.clone();
+    let mut l = identity(n);
+    let mut perm: Vec<usize> = (0..n).collect();
+    let mut swaps = 0;
+
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&a, &b| u[a][k].abs().total_cmp(&u[b][k].abs()))
+            .unwrap_or(k);
+        if pivot != k {
+            u.swap(pivot, k);
+            perm.swap(pivot, k);
+            // Only the multipliers found so far move with the row.
+            let (above, below) = l.split_at_mut(pivot);
+            above[k][..k].swap_with_slice(&mut below[0][..k]);
+            swaps += 1;
+        }
+        if u[k][k].abs() <= tol {
+            continue;
+        }
+        let (pivot_rows, rest) = u.split_at_mut(k + 1);
+        let pivot_row = &pivot_rows[k];
+        for (i, row) in rest.iter_mut().enumerate() {
+            let factor = row[k] / pivot_row[k];
+            l[k + 1 + i][k] = factor;
+            for (v, p) in row[k..].iter_mut().zip(&pivot_row[k..]) {
+                *v -= factor * p;
+            }
+            row[k] = 0.0;
+        }
+    }
+    (perm, swaps, l, u)
+}
+
+pub(crate) fn determinant(m: &Matrix) -> Result<f64, String> {
+    let n = square(m, "a determinant")?;
+    let (_, swaps, _, u) = lu_parts(m, n);
+    let sign = if swaps.is_multiple_of(2) { 1.0 } else { -1.0 };
+    Ok(sign * (0..n).map(|i| u[i][i]).product::<f64>())
+}
+
+/// Row-reduces `m` in place with partial pivoting and returns the pivot
+/// columns. Pivot rows are scaled to 1 and cleared above and below.
+fn reduce(m: &mut Matrix, tol: f64) -> Vec<usize> {
+    let (rows, cols) = shape(m);
+    let mut pivots = Vec::new();
+    for col in 0..cols {
+        let row = pivots.len();
+        if row == rows {
+            break;
+        }
+        let best = (row..rows)
+            .max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))
+            .unwrap_or(row);
+        if m[best][col].abs() <= tol {
+            continue;
+        }
+        m.swap(best, row);
+        let scale = m[row][col];
+        for v in &mut m[row] {
+            *v /= scale;
+        }
+        let pivot_row = m[row].clone();
+        for (i, other) in m.iter_mut().enumerate() {
+            let factor = other[col];
+            if i != row && factor != 0.0 {
+                for (v, p) in other.iter_mut().zip(&pivot_row) {
+                    *v -= factor * p;
+                }
+            }
+        }
+        pivots.push(col);
+    }
+    pivots
+}
+
+/// Whether a row-reduced `[A | ...]` found a pivot in each of A's `n`
+/// columns, so A is not singular.
+fn pivots_on_diagonal(pivots: &[usize], n: usize) -> bool {
+    pivots.len() >= n && pivots[..n].iter().copied().eq(0..n)
+}
+
+pub(crate) fn rank(m: &Matrix) -> usize {
+    let tol = tolerance(m);
+    reduce(&mut m.clone(), tol).len()
+}
+
+pub(crate) fn inverse(m: &Matrix) -> Result<Matrix, String> {
+    let n = square(m, "an inverse")?;
+    let tol = tolerance(m);
+    let mut augmented: Matrix = m
+        .iter()
+        .zip(identity(n))
+        .map(|(row, id)| row.iter().copied().chain(id).collect())
+        .collect();
+    if !pivots_on_diagonal(&reduce(&mut augmented, tol), n) {
+        return Err("The matrix is singular, so it has no inverse".to_string());
+    }
+    Ok(augmented.into_iter().map(|row| row[n..].to_vec()).collect())
+}
+
+/// x with `a` x = `b`, for square, non-singular `a`.
+pub(crate) fn solve(a: &Matrix, b: &[f64]) -> Result<Vec<f64>, String> {
+    let n = square(a, "a unique solution")?;
+    if b.len() != n {
+        return Err(format!(
+            "b needs {n} entries, one per row of A, but has {}",
+            b.len()
+        ));
+    }
+    let tol = tolerance(a);
+    let mut augmented: Matrix = a
+        .iter()
+        .zip(b)
+        .map(|(row, &v)| row.iter().copied().chain([v]).collect())
+        .collect();
+    if !pivots_on_diagonal(&reduce(&mut augmented, tol), n) {
+        return Err("A is singular, so Ax = b has no unique solution".to_string());
+    }
+    Ok(augmented.iter().map(|row| row[n]).collect())
+}
+
+/// The x that brings `a` x closest to `b` (least squares), for `a` with
+/// independent columns. The reflections of [`qr`] are applied to `[A | b]`
+/// as they go, so Q is never built — `a` may have thousands of rows.
+pub(crate) fn least_squares(a: &Matrix, b: &[f64]) -> Result<Vec<f64>, String> {
+    let (rows, cols) = shape(a);
+    if b.len() != rows {
+        return Err(format!(
+            "b needs {rows} entries, one per row of A, but has {}",
+            b.len()
+        ));
+    }
+    // Each column is judged against its own size: in a design matrix like
+    // 1, x, ..., x⁴ the columns differ by many orders of magnitude, and a
+    // tolerance from the largest entry would call the small ones zero.
+    let tols: Vec<f64> = (0..cols)
+        .map(|j| a.iter().map(|row| row[j] * row[j]).sum::<f64>().sqrt() * RELATIVE_TOLERANCE)
+        .collect();
+    let mut r: Matrix = a
+        .iter()
+        .zip(b)
+        .map(|(row, &v)| row.iter().copied().chain([v]).collect())
+        .collect();
+
+    for k in 0..cols.min(rows) {
+        let norm = (k..rows).map(|i| r[i][k] * r[i][k]).sum::<f64>().sqrt();
+        let alpha = if r[k][k] > 0.0 { -norm } else { norm };
+        let mut v: Vec<f64> = (k..rows).map(|i| r[i][k]).collect();
+        v[0] -= alpha;
+        let v_norm2: f64 = v.iter().map(|x| x * x).sum();
+        if v_norm2 == 0.0 {
+            continue;
+        }
+        let reflected = &mut r[k..];
+        for j in k..=cols {
+            let s: f64 = v
+                .iter()
+                .zip(reflected.iter())
+                .map(|(vi, row)| vi * row[j])
+                .sum();
+            for (vi, row) in v.iter().zip(reflected.iter_mut()) {
+                row[j] -= 2.0 * s * vi / v_norm2;
+            }
+        }
+    }
+
+    // Back substitution through the triangle R leaves on top.
+    let mut x = vec![0.0; cols];
+    for k in (0..cols).rev() {
+        if k >= rows || r[k][k].abs() <= tols[k] {
+            return Err("The columns of A are dependent, so there is no unique fit".to_string());
+        }
+        let s: f64 = (k + 1..cols).map(|j| r[k][j] * x[j]).sum();
+        x[k] = (r[k][cols] - s) / r[k][k];
+    }
+    Ok(x)
+}
+
+/// `m = QR` by Householder reflections, with Q orthogonal and R upper
+/// triangular with a non-negative diagonal.
+pub(crate) fn qr(m: &Matrix) -> (Matrix, Matrix) {
+    let (rows, cols) = shape(m);
+    let mut r = m.clone();
+    let mut q = identity(rows);
+
+    for k in 0..cols.min(rows.saturating_sub(1)) {
+        let norm = (k..rows).map(|i| r[i][k] * r[i][k]).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            continue;
+        }
+        let alpha = if r[k][k] > 0.0 { -norm } else { norm };
+        let mut v: Vec<f64> = (k..rows).map(|i| r[i][k]).collect();
+        v[0] -= alpha;
+        let v_norm2: f64 = v.iter().map(|x| x * x).sum();
+        if v_norm2 == 0.0 {
+            continue;
+        }
+        let reflected = &mut r[k..];
+        for j in 0..cols {
+            let s: f64 = v
+                .iter()
+                .zip(reflected.iter())
+                .map(|(vi, row)| vi * row[j])
+                .sum();
+            for (vi, row) in v.iter().zip(reflected.iter_mut()) {
+                row[j] -= 2.0 * s * vi / v_norm2;
+            }
+        }
+        for row in &mut q {
+            let s: f64 = v.iter().enumerate().map(|(i, vi)| vi * row[k + i]).sum();
+            for (i, vi) in v.iter().enumerate() {
+                row[k + i] -= 2.0 * s * vi / v_norm2;
+            }
+        }
+        for row in r.iter_mut().skip(k + 1) {
+            row[k] = 0.0;
+        }
+    }
+
+    for k in 0..cols.min(rows) {
+        if r[k][k] < 0.0 {
+            for v in &mut r[k] {
+                *v = -*v;
+            }
+            for row in &mut q {
+                row[k] = -row[k];
+            }
+        }
+    }
+    (q, r)
+}
+
+/// The eigenvalues of a square matrix by shifted QR iteration, largest
+/// real part first. Complex ones come in conjugate pairs.
+pub(crate) fn eigenvalues(m: &Matrix) -> Result<Vec<Eigenvalue>, String> {
+    let size = square(m, "eigenvalues")?;
+    if size > MAX_EIGEN_SIZE {
+        return Err(format!(
+            "Eigenvalues are only found for matrices up to {MAX_EIGEN_SIZE}×{MAX_EIGEN_SIZE}"
+        ));
+    }
+    let tol = tolerance(m).max(f64::MIN_POSITIVE);
+    let mut h = m.clone();
+    let mut n = size;
+    let mut values = Vec::with_capacity(size);
+    let mut sweeps = 0;
+
+    // Sweeps shrink the last row (or last two rows) of the active block
+    // to zero left of the diagonal, which splits its eigenvalues off.
+    while n > 0 {
+        let row_is_clear =
+            |h: &Matrix, row: usize, before: usize| h[row][..before].iter().all(|v| v.abs() <= tol);
+        if n == 1 || row_is_clear(&h, n - 1, n - 1) {
+            values.push((h[n - 1][n - 1], 0.0));
+            n -= 1;
+            sweeps = 0;
+            continue;
+        }
+        if n == 2 || (row_is_clear(&h, n - 1, n - 2) && row_is_clear(&h, n - 2, n - 2)) {
+            values.extend(block_eigenvalues(&h, n - 2, tol));
+            n -= 2;
+            sweeps = 0;
+            continue;
+        }
+
+        sweeps += 1;
+        if sweeps > MAX_EIGEN_SWEEPS {
+            return Err("The eigenvalues did not converge".to_string());
+        }
+        let mut shift = wilkinson_shift(&h, n, tol);
+        // Now and then nudge the shift, so a sweep can't cycle forever.
+        if sweeps.is_multiple_of(10) {
+            shift += h[n - 1][n - 2].abs();
+        }
+
+        let mut active: Matrix = h[..n].iter().map(|row| row[..n].to_vec()).collect();
+        for (i, row) in active.iter_mut().enumerate() {
+            row[i] -= shift;
+        }
+        let (q, r) = qr(&active);
+        let next = multiply(&r, &q)?;
+        for i in 0..n {
+            for j in 0..n {
+                h[i][j] = next[i][j] + if i == j { shift } else { 0.0 };
+            }
+        }
+    }
+
+    values.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.total_cmp(&a.1)));
+    Ok(values)
+}
+
+/// The eigenvalues of the 2×2 block starting at row and column `k`.
+/// Rounding can leave a repeated real pair a hair short of real; `tol`
+/// decides how short still counts.
+fn block_eigenvalues(h: &Matrix, k: usize, tol: f64) -> [Eigenvalue; 2] {
+    let (a, b, c, d) = (h[k][k], h[k][k + 1], h[k + 1][k], h[k + 1][k + 1]);
+    let mean = (a + d) / 2.0;
+    let disc = ((a - d) / 2.0).powi(2) + b * c;
+    if -disc > tol * (a.abs() + b.abs() + c.abs() + d.abs()) {
+        let root = (-disc).sqrt();
+        [(mean, root), (mean, -root)]
+    } else {
+        let root = disc.max(0.0).sqrt();
+        [(mean + root, 0.0), (mean - root, 0.0)]
+    }
+}
+
+/// The eigenvalue of the active block's trailing 2×2 nearest its last
+/// diagonal entry, or that entry itself when the pair is complex.
+fn wilkinson_shift(h: &Matrix, n: usize, tol: f64) -> f64 {
+    let corner = h[n - 1][n - 1];
+    let [(a, imaginary), (b, _)] = block_eigenvalues(h, n - 2, tol);
+    if imaginary != 0.0 {
+        corner
+    } else if (a - corner).abs() < (b - corner).abs() {
+        a
+    } else {
+        b
+    }
+}
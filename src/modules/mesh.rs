@@ -0,0 +1,372 @@
+//! Polygon meshes for the wireframe viewer: built-in solids and simple OBJ
+//! files, centred on the origin and scaled to fit a unit sphere.
+
+use std::{
+    collections::HashMap,
+    f32::consts::{PI, TAU},
+};
+
+/// The built-in meshes, by the names `:wf` accepts.
+pub(crate) const BUILT_INS: &[&str] = &[
+    "tetrahedron",
+    "cube",
+    "octahedron",
+    "icosahedron",
+    "sphere",
+    "torus",
+];
+
+const SPHERE_STACKS: usize = 8;
+const SPHERE_SLICES: usize = 16;
+const TORUS_RING: usize = 24;
+const TORUS_TUBE: usize = 10;
+/// Tube radius over ring radius.
+const TORUS_THICKNESS: f32 = 0.4;
+
+/// An edge between two vertices and the faces that share it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Edge {
+    pub a: usize,
+    pub b: usize,
+    pub faces: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Mesh {
+    pub vertices: Vec<[f32; 3]>,
+    /// Vertex indices, counter-clockwise seen from outside
+    pub faces: Vec<Vec<usize>>,
+    /// Every face side once, plus any loose lines
+    pub edges: Vec<Edge>,
+}
+
+impl Mesh {
+    /// A mesh from its faces and any loose polylines, centred and scaled
+    /// to the unit sphere.
+    fn new(vertices: Vec<[f32; 3]>, faces: Vec<Vec<usize>>, lines: &[Vec<usize>]) -> Self {
+        let mut edges: Vec<Edge> = Vec::new();
+        let mut index: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut add = |a: usize, b: usize, face: Option<usize>| {
+            let (a, b) = (a.min(b), a.max(b));
+            if a == b {
+                return;
+            }
+            let i = *index.entry((a, b)).or_insert_with(|| {
+                edges.push(Edge {
+                    a,
+                    b,
+                    faces: vec![],
+                });
+                edges.len() - 1
+            });
+            edges[i].faces.extend(face);
+        };
+        for (f, face) in faces.iter().enumerate() {
+            for (i, &a) in face.iter().enumerate() {
+                add(a, face[(i + 1) % face.len()], Some(f));
+            }
+        }
+        for line in lines {
+            for w in line.windows(2) {
+                add(w[0], w[1], None);
+            }
+        }
+
+        let mut mesh = Mesh {
+            vertices,
+            faces,
+            edges,
+        };
+        mesh.normalize();
+        mesh
+    }
+
+    /// Centres the bounding box on the origin and scales the farthest
+    /// vertex to distance 1.
+    fn normalize(&mut self) {
+        let mut lo = [f32::MAX; 3];
+        let mut hi = [f32::MIN; 3];
+        for v in &self.vertices {
+            for k in 0..3 {
+                lo[k] = lo[k].min(v[k]);
+                hi[k] = hi[k].max(v[k]);
+            }
+        }
+        let centre: Vec<f32> = lo.iter().zip(hi).map(|(l, h)| (l + h) / 2.0).collect();
+        let radius = self
+            .vertices
+            .iter()
+            .map(|v| length(sub(*v, [centre[0], centre[1], centre[2]])))
+            .fold(0.0, f32::max);
+        let scale = if radius > 0.0 { 1.0 / radius } else { 1.0 };
+        for v in &mut self.vertices {
+            for k in 0..3 {
+                v[k] = (v[k] - centre[k]) * scale;
+            }
+        }
+    }
+
+    /// One of the [`BUILT_INS`] by name.
+    pub(crate) fn built_in(name: &str) -> Option<Self> {
+        let mesh = match name {
+            "tetrahedron" => {
+                let vertices = vec![
+                    [1.0, 1.0, 1.0],
+                    [1.0, -1.0, -1.0],
+                    [-1.0, 1.0, -1.0],
+                    [-1.0, -1.0, 1.0],
+                ];
+                let faces = triangles_with_side(&vertices, 8.0_f32.sqrt());
+                convex(vertices, faces)
+            }
+            "cube" => {
+                let vertices: Vec<[f32; 3]> = (0..8)
+                    .map(|i| {
+                        let bit = |b: usize| if i & b == 0 { -1.0 } else { 1.0 };
+                        [bit(1), bit(2), bit(4)]
+                    })
+                    .collect();
+                let faces = vec![
+                    vec![0, 1, 3, 2],
+                    vec![4, 5, 7, 6],
+                    vec![0, 1, 5, 4],
+                    vec![2, 3, 7, 6],
+                    vec![0, 2, 6, 4],
+                    vec![1, 3, 7, 5],
+                ];
+                convex(vertices, faces)
+            }
+            "octahedron" => {
+                let vertices = vec![
+                    [1.0, 0.0, 0.0],
+                    [-1.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                    [0.0, -1.0, 0.0],
+                    [0.0, 0.0, 1.0],
+                    [0.0, 0.0, -1.0],
+                ];
+                let faces = triangles_with_side(&vertices, 2.0_f32.sqrt());
+                convex(vertices, faces)
+            }
+            "icosahedron" => {
+                let phi = (1.0 + 5.0_f32.sqrt()) / 2.0;
+                let mut vertices = Vec::new();
+                for a in [-1.0, 1.0] {
+                    for b in [-phi, phi] {
+                        vertices.push([0.0, a, b]);
+                        vertices.push([a, b, 0.0]);
+                        vertices.push([b, 0.0, a]);
+                    }
+                }
+                let faces = triangles_with_side(&vertices, 2.0);
+                convex(vertices, faces)
+            }
+            "sphere" => sphere(),
+            "torus" => torus(),
+            _ => return None,
+        };
+        Some(mesh)
+    }
+}
+
+/// Every triangle of vertices `side` apart from each other: the faces of
+/// a regular solid whose edges are that long.
+fn triangles_with_side(vertices: &[[f32; 3]], side: f32) -> Vec<Vec<usize>> {
+    let n = vertices.len();
+    let near = |i: usize, j: usize| (length(sub(vertices[i], vertices[j])) - side).abs() < 1e-3;
+    let mut faces = Vec::new();
+    for i in 0..n {
+        for j in i + 1..n {
+            for k in j + 1..n {
+                if near(i, j) && near(j, k) && near(i, k) {
+                    faces.push(vec![i, j, k]);
+                }
+            }
+        }
+    }
+    faces
+}
+
+/// A convex solid around the origin, its faces turned to face outwards.
+fn convex(vertices: Vec<[f32; 3]>, faces: Vec<Vec<usize>>) -> Mesh {
+    let faces = faces
+        .into_iter()
+        .map(|face| orient_away_from(face, &vertices, |_| [0.0; 3]))
+        .collect();
+    Mesh::new(vertices, faces, &[])
+}
+
+/// `face`, reversed if needed so its normal points away from `inside`
+/// (a point the face should be seen from the far side of, given the face's
+/// centroid).
+fn orient_away_from(
+    mut face: Vec<usize>,
+    vertices: &[[f32; 3]],
+    inside: impl Fn([f32; 3]) -> [f32; 3],
+) -> Vec<usize> {
+    let centroid = face.iter().fold([0.0; 3], |acc, &i| {
+        let v = vertices[i];
+        [acc[0] + v[0], acc[1] + v[1], acc[2] + v[2]]
+    });
+    let centroid = centroid.map(|c| c / face.len() as f32);
+    if dot(
+        face_normal(&face, vertices),
+        sub(centroid, inside(centroid)),
+    ) < 0.0
+    {
+        face.reverse();
+    }
+    face
+}
+
+fn sphere() -> Mesh {
+    let mut vertices = vec![[0.0, 1.0, 0.0]];
+    for stack in 1..SPHERE_STACKS {
+        let polar = PI * stack as f32 / SPHERE_STACKS as f32;
+        for slice in 0..SPHERE_SLICES {
+            let azimuth = TAU * slice as f32 / SPHERE_SLICES as f32;
+            vertices.push([
+                polar.sin() * azimuth.cos(),
+                polar.cos(),
+                polar.sin() * azimuth.sin(),
+            ]);
+        }
+    }
+    vertices.push([0.0, -1.0, 0.0]);
+    let bottom = vertices.len() - 1;
+
+    let ring = |stack: usize, slice: usize| 1 + (stack - 1) * SPHERE_SLICES + slice % SPHERE_SLICES;
+    let mut faces = Vec::new();
+    for slice in 0..SPHERE_SLICES {
+        faces.push(vec![0, ring(1, slice), ring(1, slice + 1)]);
+        for stack in 1..SPHERE_STACKS - 1 {
+            faces.push(vec![
+                ring(stack, slice),
+                ring(stack + 1, slice),
+                ring(stack + 1, slice + 1),
+                ring(stack, slice + 1),
+            ]);
+        }
+        faces.push(vec![
+            ring(SPHERE_STACKS - 1, slice),
+            bottom,
+            ring(SPHERE_STACKS - 1, slice + 1),
+        ]);
+    }
+    convex(vertices, faces)
+}
+
+fn torus() -> Mesh {
+    let ring_radius = 1.0;
+    let tube_radius = TORUS_THICKNESS;
+    let mut vertices = Vec::new();
+    for i in 0..TORUS_RING {
+        let u = TAU * i as f32 / TORUS_RING as f32;
+        for j in 0..TORUS_TUBE {
+            let v = TAU * j as f32 / TORUS_TUBE as f32;
+            let r = ring_radius + tube_radius * v.cos();
+            vertices.push([r * u.cos(), tube_radius * v.sin(), r * u.sin()]);
+        }
+    }
+    let at = |i: usize, j: usize| (i % TORUS_RING) * TORUS_TUBE + j % TORUS_TUBE;
+    let faces = (0..TORUS_RING)
+        .flat_map(|i| (0..TORUS_TUBE).map(move |j| (i, j)))
+        .map(|(i, j)| vec![at(i, j), at(i + 1, j), at(i + 1, j + 1), at(i, j + 1)])
+        // The inside of a tube face is the ring's centre line next to it.
+        .map(|face| {
+            orient_away_from(face, &vertices, |c| {
+                let r = c[0].hypot(c[2]).max(f32::EPSILON);
+                [c[0] / r * ring_radius, 0.0, c[2] / r * ring_radius]
+            })
+        })
+        .collect();
+    Mesh::new(vertices, faces, &[])
+}
+
+/// Reads the vertices (`v`), faces (`f`) and lines (`l`) of an OBJ file;
+/// everything else in it is ignored. Indices count from 1, or back from the
+/// latest vertex when negative.
+pub(crate) fn parse_obj(text: &str) -> Result<Mesh, String> {
+    let mut vertices = Vec::new();
+    let mut faces = Vec::new();
+    let mut lines = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let error = |what: &str| format!("line {}: {what}: '{}'", n + 1, line.trim());
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("v") => {
+                let coords: Vec<f32> = words
+                    .take(3)
+                    .map(|w| w.parse::<f32>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| error("bad vertex"))?;
+                match coords[..] {
+                    [x, y, z] => vertices.push([x, y, z]),
+                    _ => return Err(error("a vertex needs x, y and z")),
+                }
+            }
+            Some(kind @ ("f" | "l")) => {
+                let indices = words
+                    .map(|w| {
+                        // `f 1/2/3`: only the vertex index matters here.
+                        let index: i64 = w
+                            .split('/')
+                            .next()
+                            .and_then(|i| i.parse().ok())
+                            .ok_or_else(|| error("bad index"))?;
+                        let resolved = if index < 0 {
+                            vertices.len() as i64 + index
+                        } else {
+                            index - 1
+                        };
+                        usize::try_from(resolved)
+                            .ok()
+                            .filter(|&i| i < vertices.len())
+                            .ok_or_else(|| error("index out of range"))
+                    })
+                    .collect::<Result<Vec<usize>, String>>()?;
+                match (kind, indices.len()) {
+                    ("f", 3..) => faces.push(indices),
+                    ("l", 2..) => lines.push(indices),
+                    _ => return Err(error("too few indices")),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if vertices.is_empty() {
+        return Err("The file has no vertices".to_string());
+    }
+    if faces.is_empty() && lines.is_empty() {
+        return Err("The file has no faces or lines".to_string());
+    }
+    Ok(Mesh::new(vertices, faces, &lines))
+}
+
+/// A face's normal from its first three vertices (not normalised).
+pub(crate) fn face_normal(face: &[usize], vertices: &[[f32; 3]]) -> [f32; 3] {
+    let a = vertices[face[0]];
+    cross(sub(vertices[face[1]], a), sub(vertices[face[2]], a))
+}
+
+pub(crate) fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn length(a: [f32; 3]) -> f32 {
+    dot(a, a).sqrt()
+}
@@ -1,15 +1,31 @@
+pub(crate) mod animate;
+pub(crate) mod arrays;
 pub(crate) mod bezier_curve;
+pub(crate) mod bezier_editor;
 pub(crate) mod bindings;
 pub(crate) mod commands;
 pub(crate) mod common;
 pub(crate) mod cube;
 pub(crate) mod error_render;
 pub(crate) mod evaluate;
+pub(crate) mod export;
+pub(crate) mod fft;
+pub(crate) mod fit;
 pub(crate) mod graphing;
 pub(crate) mod inputs;
 pub(crate) mod logger;
+pub(crate) mod matrix;
+pub(crate) mod mesh;
+pub(crate) mod png;
 pub(crate) mod repl;
 pub(crate) mod run;
+pub(crate) mod scatter;
+pub(crate) mod sliders;
+pub(crate) mod spline;
+pub(crate) mod stats;
 pub(crate) mod string_maker;
+pub(crate) mod surface;
 #[cfg(test)]
 pub(crate) mod tests;
+pub(crate) mod viewer;
+pub(crate) mod wireframe;
@@ -0,0 +1,629 @@
+//! PNG export: draws a [`Plot`] as an antialiased raster image.
+//!
+//! Everything is done here, with no image crates, so export works offline:
+//! curves are stroked from the same sampled points the braille graph used,
+//! tick labels come from a small built-in digit font, and the encoder
+//! compresses with deflate's fixed Huffman codes.
+
+use std::path::Path;
+
+use crate::modules::{
+    common::Scale,
+    graphing::{axis_ticks, Plot},
+};
+
+/// Image size when none is given.
+pub(crate) const DEFAULT_SIZE: (u32, u32) = (800, 600);
+const MIN_SIDE: u32 = 100;
+const MAX_SIDE: u32 = 4000;
+
+type Rgb = [f32; 3];
+
+const BACKGROUND: Rgb = [1.0, 1.0, 1.0];
+const GRID: Rgb = [0.9, 0.9, 0.9];
+const AXIS: Rgb = [0.55, 0.55, 0.55];
+const FRAME: Rgb = [0.2, 0.2, 0.2];
+const TEXT: Rgb = [0.25, 0.25, 0.25];
+const POINTS: Rgb = [0.1, 0.1, 0.1];
+
+/// One colour per `|`-separated equation, reused in order past the end.
+const PALETTE: [(&str, Rgb); 6] = [
+    ("blue", [0.12, 0.47, 0.71]),
+    ("orange", [1.0, 0.5, 0.05]),
+    ("green", [0.17, 0.63, 0.17]),
+    ("red", [0.84, 0.15, 0.16]),
+    ("purple", [0.58, 0.4, 0.74]),
+    ("brown", [0.55, 0.34, 0.29]),
+];
+
+// Pixels between ticks the labelling aims for
+const X_TICK_SPACING: f32 = 90.0;
+const Y_TICK_SPACING: f32 = 60.0;
+
+/// Parses `WxH`, e.g. `800x600`.
+pub(crate) fn parse_size(s: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("'{s}' is not a size. Use WIDTHxHEIGHT, e.g. 800x600");
+    let (w, h) = s.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let (Ok(w), Ok(h)) = (w.parse::<u32>(), h.parse::<u32>()) else {
+        return Err(invalid());
+    };
+    if !(MIN_SIDE..=MAX_SIDE).contains(&w) || !(MIN_SIDE..=MAX_SIDE).contains(&h) {
+        return Err(format!(
+            "Width and height must each be {MIN_SIDE} to {MAX_SIDE} pixels"
+        ));
+    }
+    Ok((w, h))
+}
+
+/// Which colour each equation is drawn in, e.g. `y=sin(x): blue`.
+pub(crate) fn colour_key(plot: &Plot) -> String {
+    plot.equations
+        .iter()
+        .enumerate()
+        .map(|(i, eq)| format!("{eq}: {}", PALETTE[i % PALETTE.len()].0))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub(crate) fn save_png(
+    plot: &Plot,
+    (width, height): (u32, u32),
+    path: &Path,
+) -> std::io::Result<()> {
+    std::fs::write(path, render_png(plot, width, height))
+}
+
+/// The encoded PNG file for `plot` at `width`×`height` pixels.
+pub(crate) fn render_png(plot: &Plot, width: u32, height: u32) -> Vec<u8> {
+    let canvas = rasterise(plot, width as usize, height as usize);
+    encode_png(width, height, &canvas.to_rgb8())
+}
+
+// ============================================================================
+// Rasterising
+// ============================================================================
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width * height],
+        }
+    }
+
+    fn blend(&mut self, x: usize, y: usize, colour: Rgb, alpha: f32) {
+        if x >= self.width || y >= self.height || alpha <= 0.0 {
+            return;
+        }
+        let p = &mut self.pixels[y * self.width + x];
+        for (c, new) in p.iter_mut().zip(colour) {
+            *c += (new - *c) * alpha.min(1.0);
+        }
+    }
+
+    fn fill_rect(&mut self, (x0, y0): (usize, usize), (x1, y1): (usize, usize), colour: Rgb) {
+        for y in y0..y1 {
+            for x in x0..x1 {
+                self.blend(x, y, colour, 1.0);
+            }
+        }
+    }
+
+    /// Paints `layer`'s coverage in `colour`, only inside `clip`.
+    fn composite(&mut self, layer: &Layer, colour: Rgb, clip: &Rect) {
+        for y in clip.top as usize..=(clip.bottom as usize).min(self.height - 1) {
+            for x in clip.left as usize..=(clip.right as usize).min(self.width - 1) {
+                self.blend(x, y, colour, layer.coverage[y * layer.width + x]);
+            }
+        }
+    }
+
+    fn to_rgb8(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|p| p.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8))
+            .collect()
+    }
+}
+
+/// Coverage for one stroke set. Overlapping segments take the max rather
+/// than adding up, so a polyline's joins don't come out darker.
+struct Layer {
+    width: usize,
+    height: usize,
+    coverage: Vec<f32>,
+}
+
+impl Layer {
+    fn new(width: usize, height: usize) -> Self {
+        Layer {
+            width,
+            height,
+            coverage: vec![0.0; width * height],
+        }
+    }
+
+    /// Empties the layer for the next stroke set, keeping its buffer.
+    fn clear(&mut self) {
+        self.coverage.fill(0.0);
+    }
+
+    /// Adds an antialiased segment `thickness` pixels wide: each pixel is
+    /// covered by how far its center sits inside the stroke's edge.
+    fn stroke(&mut self, (x0, y0): (f32, f32), (x1, y1): (f32, f32), thickness: f32) {
+        let reach = thickness / 2.0 + 1.0;
+        let lo_x = (x0.min(x1) - reach).floor().max(0.0) as usize;
+        let hi_x = ((x0.max(x1) + reach).ceil().max(0.0) as usize).min(self.width);
+        let lo_y = (y0.min(y1) - reach).floor().max(0.0) as usize;
+        let hi_y = ((y0.max(y1) + reach).ceil().max(0.0) as usize).min(self.height);
+
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let len_sq = dx * dx + dy * dy;
+        for y in lo_y..hi_y {
+            for x in lo_x..hi_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let t = if len_sq == 0.0 {
+                    0.0
+                } else {
+                    (((px - x0) * dx + (py - y0) * dy) / len_sq).clamp(0.0, 1.0)
+                };
+                let d = ((px - x0 - t * dx).powi(2) + (py - y0 - t * dy).powi(2)).sqrt();
+                let cover = (thickness / 2.0 + 0.5 - d).clamp(0.0, 1.0);
+                let c = &mut self.coverage[y * self.width + x];
+                *c = c.max(cover);
+            }
+        }
+    }
+}
+
+/// The plot area, in pixels.
+struct Rect {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+}
+
+fn rasterise(plot: &Plot, width: usize, height: usize) -> Canvas {
+    let layout = &plot.layout;
+    let (x_min, x_max) = layout.x_range;
+    let (y_min, y_max) = layout.y_range;
+
+    // Text and line weights grow with the image so large exports stay legible.
+    let s = (height.min(width) as f32 / 300.0).floor().max(1.0);
+    let line = 1.0 * s;
+
+    let y_ticks = axis_ticks(
+        y_min,
+        y_max,
+        ((height as f32 / Y_TICK_SPACING) as usize).max(2),
+        layout.y_scale,
+    );
+    let label_chars = y_ticks.iter().map(|(_, l)| l.len()).max().unwrap_or(0);
+
+    let area = Rect {
+        left: (label_chars as f32 * GLYPH_ADVANCE + 3.0) * s + 6.0 * s,
+        top: 8.0 * s,
+        right: width as f32 - 12.0 * s,
+        bottom: height as f32 - (GLYPH_HEIGHT + 6.0) * s - 6.0 * s,
+    };
+    let to_px = |x: f32| area.left + (x - x_min) / (x_max - x_min) * (area.right - area.left);
+    let to_py = |y: f32| area.bottom - (y - y_min) / (y_max - y_min) * (area.bottom - area.top);
+
+    let x_ticks = axis_ticks(
+        x_min,
+        x_max,
+        ((width as f32 / X_TICK_SPACING) as usize).max(2),
+        layout.x_scale,
+    );
+
+    let mut canvas = Canvas::new(width, height);
+
+    // Grid, then the axes over it, then the frame. One layer is reused
+    // for every stroke set, so a large export holds a single extra buffer.
+    let mut layer = Layer::new(width, height);
+    for (x, _) in &x_ticks {
+        let px = to_px(*x);
+        layer.stroke((px, area.top), (px, area.bottom), line);
+    }
+    for (y, _) in &y_ticks {
+        let py = to_py(*y);
+        layer.stroke((area.left, py), (area.right, py), line);
+    }
+    canvas.composite(&layer, GRID, &area);
+
+    layer.clear();
+    if layout.y_scale == Scale::Linear && y_min < 0.0 && y_max > 0.0 {
+        layer.stroke(
+            (area.left, to_py(0.0)),
+            (area.right, to_py(0.0)),
+            line * 1.5,
+        );
+    }
+    if layout.x_scale == Scale::Linear && x_min < 0.0 && x_max > 0.0 {
+        layer.stroke(
+            (to_px(0.0), area.top),
+            (to_px(0.0), area.bottom),
+            line * 1.5,
+        );
+    }
+    canvas.composite(&layer, AXIS, &area);
+
+    for (i, curve) in plot.curves.iter().enumerate() {
+        layer.clear();
+        let span = area.bottom - area.top;
+        for pair in curve.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            let (ax, ay, bx, by) = (to_px(a.x), to_py(a.y), to_px(b.x), to_py(b.y));
+            if ![ax, ay, bx, by].iter().all(|v| v.is_finite()) {
+                continue;
+            }
+            let off_top = ay < area.top && by < area.top;
+            let off_bottom = ay > area.bottom && by > area.bottom;
+            // A jump taller than the plot is an asymptote, not a steep curve.
+            if off_top || off_bottom || (ay - by).abs() > span {
+                continue;
+            }
+            layer.stroke((ax, ay), (bx, by), line * 2.0);
+        }
+        canvas.composite(&layer, PALETTE[i % PALETTE.len()].1, &area);
+    }
+
+    // Markers and scattered points, as round dots over the curves
+    layer.clear();
+    for p in &plot.points {
+        let (px, py) = (to_px(p.x), to_py(p.y));
+        if px.is_finite() && py.is_finite() {
+            layer.stroke((px, py), (px, py), line * 4.0);
+        }
+    }
+    canvas.composite(&layer, POINTS, &area);
+
+    layer.clear();
+    let corners = [
+        (area.left, area.top),
+        (area.right, area.top),
+        (area.right, area.bottom),
+        (area.left, area.bottom),
+    ];
+    for i in 0..4 {
+        layer.stroke(corners[i], corners[(i + 1) % 4], line);
+    }
+    let whole = Rect {
+        left: 0.0,
+        top: 0.0,
+        right: width as f32,
+        bottom: height as f32,
+    };
+    canvas.composite(&layer, FRAME, &whole);
+
+    // Tick labels: x centered under its tick, y right-aligned beside it
+    for (x, label) in &x_ticks {
+        let w = label.len() as f32 * GLYPH_ADVANCE * s;
+        let px = to_px(*x) - w / 2.0;
+        draw_text(&mut canvas, label, (px, area.bottom + 6.0 * s), s);
+    }
+    for (y, label) in &y_ticks {
+        let w = label.len() as f32 * GLYPH_ADVANCE * s;
+        let py = to_py(*y) - GLYPH_HEIGHT * s / 2.0;
+        draw_text(&mut canvas, label, (area.left - 6.0 * s - w, py), s);
+    }
+
+    canvas
+}
+
+// ============================================================================
+// Label font
+// ============================================================================
+
+const GLYPH_HEIGHT: f32 = 7.0;
+/// Glyph width (5) plus a column of spacing
+const GLYPH_ADVANCE: f32 = 6.0;
+
+/// 5×7 bitmaps for the characters tick labels use; each row's low 5 bits
+/// are its pixels, left to right.
+fn glyph(c: char) -> Option<[u8; 7]> {
+    Some(match c {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        'e' => [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E],
+        _ => return None,
+    })
+}
+
+/// Draws `text` with its top-left corner at `at`, each font pixel `scale`
+/// image pixels square. Characters without a glyph leave a gap.
+fn draw_text(canvas: &mut Canvas, text: &str, (x, y): (f32, f32), scale: f32) {
+    let s = scale as usize;
+    for (i, c) in text.chars().enumerate() {
+        let Some(rows) = glyph(c) else {
+            continue;
+        };
+        let left = x + i as f32 * GLYPH_ADVANCE * scale;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..5 {
+                if bits & (0x10 >> col) == 0 {
+                    continue;
+                }
+                let px = (left + (col * s) as f32).round();
+                let py = (y + (row * s) as f32).round();
+                if px < 0.0 || py < 0.0 {
+                    continue;
+                }
+                let (px, py) = (px as usize, py as usize);
+                canvas.fill_rect((px, py), (px + s, py + s), TEXT);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// PNG encoding
+// ============================================================================
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// An 8-bit RGB PNG of `rgb` (3 bytes per pixel, rows top to bottom).
+pub(crate) fn encode_png(width: u32, height: u32, rgb: &[u8]) -> Vec<u8> {
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend(width.to_be_bytes());
+    ihdr.extend(height.to_be_bytes());
+    // 8 bits per channel, colour type 2 (RGB), default compression,
+    // filter method and no interlacing
+    ihdr.extend([8, 2, 0, 0, 0]);
+
+    // Each scanline is prefixed with its filter type; 0 leaves it as is.
+    let row_len = width as usize * 3;
+    let mut raw = Vec::with_capacity((row_len + 1) * height as usize);
+    for row in rgb.chunks(row_len) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut out = SIGNATURE.to_vec();
+    write_chunk(&mut out, b"IHDR", &ihdr);
+    write_chunk(&mut out, b"IDAT", &zlib(&raw));
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |c, &b| {
+        CRC_TABLE[((c ^ b as u32) & 0xFF) as usize] ^ (c >> 8)
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+/// A zlib stream: header, one deflate block, Adler-32 trailer.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    // 32K window, deflate, no preset dictionary, fastest-level hint
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const WINDOW: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+/// Candidates tried per position; plenty for flat-coloured images.
+const MAX_CHAIN: usize = 32;
+
+/// Deflate with a single fixed-Huffman block and greedy LZ77 matching.
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut w = BitWriter::default();
+    w.bits(1, 1); // final block
+    w.bits(1, 2); // fixed Huffman codes
+
+    // Most recent position for each 3-byte hash, and the one before it.
+    // Only the last window's worth of positions can be matched, so `prev`
+    // is a ring indexed by position modulo the window.
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW];
+
+    let mut i = 0;
+    while i < data.len() {
+        let (len, distance) = longest_match(data, i, &head, &prev);
+        let step = if len >= MIN_MATCH {
+            write_match(&mut w, len, distance);
+            len
+        } else {
+            write_symbol(&mut w, u16::from(data[i]));
+            1
+        };
+        for j in i..i + step {
+            if let Some(h) = hash3(data, j) {
+                prev[j & (WINDOW - 1)] = head[h];
+                head[h] = j;
+            }
+        }
+        i += step;
+    }
+    write_symbol(&mut w, 256); // end of block
+    w.finish()
+}
+
+fn hash3(data: &[u8], i: usize) -> Option<usize> {
+    let bytes = data.get(i..i + MIN_MATCH)?;
+    let v = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+    Some((v.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize)
+}
+
+/// The longest earlier run (within the window) matching the bytes at `i`,
+/// as (length, distance back).
+fn longest_match(data: &[u8], i: usize, head: &[usize], prev: &[usize]) -> (usize, usize) {
+    let Some(h) = hash3(data, i) else {
+        return (0, 0);
+    };
+    let max_len = MAX_MATCH.min(data.len() - i);
+    let mut best = (0, 0);
+    let mut candidate = head[h];
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || i - candidate > WINDOW {
+            break;
+        }
+        // Runs may overlap the position being matched; deflate allows it.
+        let len = data[candidate..]
+            .iter()
+            .zip(&data[i..i + max_len])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if len > best.0 {
+            best = (len, i - candidate);
+            if len == max_len {
+                break;
+            }
+        }
+        // A slot older than the window has been overwritten by a newer
+        // position; the distance check above stops the walk there.
+        let next = prev[candidate & (WINDOW - 1)];
+        if next == usize::MAX || next >= candidate {
+            break;
+        }
+        candidate = next;
+    }
+    best
+}
+
+/// Writes a literal/length symbol with its fixed Huffman code.
+fn write_symbol(w: &mut BitWriter, symbol: u16) {
+    let (code, len) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xC0 + symbol - 280, 8),
+    };
+    w.huffman(u32::from(code), len);
+}
+
+fn write_match(w: &mut BitWriter, len: usize, distance: usize) {
+    let li = LENGTH_BASE
+        .iter()
+        .rposition(|&b| usize::from(b) <= len)
+        .unwrap_or(0);
+    write_symbol(w, 257 + li as u16);
+    w.bits(
+        (len - usize::from(LENGTH_BASE[li])) as u32,
+        u32::from(LENGTH_EXTRA[li]),
+    );
+
+    let di = DISTANCE_BASE
+        .iter()
+        .rposition(|&b| usize::from(b) <= distance)
+        .unwrap_or(0);
+    w.huffman(di as u32, 5);
+    w.bits(
+        (distance - usize::from(DISTANCE_BASE[di])) as u32,
+        u32::from(DISTANCE_EXTRA[di]),
+    );
+}
+
+/// Packs bits least-significant first, as deflate stores them.
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, value: u32, n: u32) {
+        self.acc |= value << self.count;
+        self.count += n;
+        while self.count >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes go most-significant bit first.
+    fn huffman(&mut self, code: u32, len: u32) {
+        self.bits(code.reverse_bits() >> (32 - len), len);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
@@ -1,46 +1,134 @@
 use rusty_maths::equation_analyzer::Definitions;
 use std::path::PathBuf;
 
+use crate::modules::{arrays::Value, common::terminal_graph_size, graphing::Plot};
+
 pub(crate) struct Repl {
     /// User `let` bindings — named values and functions — plus the
     /// auto-maintained `ans` value. Evaluation runs against this set.
     pub(crate) defs: Definitions,
+    /// Vector and matrix bindings, in the order they were made.
+    /// `Definitions` only holds numbers, so these live beside it; a name is
+    /// bound in one or the other, never both.
+    pub(crate) arrays: Vec<(String, Value)>,
 
     pub(crate) height: usize,
     pub(crate) width: usize,
+    /// The height was typed in `:o`, so a new width leaves it alone.
+    pub(crate) height_set: bool,
     pub(crate) precision: usize,
 
+    /// Draw 1 unit of x the same length as 1 unit of y.
+    pub(crate) equal_aspect: bool,
+    /// Re-fit width and height to the terminal before every graph.
+    pub(crate) auto_size: bool,
+
+    /// Session-wide y window set from `:o`; `None` lets each graph fit its
+    /// own range.
+    pub(crate) y_range: Option<(f32, f32)>,
+
+    /// The most recent graph, curve or cube frame, for `:save`.
+    pub(crate) last_frame: Option<String>,
+    /// What `last_frame` plots, when it is a graph, for `:save png`.
+    pub(crate) last_plot: Option<Plot>,
+
     /// Where bindings persist across sessions; `None` disables persistence
     /// (tests, or no resolvable home directory).
     pub(crate) bindings_path: Option<PathBuf>,
 }
 
-/// Narrowest usable graph: one braille glyph spans 2×4 cells and height is
-/// width/2, so anything under 8 renders zero glyph rows.
+/// Narrowest usable graph: one braille glyph spans 2×4 cells and height
+/// defaults to width/2, so anything under 8 renders zero glyph rows.
 pub(crate) const MIN_GRAPH_WIDTH: usize = 8;
+/// Shortest usable graph: one row of braille glyphs is 4 cells tall.
+pub(crate) const MIN_GRAPH_HEIGHT: usize = 4;
 
 impl Repl {
     pub(crate) fn new(width: usize) -> Self {
         Self {
             defs: Definitions::new(),
+            arrays: Vec::new(),
             height: width / 2,
             width,
+            height_set: false,
             precision: 2,
+            equal_aspect: false,
+            auto_size: false,
+            y_range: None,
+            last_frame: None,
+            last_plot: None,
             bindings_path: None,
         }
     }
 
-    /// Applies a new graph width (height tracks at width/2). Returns false,
-    /// leaving dimensions unchanged, if `width` is below [`MIN_GRAPH_WIDTH`].
+    /// Applies a new graph width. The height tracks at width/2 unless one
+    /// was set with [`Repl::update_height`]. Returns false, leaving
+    /// dimensions unchanged, if `width` is below [`MIN_GRAPH_WIDTH`].
     pub(crate) fn update_dimensions(&mut self, width: usize) -> bool {
         if width < MIN_GRAPH_WIDTH {
             return false;
         }
-        self.height = width / 2;
+        if !self.height_set {
+            self.height = width / 2;
+        }
         self.width = width;
         true
     }
 
+    /// Applies a new graph height, independent of width. Returns false,
+    /// leaving it unchanged, if `height` is below [`MIN_GRAPH_HEIGHT`].
+    pub(crate) fn update_height(&mut self, height: usize) -> bool {
+        if height < MIN_GRAPH_HEIGHT {
+            return false;
+        }
+        self.height = height;
+        self.height_set = true;
+        true
+    }
+
+    /// Sizes the graph to fill the current terminal. Leaves dimensions
+    /// unchanged when there is no terminal to measure.
+    pub(crate) fn fit_to_terminal(&mut self) {
+        if let Some((width, height)) = terminal_graph_size() {
+            self.width = width;
+            self.height = height;
+            self.height_set = false;
+        }
+    }
+
+    /// Keeps a mode's final frame for `:save`. Modes that drew nothing
+    /// (bad input, cancelled) leave the previous frame in place.
+    pub(crate) fn remember_frame(&mut self, frame: Option<String>) {
+        if frame.is_some() {
+            self.last_frame = frame;
+            self.last_plot = None;
+        }
+    }
+
+    /// [`Repl::remember_frame`] for a graph, keeping what it plots too.
+    pub(crate) fn remember_graph(&mut self, graph: Option<(String, Plot)>) {
+        if let Some((frame, plot)) = graph {
+            self.last_frame = Some(frame);
+            self.last_plot = Some(plot);
+        }
+    }
+
+    /// Binds `name` to a vector or matrix, replacing any earlier array of
+    /// that name in place.
+    pub(crate) fn bind_array(&mut self, name: &str, value: Value) {
+        match self.arrays.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => self.arrays.push((name.to_string(), value)),
+        }
+    }
+
+    /// Removes the array bound to `name`. Returns whether there was one.
+    pub(crate) fn unbind_array(&mut self, name: &str) -> bool {
+        let before = self.arrays.len();
+        self.arrays.retain(|(n, _)| n != name);
+        self.arrays.len() < before
+    }
+
     /// Records a successful evaluation's result as the `ans` binding.
     pub(crate) fn set_ans(&mut self, value: f32) {
         // "ans" is a valid non-catalog name, so this cannot fail.
@@ -1,11 +1,17 @@
+use std::io::IsTerminal;
+use std::path::Path;
 use std::process::Command;
 use std::{cmp::Ordering, error::Error};
 
 use rusty_maths::equation_analyzer::{calculator::plot, Definitions};
 
 use crate::modules::{
-    bindings, commands, common::GraphOptions, error_render, evaluate, graphing, logger::Logger,
-    repl, string_maker::make_table_string,
+    bindings, commands,
+    common::{terminal_graph_size, GraphOptions, Scale},
+    error_render, evaluate, export, graphing,
+    logger::Logger,
+    png, repl,
+    string_maker::make_table_string,
 };
 
 use linefeed::{DefaultTerminal, Interface, ReadResult};
@@ -14,6 +20,7 @@ pub(crate) fn as_repl(l: &mut impl Logger) {
     l.print("\n--rusty maths repl--\n");
 
     let mut repl = repl::Repl::new(140);
+    repl.fit_to_terminal();
     repl.bindings_path = bindings::default_bindings_path();
     bindings::load(&mut repl, l);
 
@@ -54,6 +61,152 @@ pub(crate) fn as_repl(l: &mut impl Logger) {
     }
 }
 
+const GRAPH_USAGE: &str =
+    "Usage: rmr -g [equation] [x-min] [x-max] [--logx] [--logy] [--y y-min y-max] [-o file.txt] [--png file.png] [--size WxH]";
+
+/// Options trailing the `-g` positional arguments.
+struct GraphFlags {
+    x_scale: Scale,
+    y_scale: Scale,
+    y_range: Option<(f32, f32)>,
+    /// Write the graph here instead of printing it
+    output: Option<String>,
+    /// Also (or instead) rasterise it to this PNG
+    png: Option<String>,
+    size: Option<(u32, u32)>,
+}
+
+fn parse_graph_flags(flags: &[String]) -> Result<GraphFlags, String> {
+    let mut scale_words = Vec::new();
+    let mut y_range = None;
+    let mut output = None;
+    let mut png = None;
+    let mut size = None;
+
+    let mut rest = flags.iter();
+    while let Some(flag) = rest.next() {
+        match flag.as_str() {
+            "--y" => {
+                let (Some(lo), Some(hi)) = (rest.next(), rest.next()) else {
+                    return Err("--y needs a y-min and a y-max".to_string());
+                };
+                let (Ok(y_min), Ok(y_max)) = (lo.parse::<f32>(), hi.parse::<f32>()) else {
+                    return Err(format!(
+                        "y-min: `{lo}` and y-max: `{hi}` must both be valid numbers"
+                    ));
+                };
+                if y_min >= y_max {
+                    return Err(format!("y min `{y_min}` must be less than y max `{y_max}`"));
+                }
+                y_range = Some((y_min, y_max));
+            }
+            "-o" | "--output" => match rest.next() {
+                Some(path) => output = Some(path.clone()),
+                None => return Err(format!("{flag} needs a file path")),
+            },
+            "--png" => match rest.next() {
+                Some(path) => png = Some(path.clone()),
+                None => return Err("--png needs a file path".to_string()),
+            },
+            "--size" => match rest.next() {
+                Some(s) => size = Some(png::parse_size(s)?),
+                None => return Err("--size needs WIDTHxHEIGHT, e.g. 800x600".to_string()),
+            },
+            other => scale_words.push(other),
+        }
+    }
+
+    if size.is_some() && png.is_none() {
+        return Err("--size only applies to --png".to_string());
+    }
+
+    let (x_scale, y_scale) = commands::parse_scales(scale_words.into_iter())?;
+    Ok(GraphFlags {
+        x_scale,
+        y_scale,
+        y_range,
+        output,
+        png,
+        size,
+    })
+}
+
+fn graph_cli(args: &[String], l: &mut impl Logger) {
+    if args.len() < 5 {
+        l.eprint(GRAPH_USAGE);
+        return;
+    }
+    let (Ok(x_min), Ok(x_max)) = (args[3].parse::<f32>(), args[4].parse::<f32>()) else {
+        l.eprint(&format!(
+            "x-min: `{}` and x-max: `{}` must both be valid numbers",
+            args[3], args[4]
+        ));
+        return;
+    };
+    if x_min >= x_max {
+        l.eprint(&format!(
+            "x min `{x_min}` must be less than x max `{x_max}`"
+        ));
+        return;
+    }
+    let flags = match parse_graph_flags(&args[5..]) {
+        Ok(flags) => flags,
+        Err(e) => {
+            l.eprint(&format!("{e}\n{GRAPH_USAGE}"));
+            return;
+        }
+    };
+    if flags.x_scale == Scale::Log && x_min <= 0.0 {
+        l.eprint(&format!("x min `{x_min}` must be positive on a log x axis"));
+        return;
+    }
+    let (y_min, y_max) = flags.y_range.unwrap_or((-7., 7.));
+    if flags.y_scale == Scale::Log && flags.y_range.is_some() && y_min <= 0.0 {
+        l.eprint(&format!("y min `{y_min}` must be positive on a log y axis"));
+        return;
+    }
+
+    // Output piped to a file gets a fixed size: measuring would find
+    // whatever terminal the command was started from (crossterm falls back
+    // to /dev/tty), so the file would change with the window.
+    let measured = if std::io::stdout().is_terminal() {
+        terminal_graph_size()
+    } else {
+        None
+    };
+    let (width, height) = measured.unwrap_or((200, 100));
+    let go = GraphOptions {
+        y_min,
+        y_max,
+        y_fixed: flags.y_range.is_some(),
+        width,
+        height,
+        x_scale: flags.x_scale,
+        y_scale: flags.y_scale,
+        equal_aspect: false,
+    };
+    let (g, plot) =
+        match graphing::graph_with_plot(&args[2], x_min, x_max, &go, &Definitions::default()) {
+            Ok(graph) => graph,
+            Err(e) => {
+                l.eprint(&error_render::format_error_with_source(&args[2], &e));
+                return;
+            }
+        };
+    if let Some(path) = &flags.png {
+        export::write_png(&plot, flags.size.unwrap_or(png::DEFAULT_SIZE), path, l);
+    }
+    match flags.output {
+        Some(path) => match export::save_txt(&g, Path::new(&path)) {
+            Ok(()) => l.print(&format!("Saved to {path}")),
+            Err(e) => l.eprint(&format!("Could not save to {path}: {e}")),
+        },
+        // A PNG on its own replaces printing, as -o does.
+        None if flags.png.is_some() => {}
+        None => l.print(&g),
+    }
+}
+
 fn build_interface() -> Result<Interface<DefaultTerminal>, Box<dyn Error>> {
     let interface = Interface::new("rmr-repl")?;
     interface.set_prompt("\x01\x1b[38;2;196;85;8m\x02>> \x01\x1b[0m\x02")?;
@@ -65,37 +218,7 @@ pub(crate) fn as_cli_tool(args: &[String], l: &mut impl Logger) {
         Ordering::Equal => evaluate::simple_evaluate(&args[1], l),
 
         Ordering::Greater => match args[1].as_str() {
-            "-g" | "--graph" => {
-                if args.len() != 5 {
-                    l.eprint("Usage: rmr -g [equation] [x-min] [x-max]");
-                } else if let (Ok(x_min), Ok(x_max)) = (args[3].parse(), args[4].parse()) {
-                    if x_min < x_max {
-                        let go = GraphOptions {
-                            y_min: -7.,
-                            y_max: 7.,
-                            width: 200,
-                            height: 100,
-                        };
-                        let g =
-                            graphing::graph(&args[2], x_min, x_max, &go, &Definitions::default());
-                        match g {
-                            Ok(g) => l.print(&g),
-                            Err(e) => {
-                                l.eprint(&error_render::format_error_with_source(&args[2], &e))
-                            }
-                        }
-                    } else {
-                        l.eprint(&format!(
-                            "x min `{x_min}` must be less than x max `{x_max}`"
-                        ));
-                    }
-                } else {
-                    l.eprint(&format!(
-                        "x-min: `{}` and x-max: `{}` must both be valid numbers",
-                        args[3], args[4]
-                    ));
-                }
-            }
+            "-g" | "--graph" => graph_cli(args, l),
             "-t" | "--table" => {
                 if args.len() != 6 {
                     l.eprint("Usage: rmr -t [equation] [x-min] [x-max] [step_size]");
@@ -0,0 +1,126 @@
+//! `:scatter`: plots explicit (x, y) points as dots or markers, on the
+//! same frame, ticks and y window as any graph, over optional equations.
+
+use std::path::Path;
+
+use rusty_maths::equation_analyzer::Definitions;
+
+use crate::modules::{
+    bezier_curve::x_window,
+    common::{GraphOptions, Point},
+    error_render,
+    export::strip_ansi,
+    graphing::{graph_with_overlay, Overlay, Plot},
+    inputs::read_user_input,
+    logger::Logger,
+    spline::read_csv_points,
+};
+
+/// Points as x y pairs, any number to a line, split by spaces, commas,
+/// parentheses or brackets. A `:t` table pastes straight in: its borders
+/// and its X/Y header have no numbers, so those lines are skipped.
+pub(crate) fn read_pairs(text: &str) -> Result<Vec<Point>, String> {
+    let text = strip_ansi(text);
+    let mut points = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let tokens: Vec<&str> = line
+            .split(|c: char| c.is_whitespace() || ",()[]|│".contains(c))
+            .filter(|t| !t.is_empty())
+            .collect();
+        let numbers: Vec<f32> = tokens.iter().filter_map(|t| t.parse().ok()).collect();
+        if numbers.is_empty() {
+            continue;
+        }
+        if let Some(word) = tokens.iter().find(|t| t.parse::<f32>().is_err()) {
+            return Err(format!("line {}: '{word}' is not a number", i + 1));
+        }
+        if !numbers.len().is_multiple_of(2) {
+            return Err(format!("line {}: each point needs an x and a y", i + 1));
+        }
+        points.extend(numbers.chunks(2).map(|c| Point::new(c[0], c[1])));
+    }
+    if points.is_empty() {
+        return Err("There are no points to plot".to_string());
+    }
+    Ok(points)
+}
+
+/// Reads the points: a .csv file, or a line of pairs followed by any more
+/// lines of them (pasted data).
+fn get_points(l: &mut impl Logger) -> Option<Vec<Point>> {
+    loop {
+        let answer = read_user_input("points (x y pairs, pasted :t rows or a .csv file): ").ok()?;
+        if answer.is_empty() {
+            return None;
+        }
+        let points = if answer.to_ascii_lowercase().ends_with(".csv") {
+            std::fs::read_to_string(Path::new(&answer))
+                .map_err(|e| format!("Could not read {answer}: {e}"))
+                .and_then(|text| read_csv_points(&text))
+                .and_then(|points| {
+                    if points.is_empty() {
+                        Err("There are no points to plot".to_string())
+                    } else {
+                        Ok(points)
+                    }
+                })
+        } else {
+            let mut text = answer;
+            while let Ok(more) = read_user_input("more (blank = done): ") {
+                if more.is_empty() {
+                    break;
+                }
+                text.push('\n');
+                text.push_str(&more);
+            }
+            read_pairs(&text)
+        };
+        match points {
+            Ok(points) => return Some(points),
+            Err(e) => l.eprint(&e),
+        }
+    }
+}
+
+/// Runs `:scatter` and returns the graph it drew.
+pub(crate) fn scatter(
+    l: &mut impl Logger,
+    go: &GraphOptions,
+    defs: &Definitions,
+) -> Option<(String, Plot)> {
+    let points = get_points(l)?;
+    let markers = loop {
+        let answer = read_user_input("style dots/markers (blank = dots): ").unwrap_or_default();
+        match answer.as_str() {
+            "" | "dots" | "d" => break false,
+            "markers" | "m" => break true,
+            _ => l.eprint(&format!("Unknown style '{answer}'. Valid: dots, markers")),
+        }
+    };
+    let eq = read_user_input("overlay equations (blank = none): ").unwrap_or_default();
+
+    let (x_min, x_max) = x_window(&points);
+    let overlay = if markers {
+        Overlay {
+            paths: vec![],
+            markers: points,
+            dots: vec![],
+        }
+    } else {
+        Overlay {
+            paths: vec![],
+            markers: vec![],
+            dots: points,
+        }
+    };
+    match graph_with_overlay(&eq, x_min, x_max, go, defs, &overlay) {
+        Ok(drawn) => {
+            l.print(&drawn.0);
+            Some(drawn)
+        }
+        Err(e) => {
+            l.eprint(&error_render::render_error_with_source(&eq, &e, defs));
+            None
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Sliders: graph an equation while nudging value bindings up and down.
+//!
+//! Function bodies resolve late, so after `let a = 3` and
+//! `let g(x) = a * x^2`, graphing `y = g(x)` with a slider on `a` reshapes
+//! the curve live. The sliders change the real bindings while the viewer
+//! is open; on the way out the new values are either kept (and saved like
+//! any `let`) or put back as they were.
+
+use crossterm::event::KeyCode;
+use rusty_maths::equation_analyzer::{Definition, Definitions};
+
+use crate::modules::{
+    bindings,
+    commands::{open_viewer, with_y_range},
+    common::GraphOptions,
+    error_render,
+    graphing::{format_tick, graph, graph_with_plot, Plot},
+    inputs::{confirm, get_g_inputs, get_optional_numerical_input, read_user_input},
+    logger::Logger,
+    repl::Repl,
+    viewer::ViewerEvent,
+};
+
+const DEFAULT_STEP: f32 = 0.1;
+/// The step stays in this range: any finer and f32 can't show it, any
+/// coarser and the value soon overflows.
+const MIN_STEP: f32 = 1e-6;
+const MAX_STEP: f32 = 1e6;
+
+const HELP: &[(&str, &str)] = &[
+    ("↑ ↓ / tab", "choose a slider"),
+    ("← →", "decrease / increase by the step"),
+    ("[ ]", "step ÷10 / ×10"),
+    ("0", "reset the chosen slider"),
+];
+
+/// One value binding under the user's control.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Slider {
+    pub name: String,
+    pub value: f32,
+    /// The value when the mode started, to reset or restore to
+    pub original: f32,
+    pub step: f32,
+}
+
+impl Slider {
+    pub(crate) fn nudge(&mut self, up: bool) {
+        let by = if up { self.step } else { -self.step };
+        // Stay on the step grid rather than accumulating float error.
+        self.value = rounded(self.value + by, self.step);
+    }
+
+    /// Makes the step 10 times finer or coarser, unless that would leave
+    /// [`MIN_STEP`]..=[`MAX_STEP`].
+    pub(crate) fn scale_step(&mut self, coarser: bool) {
+        let step = if coarser {
+            self.step * 10.0
+        } else {
+            self.step / 10.0
+        };
+        if (MIN_STEP..=MAX_STEP).contains(&step) {
+            self.step = step;
+        }
+    }
+
+    fn label(&self) -> String {
+        format!("{} = {}", self.name, format_tick(self.value, self.step))
+    }
+}
+
+/// `value` as displayed at `step`'s precision, so saved bindings are as
+/// tidy as the status bar.
+fn rounded(value: f32, step: f32) -> f32 {
+    format_tick(value, step).parse().unwrap_or(value)
+}
+
+/// Sliders for the named value bindings (comma or space separated), or for
+/// every value binding when `names` is blank. `ans` is never a slider.
+pub(crate) fn make_sliders(
+    names: &str,
+    defs: &Definitions,
+    step: f32,
+) -> Result<Vec<Slider>, String> {
+    let slider = |name: &str, value: f32| Slider {
+        name: name.to_string(),
+        value,
+        original: value,
+        step,
+    };
+
+    let names: Vec<&str> = names
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|n| !n.is_empty())
+        .collect();
+
+    if names.is_empty() {
+        let all: Vec<Slider> = defs
+            .iter()
+            .filter_map(|d| match d {
+                Definition::Value { name, value } if name != "ans" => Some(slider(name, *value)),
+                _ => None,
+            })
+            .collect();
+        if all.is_empty() {
+            return Err("No value bindings to slide. Bind one first, e.g. let a = 3".to_string());
+        }
+        return Ok(all);
+    }
+
+    names
+        .into_iter()
+        .map(|name| match defs.value(name) {
+            Some(_) if name == "ans" => Err("'ans' can't be a slider".to_string()),
+            Some(v) => Ok(slider(name, v)),
+            None if defs.function_body(name).is_some() => Err(format!(
+                "'{name}' is a function; only values can be sliders"
+            )),
+            None => Err(format!("Nothing named '{name}' is defined")),
+        })
+        .collect()
+}
+
+/// Runs the sliders and returns the frame they were showing when closed.
+pub(crate) fn sliders(
+    l: &mut impl Logger,
+    go: &GraphOptions,
+    repl: &mut Repl,
+) -> Option<(String, Plot)> {
+    let names = read_user_input("sliders (names, blank = all values): ").unwrap_or_default();
+    let step = loop {
+        match get_optional_numerical_input::<f32>("step (blank = 0.1): ", l) {
+            Some(s) if (MIN_STEP..=MAX_STEP).contains(&s) => break s,
+            Some(s) => l.eprint(&format!("step `{s}` must be from {MIN_STEP} to {MAX_STEP}")),
+            None => break DEFAULT_STEP,
+        }
+    };
+    let mut sliders = match make_sliders(&names, &repl.defs, step) {
+        Ok(s) => s,
+        Err(e) => {
+            l.eprint(&e);
+            return None;
+        }
+    };
+
+    let (eq, x_min, x_max) = get_g_inputs(l);
+    let go = with_y_range(l, go);
+
+    // Render once in the normal screen so a bad equation reports there.
+    if let Err(e) = graph(&eq, x_min, x_max, &go, &repl.defs) {
+        l.eprint(&error_render::render_error_with_source(&eq, &e, &repl.defs));
+        return None;
+    }
+
+    let last = run(&eq, (x_min, x_max), &go, &mut sliders, &mut repl.defs, l);
+
+    if sliders.iter().all(|s| s.value == s.original) {
+        return last;
+    }
+    let summary: Vec<String> = sliders.iter().map(Slider::label).collect();
+    let prompt = format!("keep {}? (y/n, blank = n): ", summary.join(", "));
+    let keep = confirm(&prompt, false, l).unwrap_or(false);
+
+    for s in &sliders {
+        let value = if keep { s.value } else { s.original };
+        // These names were value bindings when the mode started.
+        let _ = repl.defs.define_value(&s.name, value);
+    }
+    if keep {
+        bindings::save(repl, l);
+        l.print(&format!("Kept {}", summary.join(", ")));
+    }
+    last
+}
+
+fn run(
+    eq: &str,
+    (x_min, x_max): (f32, f32),
+    go: &GraphOptions,
+    sliders: &mut [Slider],
+    defs: &mut Definitions,
+    l: &mut impl Logger,
+) -> Option<(String, Plot)> {
+    let mut viewer = open_viewer(HELP, l)?;
+    let mut selected = 0;
+    let mut last = None;
+
+    loop {
+        for s in sliders.iter() {
+            let _ = defs.define_value(&s.name, s.value);
+        }
+
+        let view = viewer.fit(go);
+        if let Ok((g, plot)) = graph_with_plot(eq, x_min, x_max, &view, defs) {
+            let labels: Vec<String> = sliders
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    if i == selected {
+                        format!("▸{} (±{})", s.label(), format_tick(s.step, s.step))
+                    } else {
+                        s.label()
+                    }
+                })
+                .collect();
+            viewer.draw(&g, &format!("{eq}   {}", labels.join("   ")));
+            last = Some((g, plot));
+        }
+
+        let k = match viewer.next_event(None) {
+            ViewerEvent::Quit => return last,
+            ViewerEvent::Key(k) => k,
+            ViewerEvent::Redraw | ViewerEvent::Tick | ViewerEvent::Mouse(_) => continue,
+        };
+        let count = sliders.len();
+        let s = &mut sliders[selected];
+        match k.code {
+            KeyCode::Down | KeyCode::Tab => selected = (selected + 1) % count,
+            KeyCode::Up | KeyCode::BackTab => selected = (selected + count - 1) % count,
+            KeyCode::Right => s.nudge(true),
+            KeyCode::Left => s.nudge(false),
+            KeyCode::Char(']') => s.scale_step(true),
+            KeyCode::Char('[') => s.scale_step(false),
+            KeyCode::Char('0') => s.value = s.original,
+            _ => {}
+        }
+    }
+}
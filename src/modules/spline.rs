@@ -0,0 +1,281 @@
+//! `:spline`: smooth curves through a list of points, for eyeballing data.
+//!
+//! Every kind is turned into cubic Bezier segments, so it is drawn with the
+//! same adaptive flattening as `:bz`, and over equations in the same way.
+
+use std::path::Path;
+
+use rusty_maths::equation_analyzer::Definitions;
+
+use crate::modules::{
+    bezier_curve::{de_casteljau, flatten_for_graph, parse_points, round4, x_window, Spline},
+    common::{GraphOptions, Point},
+    error_render,
+    graphing::{graph_with_overlay, Overlay, Plot},
+    inputs::{get_optional_numerical_input, read_user_input},
+    logger::Logger,
+};
+
+/// Bisection steps when solving a segment for x; f32 runs out first.
+const SOLVE_STEPS: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SplineKind {
+    /// Through every point, tangents from the neighbours
+    CatmullRom,
+    /// Uniform cubic B-spline: smoother, pulled towards the points rather
+    /// than through them (except the two ends)
+    BSpline,
+    /// Natural cubic spline: y as a function of x through every point,
+    /// with no bend at either end
+    Natural,
+}
+
+impl SplineKind {
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "" | "catmull-rom" | "cr" => Some(SplineKind::CatmullRom),
+            "b-spline" | "bspline" | "b" => Some(SplineKind::BSpline),
+            "natural" | "n" => Some(SplineKind::Natural),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SplineKind::CatmullRom => "catmull-rom",
+            SplineKind::BSpline => "b-spline",
+            SplineKind::Natural => "natural cubic",
+        }
+    }
+}
+
+/// The curve of `kind` through (or near) `points`, as cubic Bezier segments.
+pub(crate) fn to_bezier(kind: SplineKind, points: &[Point]) -> Result<Spline, String> {
+    if points.len() < 2 {
+        return Err("A spline needs at least two points".to_string());
+    }
+    let points = match kind {
+        SplineKind::CatmullRom => catmull_rom(points),
+        SplineKind::BSpline => b_spline(points),
+        SplineKind::Natural => natural(points)?,
+    };
+    Ok(Spline { degree: 3, points })
+}
+
+/// Control points of a cubic segment, after the first one's start point.
+fn push_segment(out: &mut Vec<Point>, c1: (f32, f32), c2: (f32, f32), end: &Point) {
+    out.push(Point::new(c1.0, c1.1));
+    out.push(Point::new(c2.0, c2.1));
+    out.push(end.clone());
+}
+
+fn catmull_rom(points: &[Point]) -> Vec<Point> {
+    // The ends are repeated so the first and last segments have neighbours.
+    let mut padded = vec![points[0].clone()];
+    padded.extend_from_slice(points);
+    padded.extend(points.last().cloned());
+
+    let mut out = vec![points[0].clone()];
+    for w in padded.windows(4) {
+        let (p0, p1, p2, p3) = (&w[0], &w[1], &w[2], &w[3]);
+        push_segment(
+            &mut out,
+            (p1.x + (p2.x - p0.x) / 6.0, p1.y + (p2.y - p0.y) / 6.0),
+            (p2.x - (p3.x - p1.x) / 6.0, p2.y - (p3.y - p1.y) / 6.0),
+            p2,
+        );
+    }
+    out
+}
+
+fn b_spline(points: &[Point]) -> Vec<Point> {
+    // Tripled ends pin the curve to the first and last points.
+    let (first, last) = (&points[0], &points[points.len() - 1]);
+    let mut padded = vec![first.clone(), first.clone()];
+    padded.extend_from_slice(points);
+    padded.extend([last.clone(), last.clone()]);
+
+    let mix = |a: &Point, b: &Point, c: &Point| {
+        Point::new((a.x + 4.0 * b.x + c.x) / 6.0, (a.y + 4.0 * b.y + c.y) / 6.0)
+    };
+    // Each segment starts where the one before ended, at the mix of its
+    // first three points; the first of those mixes is `first` itself.
+    let mut out = vec![first.clone()];
+    for w in padded.windows(4) {
+        let (p1, p2, p3) = (&w[1], &w[2], &w[3]);
+        push_segment(
+            &mut out,
+            ((2.0 * p1.x + p2.x) / 3.0, (2.0 * p1.y + p2.y) / 3.0),
+            ((p1.x + 2.0 * p2.x) / 3.0, (p1.y + 2.0 * p2.y) / 3.0),
+            &mix(p1, p2, p3),
+        );
+    }
+    out
+}
+
+fn natural(points: &[Point]) -> Result<Vec<Point>, String> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x));
+    if let Some(w) = sorted.windows(2).find(|w| w[0].x == w[1].x) {
+        return Err(format!(
+            "A natural spline needs one point per x, but x = {} appears twice",
+            w[0].x
+        ));
+    }
+
+    // Second derivatives at each point, zero at the ends, from the usual
+    // tridiagonal system (Thomas algorithm).
+    let n = sorted.len();
+    let h: Vec<f32> = sorted.windows(2).map(|w| w[1].x - w[0].x).collect();
+    let slope: Vec<f32> = sorted
+        .windows(2)
+        .zip(&h)
+        .map(|(w, h)| (w[1].y - w[0].y) / h)
+        .collect();
+    let mut m = vec![0.0_f32; n];
+    if n > 2 {
+        let mut diag = vec![0.0_f32; n];
+        let mut rhs = vec![0.0_f32; n];
+        for i in 1..n - 1 {
+            diag[i] = 2.0 * (h[i - 1] + h[i]);
+            rhs[i] = 6.0 * (slope[i] - slope[i - 1]);
+            if i > 1 {
+                let factor = h[i - 1] / diag[i - 1];
+                diag[i] -= factor * h[i - 1];
+                rhs[i] -= factor * rhs[i - 1];
+            }
+        }
+        for i in (1..n - 1).rev() {
+            m[i] = (rhs[i] - h[i] * m[i + 1]) / diag[i];
+        }
+    }
+
+    // Each piece is a cubic in x, so its Bezier controls sit at thirds of
+    // the interval along the end tangents.
+    let mut out = vec![sorted[0].clone()];
+    for i in 0..n - 1 {
+        let (a, b) = (&sorted[i], &sorted[i + 1]);
+        let third = h[i] / 3.0;
+        let start_slope = slope[i] - h[i] * (2.0 * m[i] + m[i + 1]) / 6.0;
+        let end_slope = slope[i] + h[i] * (m[i] + 2.0 * m[i + 1]) / 6.0;
+        push_segment(
+            &mut out,
+            (a.x + third, a.y + third * start_slope),
+            (b.x - third, b.y - third * end_slope),
+            b,
+        );
+    }
+    Ok(out)
+}
+
+/// The curve's y where it first reaches `x`, or `None` when it never does.
+pub(crate) fn y_at(spline: &Spline, x: f32) -> Option<f32> {
+    spline.segments().find_map(|segment| {
+        let (x0, x1) = (segment[0].x, segment[segment.len() - 1].x);
+        if !(x0.min(x1)..=x0.max(x1)).contains(&x) {
+            return None;
+        }
+        let rising = x1 >= x0;
+        let (mut lo, mut hi) = (0.0_f32, 1.0_f32);
+        for _ in 0..SOLVE_STEPS {
+            let mid = (lo + hi) / 2.0;
+            if (de_casteljau(segment, mid).x < x) == rising {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Some(de_casteljau(segment, (lo + hi) / 2.0).y)
+    })
+}
+
+/// Points from a CSV file: the first two columns of each line, skipping
+/// blank lines and a header.
+pub(crate) fn read_csv_points(text: &str) -> Result<Vec<Point>, String> {
+    let mut points = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let parsed = match fields.as_slice() {
+            [""] => continue,
+            [x, y, ..] => x.parse::<f32>().ok().zip(y.parse::<f32>().ok()),
+            _ => None,
+        };
+        match parsed {
+            Some((x, y)) => points.push(Point::new(x, y)),
+            None if i == 0 => continue,
+            None => return Err(format!("line {}: expected x,y but found '{line}'", i + 1)),
+        }
+    }
+    Ok(points)
+}
+
+/// Points typed as `x y, x y, ...` or read from a .csv file, at least two.
+pub(crate) fn get_points(answer: &str) -> Result<Vec<Point>, String> {
+    if answer.to_ascii_lowercase().ends_with(".csv") {
+        let text = std::fs::read_to_string(Path::new(answer))
+            .map_err(|e| format!("Could not read {answer}: {e}"))?;
+        let points = read_csv_points(&text)?;
+        if points.len() < 2 {
+            return Err("A curve needs at least two points".to_string());
+        }
+        return Ok(points);
+    }
+    parse_points(answer)
+}
+
+/// Runs `:spline` and returns the graph it drew.
+pub(crate) fn spline(
+    l: &mut impl Logger,
+    go: &GraphOptions,
+    defs: &Definitions,
+) -> Option<(String, Plot)> {
+    let points = loop {
+        let answer = read_user_input("points (x y, x y, ... or a .csv file): ").ok()?;
+        match get_points(&answer) {
+            Ok(points) => break points,
+            Err(e) => l.eprint(&e),
+        }
+    };
+    let (kind, bezier) = loop {
+        let answer = read_user_input("kind catmull-rom/b-spline/natural (blank = catmull-rom): ")
+            .unwrap_or_default();
+        let Some(kind) = SplineKind::parse(&answer) else {
+            l.eprint(&format!(
+                "Unknown kind '{answer}'. Valid: catmull-rom, b-spline, natural"
+            ));
+            continue;
+        };
+        match to_bezier(kind, &points) {
+            Ok(bezier) => break (kind, bezier),
+            Err(e) => l.eprint(&e),
+        }
+    };
+    let eq = read_user_input("overlay equations (blank = none): ").unwrap_or_default();
+
+    let (x_min, x_max) = x_window(&bezier.points);
+    let overlay = Overlay {
+        paths: vec![(
+            kind.name().to_string(),
+            flatten_for_graph(&bezier, (x_min, x_max), go),
+        )],
+        markers: points,
+        dots: vec![],
+    };
+    let drawn = match graph_with_overlay(&eq, x_min, x_max, go, defs, &overlay) {
+        Ok(drawn) => drawn,
+        Err(e) => {
+            l.eprint(&error_render::render_error_with_source(&eq, &e, defs));
+            return None;
+        }
+    };
+    l.print(&drawn.0);
+
+    while let Some(x) = get_optional_numerical_input::<f32>("y at x (blank = done): ", l) {
+        match y_at(&bezier, x) {
+            Some(y) => l.print(&format!("x = {x}: y = {}", round4(y))),
+            None => l.eprint(&format!("The curve doesn't reach x = {x}")),
+        }
+    }
+    Some(drawn)
+}
@@ -0,0 +1,436 @@
+//! `:stats`: summary statistics, a histogram and a box plot for a list of
+//! numbers typed at the prompt, pasted a line at a time, or read from a
+//! column of a CSV file.
+//!
+//! Both plots are overlays on an otherwise empty graph, so they get the
+//! same frame, ticks and braille rendering as everything else — and the
+//! histogram can be saved with `:save`.
+
+use std::path::Path;
+
+use rusty_maths::equation_analyzer::Definitions;
+
+use crate::modules::{
+    common::{GraphOptions, Point, Scale},
+    graphing::{graph_with_overlay, Overlay, Plot},
+    inputs::{get_optional_numerical_input, read_user_input},
+    logger::Logger,
+    string_maker::format_entry,
+};
+
+/// Most bins a histogram is split into
+pub(crate) const MAX_BINS: usize = 100;
+/// Tukey's fences: whiskers reach at most this many IQRs past the box, and
+/// anything beyond is an outlier.
+const FENCE: f64 = 1.5;
+/// Height of the box plot, in braille dots
+const BOX_PLOT_HEIGHT: usize = 16;
+
+/// Summary statistics of a data set.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    /// The most frequent values, or empty when none repeats
+    pub modes: Vec<f64>,
+    /// `None` for a single value
+    pub sample_variance: Option<f64>,
+    pub population_variance: f64,
+    pub min: f64,
+    pub q1: f64,
+    pub q3: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    /// `None` for an empty data set.
+    pub(crate) fn of(data: &[f64]) -> Option<Summary> {
+        if data.is_empty() {
+            return None;
+        }
+        let mut sorted = data.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let count = sorted.len();
+        let n = count as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let squares: f64 = sorted.iter().map(|v| (v - mean).powi(2)).sum();
+
+        Some(Summary {
+            count,
+            mean,
+            median: quantile(&sorted, 0.5),
+            modes: modes(&sorted),
+            sample_variance: (count > 1).then(|| squares / (n - 1.0)),
+            population_variance: squares / n,
+            min: sorted[0],
+            q1: quantile(&sorted, 0.25),
+            q3: quantile(&sorted, 0.75),
+            max: sorted[count - 1],
+        })
+    }
+
+    /// Label and value lines, values formatted at `precision`.
+    pub(crate) fn lines(&self, precision: usize) -> Vec<(&'static str, String)> {
+        let f = |v: f64| format_entry(v, precision);
+        let modes = if self.modes.is_empty() {
+            "none (no value repeats)".to_string()
+        } else {
+            self.modes
+                .iter()
+                .map(|&v| f(v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let sample = |v: Option<f64>| v.map_or("needs two values".to_string(), f);
+        vec![
+            ("count", self.count.to_string()),
+            ("mean", f(self.mean)),
+            ("median", f(self.median)),
+            ("mode", modes),
+            (
+                "stdev (sample)",
+                sample(self.sample_variance.map(f64::sqrt)),
+            ),
+            ("stdev (population)", f(self.population_variance.sqrt())),
+            ("variance (sample)", sample(self.sample_variance)),
+            ("variance (population)", f(self.population_variance)),
+            ("min", f(self.min)),
+            ("Q1", f(self.q1)),
+            ("Q3", f(self.q3)),
+            ("max", f(self.max)),
+        ]
+    }
+}
+
+/// The `p` quantile of sorted data, interpolating linearly between the
+/// closest ranks (the method most spreadsheets and numpy default to).
+pub(crate) fn quantile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    sorted[below] + (rank - below as f64) * (sorted[above] - sorted[below])
+}
+
+/// Every value that occurs most often, when that's more than once.
+fn modes(sorted: &[f64]) -> Vec<f64> {
+    let runs: Vec<(f64, usize)> = sorted
+        .chunk_by(|a, b| a == b)
+        .map(|run| (run[0], run.len()))
+        .collect();
+    let most = runs.iter().map(|&(_, n)| n).max().unwrap_or(0);
+    if most < 2 {
+        return vec![];
+    }
+    runs.into_iter()
+        .filter(|&(_, n)| n == most)
+        .map(|(v, _)| v)
+        .collect()
+}
+
+/// Numbers split on commas and/or whitespace, across any number of lines.
+pub(crate) fn read_numbers(text: &str) -> Result<Vec<f64>, String> {
+    let data = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .map(|t| parse_finite(t).ok_or_else(|| format!("'{t}' is not a number")))
+        .collect::<Result<Vec<f64>, String>>()?;
+    if data.is_empty() {
+        return Err("There are no numbers to summarize".to_string());
+    }
+    Ok(data)
+}
+
+/// `inf` and `NaN` parse as floats, but have no place in a data set.
+fn parse_finite(s: &str) -> Option<f64> {
+    s.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// A CSV file's column names: its header, or `1`, `2`, ... without one.
+pub(crate) fn csv_columns(text: &str) -> Vec<String> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let Some(first) = lines.next() else {
+        return vec![];
+    };
+    let fields: Vec<&str> = first.split(',').map(str::trim).collect();
+    if is_header(&fields) {
+        fields.iter().map(|f| f.to_string()).collect()
+    } else {
+        (1..=fields.len()).map(|i| i.to_string()).collect()
+    }
+}
+
+fn is_header(fields: &[&str]) -> bool {
+    fields
+        .iter()
+        .any(|f| !f.is_empty() && f.parse::<f64>().is_err())
+}
+
+/// The numbers in one column of a CSV file, picked by header name or by
+/// number from 1 (blank = the first). Blank cells are skipped.
+pub(crate) fn csv_column(text: &str, column: &str) -> Result<Vec<f64>, String> {
+    let columns = csv_columns(text);
+    let index = match column.trim() {
+        "" => 0,
+        c => match c.parse::<usize>() {
+            Ok(n) if (1..=columns.len()).contains(&n) => n - 1,
+            _ => columns
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(c))
+                .ok_or_else(|| format!("No column '{c}'. Columns: {}", columns.join(", ")))?,
+        },
+    };
+
+    let mut data = Vec::new();
+    let mut seen_first = false;
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if !seen_first {
+            seen_first = true;
+            if is_header(&fields) {
+                continue;
+            }
+        }
+        match fields.get(index) {
+            Some(&"") => {}
+            Some(cell) => match parse_finite(cell) {
+                Some(v) => data.push(v),
+                None => return Err(format!("line {}: '{cell}' is not a number", i + 1)),
+            },
+            None => {
+                return Err(format!(
+                    "line {} has no column {}",
+                    i + 1,
+                    columns.get(index).map_or("", String::as_str)
+                ))
+            }
+        }
+    }
+    if data.is_empty() {
+        return Err("There are no numbers in that column".to_string());
+    }
+    Ok(data)
+}
+
+/// Sturges' rule: enough bins to show the shape without leaving most of
+/// them empty.
+pub(crate) fn auto_bins(count: usize) -> usize {
+    ((count as f64).log2().ceil() as usize + 1).clamp(1, MAX_BINS)
+}
+
+/// `bins` equal-width bins over the data's range as (low edge, high edge,
+/// count). Each bin holds values from its low edge up to but not
+/// including its high edge, except the last, which also holds the max.
+pub(crate) fn histogram(data: &[f64], bins: usize) -> Vec<(f64, f64, usize)> {
+    let min = data.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    // All-equal data still gets a bar of some width.
+    let (lo, hi) = if min < max {
+        (min, max)
+    } else {
+        (min - 0.5, max + 0.5)
+    };
+    let width = (hi - lo) / bins as f64;
+
+    let mut counts = vec![0; bins];
+    for v in data {
+        let bin = ((v - lo) / width).floor() as usize;
+        counts[bin.min(bins - 1)] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, n)| (lo + i as f64 * width, lo + (i + 1) as f64 * width, n))
+        .collect()
+}
+
+/// Each non-empty bin as a bar outline: up, across, down. The bars are
+/// joined into one path with NaN breaks, which drawing skips.
+pub(crate) fn histogram_overlay(bins: &[(f64, f64, usize)]) -> Overlay {
+    let mut path = Vec::new();
+    for &(lo, hi, n) in bins.iter().filter(|&&(_, _, n)| n > 0) {
+        let (lo, hi, n) = (lo as f32, hi as f32, n as f32);
+        path.extend([
+            Point::new(lo, 0.0),
+            Point::new(lo, n),
+            Point::new(hi, n),
+            Point::new(hi, 0.0),
+            Point::new(f32::NAN, f32::NAN),
+        ]);
+    }
+    Overlay {
+        paths: vec![("histogram".to_string(), path)],
+        markers: vec![],
+        dots: vec![],
+    }
+}
+
+/// A horizontal box plot between y = 0 and 1: whiskers out to the furthest
+/// values within the fences, and the values beyond them as markers.
+pub(crate) fn box_plot_overlay(data: &[f64], s: &Summary) -> Overlay {
+    let iqr = s.q3 - s.q1;
+    let (lo_fence, hi_fence) = (s.q1 - FENCE * iqr, s.q3 + FENCE * iqr);
+    let inside = data
+        .iter()
+        .copied()
+        .filter(|v| (lo_fence..=hi_fence).contains(v));
+    let low = inside.clone().fold(s.q1, f64::min) as f32;
+    let high = inside.fold(s.q3, f64::max) as f32;
+    let (q1, median, q3) = (s.q1 as f32, s.median as f32, s.q3 as f32);
+    let gap = Point::new(f32::NAN, f32::NAN);
+
+    let path = vec![
+        // whiskers and their caps
+        Point::new(low, 0.3),
+        Point::new(low, 0.7),
+        gap.clone(),
+        Point::new(low, 0.5),
+        Point::new(q1, 0.5),
+        gap.clone(),
+        Point::new(q3, 0.5),
+        Point::new(high, 0.5),
+        gap.clone(),
+        Point::new(high, 0.3),
+        Point::new(high, 0.7),
+        gap.clone(),
+        // the box and its median
+        Point::new(q1, 0.2),
+        Point::new(q3, 0.2),
+        Point::new(q3, 0.8),
+        Point::new(q1, 0.8),
+        Point::new(q1, 0.2),
+        gap,
+        Point::new(median, 0.2),
+        Point::new(median, 0.8),
+    ];
+    Overlay {
+        paths: vec![("box plot".to_string(), path)],
+        markers: data
+            .iter()
+            .filter(|v| !(lo_fence..=hi_fence).contains(*v))
+            .map(|&v| Point::new(v as f32, 0.5))
+            .collect(),
+        dots: vec![],
+    }
+}
+
+/// Reads the data set: a line of numbers followed by any more lines of
+/// them (pasted data), or a .csv file and one of its columns.
+fn get_data(l: &mut impl Logger) -> Option<Vec<f64>> {
+    loop {
+        let answer = read_user_input("data (numbers or a .csv file): ").ok()?;
+        if answer.is_empty() {
+            return None;
+        }
+        let data = if answer.to_ascii_lowercase().ends_with(".csv") {
+            std::fs::read_to_string(Path::new(&answer))
+                .map_err(|e| format!("Could not read {answer}: {e}"))
+                .and_then(|text| {
+                    let columns = csv_columns(&text);
+                    let column = if columns.len() > 1 {
+                        read_user_input(&format!(
+                            "column ({}; blank = {}): ",
+                            columns.join(", "),
+                            columns[0]
+                        ))
+                        .unwrap_or_default()
+                    } else {
+                        String::new()
+                    };
+                    csv_column(&text, &column)
+                })
+        } else {
+            let mut text = answer;
+            while let Ok(more) = read_user_input("more (blank = done): ") {
+                if more.is_empty() {
+                    break;
+                }
+                text.push('\n');
+                text.push_str(&more);
+            }
+            read_numbers(&text)
+        };
+        match data {
+            Ok(data) => return Some(data),
+            Err(e) => l.eprint(&e),
+        }
+    }
+}
+
+/// Runs `:stats` and returns the histogram it drew.
+pub(crate) fn stats(
+    l: &mut impl Logger,
+    go: &GraphOptions,
+    precision: usize,
+) -> Option<(String, Plot)> {
+    let data = get_data(l)?;
+    let summary = Summary::of(&data)?;
+
+    let lines = summary.lines(precision);
+    let width = lines
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    for (label, value) in lines {
+        l.print(&format!("{label:<width$}  {value}"));
+    }
+
+    let bins = loop {
+        match get_optional_numerical_input::<usize>("bins (blank = auto): ", l) {
+            Some(n) if (1..=MAX_BINS).contains(&n) => break n,
+            Some(_) => l.eprint(&format!("Bins must be from 1 to {MAX_BINS}")),
+            None => break auto_bins(data.len()),
+        }
+    };
+    let bars = histogram(&data, bins);
+
+    // Both plots share an x range, a little wider than the bars so the
+    // outer ones don't sit on the frame.
+    let (lo, hi) = (bars[0].0 as f32, bars[bins - 1].1 as f32);
+    let margin = (hi - lo) * 0.02;
+    let (x_min, x_max) = (lo - margin, hi + margin);
+
+    // Counts on a plain linear axis, whatever the session's y settings.
+    let go = GraphOptions {
+        y_fixed: false,
+        x_scale: Scale::Linear,
+        y_scale: Scale::Linear,
+        equal_aspect: false,
+        ..*go
+    };
+    let defs = Definitions::new();
+    let drawn = graph_with_overlay("", x_min, x_max, &go, &defs, &histogram_overlay(&bars)).ok()?;
+
+    let box_go = GraphOptions {
+        y_min: 0.0,
+        y_max: 1.0,
+        y_fixed: true,
+        height: BOX_PLOT_HEIGHT,
+        ..go
+    };
+    let box_plot = box_plot_overlay(&data, &summary);
+    let Ok((box_g, box_plot)) = graph_with_overlay("", x_min, x_max, &box_go, &defs, &box_plot)
+    else {
+        l.print(&drawn.0);
+        return Some(drawn);
+    };
+
+    // Line the two frames up, whichever has the wider y labels.
+    let (hist_gutter, box_gutter) = (drawn.1.layout.gutter, box_plot.layout.gutter);
+    l.print(&indent(&drawn.0, box_gutter.saturating_sub(hist_gutter)));
+    l.print(&indent(&box_g, hist_gutter.saturating_sub(box_gutter)));
+    Some(drawn)
+}
+
+fn indent(s: &str, by: usize) -> String {
+    let pad = " ".repeat(by);
+    s.lines()
+        .map(|line| format!("{pad}{line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
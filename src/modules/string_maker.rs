@@ -1,4 +1,5 @@
-use crate::modules::common::Point;
+use crate::modules::common::{Point, Tick};
+use rusty_maths::linear_algebra::Matrix;
 use std::fmt::Write;
 
 const UPPER_LEFT: &str = "┌";
@@ -7,6 +8,8 @@ const BOTTOM_LEFT: &str = "└";
 const BOTTOM_RIGHT: &str = "┘";
 const HORIZONTAL_BAR: &str = "─";
 const VERTICAL_BAR: &str = "│";
+const LEFT_TICK: &str = "┤";
+const BOTTOM_TICK: &str = "┬";
 
 const UNDERLINE_START: &str = "\u{001b}[4m";
 const UNDERLINE_END: &str = "\u{001b}[0m";
@@ -49,76 +52,138 @@ pub(crate) fn make_table_string(points: Vec<Point>) -> String {
     first_row + &second_row + &middle_rows + &last_row
 }
 
-pub(crate) fn make_graph_string(
-    chars: Vec<Vec<char>>,
-    x_min: f32,
-    x_max: f32,
-    y_min: f32,
-    y_max: f32,
-) -> String {
-    let gap = chars.first().map_or(0, |row| row.len());
-    let gap_str = HORIZONTAL_BAR.repeat(gap);
-
-    let top_line = format!("{}{}{}{:.2}\n", UPPER_LEFT, gap_str, UPPER_RIGHT, y_max);
-
-    let middle_lines = chars.iter().fold(String::new(), |mut acc, s| {
+/// `m` in square brackets drawn with box characters, each column right
+/// aligned, entries at `precision` decimals.
+pub(crate) fn make_matrix_string(m: &Matrix, precision: usize) -> String {
+    let cells: Vec<Vec<String>> = m
+        .iter()
+        .map(|row| row.iter().map(|&v| format_entry(v, precision)).collect())
+        .collect();
+    let cols = cells.first().map_or(0, Vec::len);
+    let widths: Vec<usize> = (0..cols)
+        .map(|j| {
+            cells
+                .iter()
+                .map(|row| row[j].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let inner = widths.iter().sum::<usize>() + 2 * cols.saturating_sub(1) + 2;
+
+    let top_line = format!("{}{}{}\n", UPPER_LEFT, " ".repeat(inner), UPPER_RIGHT);
+    let middle_lines = cells.iter().fold(String::new(), |mut acc, row| {
+        let entries: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &w)| format!("{cell:>w$}"))
+            .collect();
         // Writing to String never fails, safe to ignore
         let _ = writeln!(
             acc,
-            "{}{}{}",
+            "{} {} {}",
             VERTICAL_BAR,
-            s.iter().collect::<String>(),
+            entries.join("  "),
             VERTICAL_BAR
         );
         acc
     });
+    let bottom_line = format!("{}{}{}", BOTTOM_LEFT, " ".repeat(inner), BOTTOM_RIGHT);
 
-    let bottom_line = format!("{}{}{}{:.2}\n", BOTTOM_LEFT, gap_str, BOTTOM_RIGHT, y_min);
-
-    let x_axis_line = format!(
-        "{}{}{}{}",
-        x_min,
-        " ".repeat(gap.saturating_sub(1)),
-        x_max,
-        " ".repeat(5)
-    );
+    top_line + &middle_lines + &bottom_line
+}
 
-    top_line + &middle_lines + &bottom_line + &x_axis_line
+/// `v` at `precision` decimals, dropping an all-zero fraction (`2.00` is
+/// `2`) and the sign of a rounded-away negative (`-0.00` is `0`).
+pub(crate) fn format_entry(v: f64, precision: usize) -> String {
+    let s = format!("{v:.precision$}");
+    let trailing = format!(".{}", "0".repeat(precision));
+    let s = s.strip_suffix(&trailing).unwrap_or(&s);
+    if s.trim_start_matches('-')
+        .chars()
+        .all(|c| c == '0' || c == '.')
+    {
+        s.trim_start_matches('-').to_string()
+    } else {
+        s.to_string()
+    }
 }
 
-pub(crate) fn make_curve_string(
+pub(crate) fn make_graph_string(
     chars: Vec<Vec<char>>,
-    x_min: f32,
-    x_max: f32,
-    y_min: f32,
-    y_max: f32,
+    x_ticks: &[Tick],
+    y_ticks: &[Tick],
 ) -> String {
     let gap = chars.first().map_or(0, |row| row.len());
-    let gap_str = HORIZONTAL_BAR.repeat(gap);
+    let gutter = gutter_width(y_ticks);
+    let margin = " ".repeat(gutter);
 
-    let top_line = format!("{}{}{}{:.2}\n", UPPER_LEFT, gap_str, UPPER_RIGHT, y_min);
-
-    let middle_lines = chars.iter().fold(String::new(), |mut acc, s| {
-        // Writing to String never fails, safe to ignore
-        let _ = writeln!(
-            acc,
-            "{}{}{}",
-            VERTICAL_BAR,
-            s.iter().collect::<String>(),
-            VERTICAL_BAR
-        );
-        acc
-    });
+    let top_line = format!(
+        "{}{}{}{}\n",
+        margin,
+        UPPER_LEFT,
+        HORIZONTAL_BAR.repeat(gap),
+        UPPER_RIGHT
+    );
 
-    let bottom_line = format!("{}{}{}{:.2}\n", BOTTOM_LEFT, gap_str, BOTTOM_RIGHT, y_max);
+    let middle_lines = chars
+        .iter()
+        .enumerate()
+        .fold(String::new(), |mut acc, (i, s)| {
+            let (label, edge) = match y_ticks.iter().find(|t| t.pos == i) {
+                Some(t) => (t.label.as_str(), LEFT_TICK),
+                None => ("", VERTICAL_BAR),
+            };
+            // Writing to String never fails, safe to ignore
+            let _ = writeln!(
+                acc,
+                "{:>gutter$}{}{}{}",
+                label,
+                edge,
+                s.iter().collect::<String>(),
+                VERTICAL_BAR
+            );
+            acc
+        });
+
+    let bottom_bar: String = (0..gap)
+        .map(|i| {
+            if x_ticks.iter().any(|t| t.pos == i) {
+                BOTTOM_TICK
+            } else {
+                HORIZONTAL_BAR
+            }
+        })
+        .collect();
+    let bottom_line = format!("{}{}{}{}\n", margin, BOTTOM_LEFT, bottom_bar, BOTTOM_RIGHT);
+
+    top_line + &middle_lines + &bottom_line + &x_label_line(x_ticks, gutter + 1)
+}
 
-    let x_axis_line = format!(
-        "\n{}{}{}{}\n",
-        x_min,
-        " ".repeat(gap.saturating_sub(1)),
-        x_max,
-        " ".repeat(5)
-    );
+/// Columns left of the graph frame taken up by the right-aligned y labels.
+pub(crate) fn gutter_width(y_ticks: &[Tick]) -> usize {
+    y_ticks
+        .iter()
+        .map(|t| t.label.chars().count())
+        .max()
+        .unwrap_or(0)
+}
 
-    x_axis_line + &top_line + &middle_lines + &bottom_line
+/// Lays x tick labels out under their `┬` marks (the frame's inner columns
+/// start at `offset`), dropping any label that would run into the previous
+/// one.
+fn x_label_line(x_ticks: &[Tick], offset: usize) -> String {
+    let mut line = String::new();
+    let mut used = 0;
+    for t in x_ticks {
+        let len = t.label.chars().count();
+        let start = (offset + t.pos).saturating_sub(len / 2);
+        if start < used {
+            continue;
+        }
+        line.push_str(&" ".repeat(start - line.chars().count()));
+        line.push_str(&t.label);
+        used = start + len + 1;
+    }
+    line
 }
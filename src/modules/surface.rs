@@ -0,0 +1,495 @@
+//! Functions of two variables: `:surf` draws z = f(x, y) as a wireframe
+//! surface you can turn with the arrow keys, and `:contour` draws its
+//! level curves on an ordinary graph.
+//!
+//! The calculator only knows one free variable, so `y` is swapped for a
+//! stand-in letter bound like any value (the way `:an` binds `t`), and the
+//! `z` on the left becomes the `y` the calculator expects. The swaps keep
+//! every character where it was, so error carets still line up.
+
+use std::{fmt::Write, time::Duration};
+
+use crossterm::event::KeyCode;
+use rusty_maths::equation_analyzer::{calculator::plot_with, Definitions, EquationError};
+
+use crate::modules::{
+    bindings::Shadow,
+    commands::open_viewer,
+    common::{
+        draw_line, get_braille, make_cell_matrix, CellMatrix, CharMatrix, GraphOptions, Point,
+    },
+    cube::rotate_points,
+    error_render,
+    graphing::{clip_segment, format_tick, graph_with_overlay, nice_ticks, Overlay, Plot},
+    inputs::{get_optional_numerical_input, read_user_input},
+    logger::Logger,
+    viewer::ViewerEvent,
+};
+
+/// Letters `y` may be swapped for, in order of preference.
+const STAND_INS: [&str; 10] = ["v", "w", "u", "s", "q", "k", "j", "h", "m", "n"];
+
+/// A `(min, max)` span of one axis.
+type Range = (f32, f32);
+
+const DEFAULT_RANGE: Range = (-5.0, 5.0);
+const DEFAULT_GRID: usize = 24;
+const MAX_GRID: usize = 100;
+const DEFAULT_LEVELS: usize = 10;
+const MAX_LEVELS: usize = 50;
+// Degrees per arrow (or , .) press, and per tick while spinning
+const TURN_STEP: f32 = 6.0;
+const SPIN_STEP: f32 = 1.5;
+const FRAME_DELAY: Duration = Duration::from_millis(30);
+// How far the surface is tipped towards the viewer to start with
+const START_TILT: f32 = -60.0;
+const START_TURN: f32 = 30.0;
+
+const HELP: &[(&str, &str)] = &[
+    ("↑ ↓", "tip towards / away"),
+    ("← →", "turn left / right"),
+    (", .", "roll"),
+    ("space", "spin / stop"),
+    ("r", "reset the view"),
+];
+
+/// A z = f(x, y) equation, ready for the calculator.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SurfaceEquation {
+    /// As shown to the user, always starting `z =`
+    pub display: String,
+    /// As handed to the calculator, the same length as `display`
+    pub calc: String,
+    /// The letter standing in for `y`
+    pub stand_in: &'static str,
+}
+
+impl SurfaceEquation {
+    /// Reads `z = f(x, y)` or just `f(x, y)`. `None` when every stand-in
+    /// letter is already used in the expression.
+    pub(crate) fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let display = match input.strip_prefix('z') {
+            Some(rest) if rest.trim_start().starts_with('=') => input.to_string(),
+            _ => format!("z = {input}"),
+        };
+        let stand_in = *STAND_INS
+            .iter()
+            .find(|s| word_positions(&display, letter(s)).next().is_none())?;
+
+        let mut calc: Vec<char> = display.chars().collect();
+        calc[0] = 'y';
+        for i in word_positions(&display, 'y') {
+            calc[i] = letter(stand_in);
+        }
+        Some(SurfaceEquation {
+            display,
+            calc: calc.into_iter().collect(),
+            stand_in,
+        })
+    }
+}
+
+fn letter(stand_in: &str) -> char {
+    stand_in.chars().next().unwrap_or('y')
+}
+
+/// Char indices where `letter` stands alone as a name.
+fn word_positions(s: &str, letter: char) -> impl Iterator<Item = usize> {
+    let chars: Vec<char> = s.chars().collect();
+    let is_name = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+    (0..chars.len()).filter(move |&i| {
+        chars[i] == letter
+            && !is_name(i.checked_sub(1).and_then(|j| chars.get(j)))
+            && !is_name(chars.get(i + 1))
+    })
+}
+
+/// z sampled on an evenly spaced grid; `z[j][i]` is at (`xs[i]`, `ys[j]`).
+#[derive(Debug, Clone)]
+pub(crate) struct Grid {
+    pub xs: Vec<f32>,
+    pub ys: Vec<f32>,
+    pub z: Vec<Vec<f32>>,
+}
+
+impl Grid {
+    /// The smallest and largest finite z, if there are any.
+    pub(crate) fn z_range(&self) -> Option<(f32, f32)> {
+        let finite = self.z.iter().flatten().filter(|z| z.is_finite());
+        let (lo, hi) = finite.fold((f32::MAX, f32::MIN), |(lo, hi), &z| (lo.min(z), hi.max(z)));
+        (lo <= hi).then_some((lo, hi))
+    }
+}
+
+fn steps((lo, hi): Range, n: usize) -> Vec<f32> {
+    (0..n)
+        .map(|i| lo + (hi - lo) * i as f32 / (n - 1).max(1) as f32)
+        .collect()
+}
+
+/// Samples `eq` at `nx`×`ny` points. The first point's error, if any, is
+/// returned so a typo reports once; elsewhere failures are holes (NaN).
+pub(crate) fn sample_grid(
+    eq: &SurfaceEquation,
+    x_range: Range,
+    y_range: Range,
+    (nx, ny): (usize, usize),
+    defs: &mut Definitions,
+) -> Result<Grid, EquationError> {
+    let shadow = Shadow::new(defs, eq.stand_in);
+    let grid = sample_with_stand_in(eq, x_range, y_range, (nx, ny), defs);
+    shadow.restore(defs);
+    grid
+}
+
+fn sample_with_stand_in(
+    eq: &SurfaceEquation,
+    x_range: Range,
+    y_range: Range,
+    (nx, ny): (usize, usize),
+    defs: &mut Definitions,
+) -> Result<Grid, EquationError> {
+    let xs = steps(x_range, nx);
+    let ys = steps(y_range, ny);
+    let mut z = Vec::with_capacity(ny);
+    for (j, &y) in ys.iter().enumerate() {
+        defs.define_value(eq.stand_in, y)?;
+        let mut row = Vec::with_capacity(nx);
+        for (i, &x) in xs.iter().enumerate() {
+            let value = match plot_with(&eq.calc, x, x, 1.0, defs) {
+                Ok(points) => points.first().map_or(f32::NAN, |p| p.y),
+                Err(e) if i == 0 && j == 0 => return Err(e),
+                Err(_) => f32::NAN,
+            };
+            row.push(value);
+        }
+        z.push(row);
+    }
+    Ok(Grid { xs, ys, z })
+}
+
+/// Prompts for the equation and the x and y ranges, each range with a
+/// default for blank answers.
+fn get_surface_inputs(l: &mut impl Logger) -> Option<(SurfaceEquation, Range, Range)> {
+    let eq = loop {
+        let answer = read_user_input("z = f(x, y): ").ok()?;
+        match SurfaceEquation::parse(&answer) {
+            Some(eq) => break eq,
+            None => l.eprint("That equation uses every letter that can stand in for y"),
+        }
+    };
+    let x_range = get_range("x", l);
+    let y_range = get_range("y", l);
+    Some((eq, x_range, y_range))
+}
+
+fn get_range(axis: &str, l: &mut impl Logger) -> Range {
+    loop {
+        let lo = get_optional_numerical_input(&format!("{axis} min (blank = -5): "), l)
+            .unwrap_or(DEFAULT_RANGE.0);
+        let hi = get_optional_numerical_input(&format!("{axis} max (blank = 5): "), l)
+            .unwrap_or(DEFAULT_RANGE.1);
+        if lo < hi {
+            return (lo, hi);
+        }
+        l.eprint(&format!(
+            "{axis} min `{lo}` must be less than {axis} max `{hi}`"
+        ));
+    }
+}
+
+fn report(eq: &SurfaceEquation, e: &EquationError, defs: &Definitions, l: &mut impl Logger) {
+    l.eprint(&error_render::render_error_with_source(
+        &eq.display,
+        e,
+        defs,
+    ));
+}
+
+/// Runs `:surf` and returns the frame it was showing when closed.
+pub(crate) fn surf(
+    l: &mut impl Logger,
+    go: &GraphOptions,
+    defs: &mut Definitions,
+) -> Option<String> {
+    let (eq, x_range, y_range) = get_surface_inputs(l)?;
+    let n = loop {
+        match get_optional_numerical_input::<usize>("grid lines (blank = 24): ", l) {
+            Some(n) if (2..=MAX_GRID).contains(&n) => break n,
+            Some(n) => l.eprint(&format!("grid lines `{n}` must be from 2 to {MAX_GRID}")),
+            None => break DEFAULT_GRID,
+        }
+    };
+    let grid = match sample_grid(&eq, x_range, y_range, (n, n), defs) {
+        Ok(grid) => grid,
+        Err(e) => {
+            report(&eq, &e, defs, l);
+            return None;
+        }
+    };
+    let Some((z_min, z_max)) = grid.z_range() else {
+        l.eprint(&format!(
+            "{} is undefined everywhere in that range",
+            eq.display
+        ));
+        return None;
+    };
+
+    let mut viewer = open_viewer(HELP, l)?;
+    let mut view = viewer.fit(go);
+    let mut points = surface_points(&grid, &view);
+    let mut spinning = false;
+    // A flat surface has no range to size the decimals by.
+    let z = if z_max > z_min {
+        let step = (z_max - z_min) / 100.0;
+        format!(
+            "z {}..{}",
+            format_tick(z_min, step),
+            format_tick(z_max, step)
+        )
+    } else {
+        format!("z = {z_min}")
+    };
+    let status = format!(
+        "{}   x {}..{}   y {}..{}   {z}",
+        eq.display, x_range.0, x_range.1, y_range.0, y_range.1,
+    );
+
+    loop {
+        let frame = make_surface(&view, &points, grid.xs.len());
+        viewer.draw(&frame, &status);
+
+        match viewer.next_event(spinning.then_some(FRAME_DELAY)) {
+            ViewerEvent::Quit => return Some(frame),
+            ViewerEvent::Tick => rotate_points(&mut points, 0.0, SPIN_STEP, 0.0),
+            ViewerEvent::Redraw => {
+                let fitted = viewer.fit(go);
+                if (fitted.width, fitted.height) != (view.width, view.height) {
+                    view = fitted;
+                    points = surface_points(&grid, &view);
+                }
+            }
+            ViewerEvent::Key(k) => match k.code {
+                KeyCode::Up => rotate_points(&mut points, TURN_STEP, 0.0, 0.0),
+                KeyCode::Down => rotate_points(&mut points, -TURN_STEP, 0.0, 0.0),
+                KeyCode::Left => rotate_points(&mut points, 0.0, TURN_STEP, 0.0),
+                KeyCode::Right => rotate_points(&mut points, 0.0, -TURN_STEP, 0.0),
+                KeyCode::Char(',') => rotate_points(&mut points, 0.0, 0.0, TURN_STEP),
+                KeyCode::Char('.') => rotate_points(&mut points, 0.0, 0.0, -TURN_STEP),
+                KeyCode::Char(' ') => spinning = !spinning,
+                KeyCode::Char('r') => points = surface_points(&grid, &view),
+                _ => {}
+            },
+            ViewerEvent::Mouse(_) => {}
+        }
+    }
+}
+
+/// The grid as screen-space points (x across, z up, y into the screen),
+/// scaled to a cube filling most of the view and tipped to look down on
+/// it, then its centre last for `rotate_points` to turn about.
+pub(crate) fn surface_points(grid: &Grid, go: &GraphOptions) -> Vec<[f32; 3]> {
+    let (z_min, z_max) = grid.z_range().unwrap_or((0.0, 0.0));
+    let half = go.width.min(go.height) as f32 * 0.35;
+    let centre = [go.width as f32 / 2.0, go.height as f32 / 2.0, 0.0];
+    let unit = |v: f32, lo: f32, hi: f32| {
+        if hi > lo {
+            (v - lo) / (hi - lo) * 2.0 - 1.0
+        } else {
+            0.0
+        }
+    };
+    let (x_lo, x_hi) = (grid.xs[0], grid.xs[grid.xs.len() - 1]);
+    let (y_lo, y_hi) = (grid.ys[0], grid.ys[grid.ys.len() - 1]);
+
+    let mut points: Vec<[f32; 3]> = grid
+        .z
+        .iter()
+        .zip(&grid.ys)
+        .flat_map(|(row, &y)| {
+            row.iter().zip(&grid.xs).map(move |(&z, &x)| {
+                [
+                    centre[0] + unit(x, x_lo, x_hi) * half,
+                    centre[1] - unit(z, z_min, z_max) * half,
+                    unit(y, y_lo, y_hi) * half,
+                ]
+            })
+        })
+        .collect();
+    points.push(centre);
+    rotate_points(&mut points, START_TILT, 0.0, 0.0);
+    rotate_points(&mut points, 0.0, START_TURN, 0.0);
+    points
+}
+
+/// Joins each point to its right and lower neighbours on the grid,
+/// skipping holes.
+fn make_surface(go: &GraphOptions, points: &[[f32; 3]], columns: usize) -> String {
+    let mut matrix: CellMatrix = make_cell_matrix(go);
+    let size = (go.width as f32, go.height as f32);
+    let grid = &points[..points.len() - 1];
+
+    for (i, p) in grid.iter().enumerate() {
+        let right = (i % columns + 1 < columns).then(|| i + 1);
+        let below = Some(i + columns).filter(|&j| j < grid.len());
+        for q in [right, below].into_iter().flatten().map(|j| grid[j]) {
+            if let Some((a, b)) = clip_segment((p[0], p[1]), (q[0], q[1]), size) {
+                let cell = |v: f32| v.round() as usize;
+                draw_line(&mut matrix, cell(a.0), cell(a.1), cell(b.0), cell(b.1));
+            }
+        }
+    }
+
+    let braille_chars: CharMatrix = get_braille(go, &matrix);
+    braille_chars.iter().fold(String::new(), |mut acc, s| {
+        // Writing to String never fails, safe to ignore
+        let _ = writeln!(acc, "{}", s.iter().collect::<String>());
+        acc
+    })
+}
+
+/// Runs `:contour` and returns the graph it drew.
+pub(crate) fn contour(
+    l: &mut impl Logger,
+    go: &GraphOptions,
+    defs: &mut Definitions,
+) -> Option<(String, Plot)> {
+    let (eq, x_range, y_range) = get_surface_inputs(l)?;
+    let count = loop {
+        match get_optional_numerical_input::<usize>("levels (blank = 10): ", l) {
+            Some(n) if (1..=MAX_LEVELS).contains(&n) => break n,
+            Some(n) => l.eprint(&format!("levels `{n}` must be from 1 to {MAX_LEVELS}")),
+            None => break DEFAULT_LEVELS,
+        }
+    };
+
+    // About one sample per glyph is enough; lines between them are
+    // interpolated.
+    let resolution = ((go.width / 2).max(2), (go.height / 4).max(2));
+    let grid = match sample_grid(&eq, x_range, y_range, resolution, defs) {
+        Ok(grid) => grid,
+        Err(e) => {
+            report(&eq, &e, defs, l);
+            return None;
+        }
+    };
+    let Some((z_min, z_max)) = grid.z_range() else {
+        l.eprint(&format!(
+            "{} is undefined everywhere in that range",
+            eq.display
+        ));
+        return None;
+    };
+    let levels = contour_levels(z_min, z_max, count);
+    if levels.is_empty() {
+        l.eprint(&format!(
+            "{} is flat (z = {z_min}) in that range",
+            eq.display
+        ));
+        return None;
+    }
+
+    let step = match levels.as_slice() {
+        [a, b, ..] => b - a,
+        _ => 1.0,
+    };
+    let overlay = Overlay {
+        paths: levels
+            .iter()
+            .map(|&level| {
+                (
+                    format!("z = {}", format_tick(level, step)),
+                    contour_path(&grid, level),
+                )
+            })
+            .collect(),
+        markers: vec![],
+        dots: vec![],
+    };
+
+    // The y range is the one asked for, not fitted to the lines.
+    let go = GraphOptions {
+        y_min: y_range.0,
+        y_max: y_range.1,
+        y_fixed: true,
+        ..*go
+    };
+    let drawn = graph_with_overlay("", x_range.0, x_range.1, &go, defs, &overlay);
+    match drawn {
+        Ok((g, plot)) => {
+            l.print(&g);
+            let names: Vec<&str> = plot.equations.iter().map(|n| &n["z = ".len()..]).collect();
+            l.print(&format!("{}   levels: {}", eq.display, names.join(", ")));
+            Some((g, plot))
+        }
+        Err(e) => {
+            report(&eq, &e, defs, l);
+            None
+        }
+    }
+}
+
+/// Round level values strictly between the lowest and highest z.
+pub(crate) fn contour_levels(z_min: f32, z_max: f32, count: usize) -> Vec<f32> {
+    nice_ticks(z_min, z_max, count + 1)
+        .into_iter()
+        .filter(|&v| v > z_min && v < z_max)
+        .collect()
+}
+
+/// The curve where the grid crosses `level`, by marching squares. Each
+/// crossing of a grid square is a separate two-point piece; the pieces
+/// are joined into one path with NaN breaks, which drawing skips.
+pub(crate) fn contour_path(grid: &Grid, level: f32) -> Vec<Point> {
+    let mut path = Vec::new();
+    for j in 0..grid.ys.len().saturating_sub(1) {
+        for i in 0..grid.xs.len().saturating_sub(1) {
+            // Corners counter-clockwise from the bottom left.
+            let corners = [
+                (grid.xs[i], grid.ys[j], grid.z[j][i]),
+                (grid.xs[i + 1], grid.ys[j], grid.z[j][i + 1]),
+                (grid.xs[i + 1], grid.ys[j + 1], grid.z[j + 1][i + 1]),
+                (grid.xs[i], grid.ys[j + 1], grid.z[j + 1][i]),
+            ];
+            if corners.iter().any(|c| !c.2.is_finite()) {
+                continue;
+            }
+            for (a, b) in square_crossings(&corners, level) {
+                path.extend([a, b, Point::new(f32::NAN, f32::NAN)]);
+            }
+        }
+    }
+    path
+}
+
+/// The level's segments across one grid square.
+fn square_crossings(corners: &[(f32, f32, f32); 4], level: f32) -> Vec<(Point, Point)> {
+    // Where the level crosses the side from corner `a` to corner `b`.
+    let side = |a: usize, b: usize| {
+        let (ax, ay, az) = corners[a];
+        let (bx, by, bz) = corners[b];
+        let t = (level - az) / (bz - az);
+        Point::new(ax + t * (bx - ax), ay + t * (by - ay))
+    };
+    let above: Vec<bool> = corners.iter().map(|c| c.2 > level).collect();
+    let crossed: Vec<usize> = (0..4).filter(|&k| above[k] != above[(k + 1) % 4]).collect();
+
+    match crossed[..] {
+        [a, b] => vec![(side(a, (a + 1) % 4), side(b, (b + 1) % 4))],
+        [_, _, _, _] => {
+            // A saddle: the centre decides which corners the lines cut off.
+            let centre = corners.iter().map(|c| c.2).sum::<f32>() / 4.0;
+            let pairs = if (centre > level) == above[0] {
+                [(0, 1), (2, 3)]
+            } else {
+                [(3, 0), (1, 2)]
+            };
+            pairs
+                .iter()
+                .map(|&(a, b)| (side(a, (a + 1) % 4), side(b, (b + 1) % 4)))
+                .collect()
+        }
+        _ => vec![],
+    }
+}
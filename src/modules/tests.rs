@@ -4,15 +4,40 @@ mod rmr_tests {
     use rusty_maths::equation_analyzer::Definitions;
 
     use crate::modules::{
-        bindings::{self, handle_let, looks_like_binding, LetSource},
-        common::{GraphOptions, Point},
+        animate::{Clock, Playback, Timeline},
+        arrays::{evaluate_array, ArrayError, Value},
+        bindings::{self, handle_let, looks_like_binding, LetSource, Shadow},
+        commands::{format_eigenvalue, run_command, shift_range, zoom_range},
+        common::{GraphOptions, Point, Scale},
         error_render,
         evaluate::{evaluate, simple_evaluate},
-        graphing::graph,
+        export::strip_ansi,
+        fft::{
+            analyse, auto_segment_length, check_segment_length, dominant_frequencies, fft,
+            frame_length, parse_wav, shortest_segment, stft, to_db, Wav,
+        },
+        fit::{fit, Model},
+        graphing::{graph, graph_with_overlay, graph_with_plot, Overlay},
+        inputs::typed_matrix,
         logger::Logger,
+        matrix::{
+            determinant, eigenvalues, identity, inverse, least_squares, lu, multiply, qr, rank,
+            read_rows, solve, transpose,
+        },
+        mesh::{face_normal, parse_obj, Mesh, BUILT_INS},
+        png::{colour_key, crc32, encode_png, parse_size, render_png},
         repl::Repl,
         run::as_cli_tool,
-        string_maker::make_table_string,
+        scatter::read_pairs,
+        sliders::make_sliders,
+        spline::{read_csv_points, to_bezier, y_at, SplineKind},
+        stats::{
+            auto_bins, box_plot_overlay, csv_column, histogram, histogram_overlay, read_numbers,
+            Summary,
+        },
+        string_maker::{make_matrix_string, make_table_string},
+        surface::{contour_levels, contour_path, sample_grid, surface_points, SurfaceEquation},
+        wireframe::{facing_faces, load_mesh, render_mesh, BackEdges, Camera},
     };
 
     pub(crate) struct TestLogger {
@@ -53,8 +78,12 @@ mod rmr_tests {
         GraphOptions {
             y_max: 7.,
             y_min: -7.,
+            y_fixed: false,
             width: 240,
             height: 120,
+            x_scale: Scale::Linear,
+            y_scale: Scale::Linear,
+            equal_aspect: false,
         }
     }
 
@@ -363,8 +392,12 @@ mod rmr_tests {
         let go = GraphOptions {
             y_max: 7.,
             y_min: -7.,
+            y_fixed: false,
             width: 2,
             height: 1,
+            x_scale: Scale::Linear,
+            y_scale: Scale::Linear,
+            equal_aspect: false,
         };
         assert!(graph("y=x", -2.0, 2.0, &go, &empty_defs()).is_ok());
     }
@@ -490,6 +523,32 @@ mod rmr_tests {
         assert!(test_logger.val.contains("sin(0) = 0"));
     }
 
+    #[test]
+    fn fns_describes_array_bindings_and_functions() {
+        use crate::modules::commands::run_command;
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+
+        //Given
+        assert!(let_line(
+            "let m = [[1, 2], [3, 4]]",
+            &mut repl,
+            &mut test_logger
+        ));
+
+        //When
+        run_command("fns m", &mut test_logger, &mut repl);
+
+        //Then
+        assert!(test_logger.val.contains("2×2 matrix binding"));
+
+        //When
+        run_command("fns det", &mut test_logger, &mut repl);
+
+        //Then
+        assert!(test_logger.val.contains("determinant"));
+        assert!(!test_logger.error_val.contains("No symbol"));
+    }
+
     #[test]
     fn piped_user_function_evaluates() {
         let (mut repl, mut test_logger) = get_repl_and_logger();
@@ -671,7 +730,7 @@ mod rmr_tests {
         //Then
         assert!(&test_logger.val.is_empty());
         assert_eq!(
-            "Usage: rmr -g [equation] [x-min] [x-max]",
+            "Usage: rmr -g [equation] [x-min] [x-max] [--logx] [--logy] [--y y-min y-max] [-o file.txt] [--png file.png] [--size WxH]",
             test_logger.error_val
         );
     }
@@ -1066,217 +1125,2861 @@ mod rmr_tests {
     #[test]
     fn quadratic_bezier_renders() {
         //Given
+        use crate::modules::bezier_curve::{draw_bezier, Spline};
         let mut test_logger = get_test_logger();
         let go = get_graph_options();
-
-        let p1 = Point::new(10.0, 10.0);
-        let p2 = Point::new(50.0, 80.0);
-        let p3 = Point::new(90.0, 10.0);
+        let spline = Spline {
+            degree: 2,
+            points: vec![
+                Point::new(10.0, 10.0),
+                Point::new(50.0, 80.0),
+                Point::new(90.0, 10.0),
+            ],
+        };
 
         //When
-        use crate::modules::bezier_curve::quadratic_bezier;
-        quadratic_bezier(p1, p2, p3, &go, &mut test_logger);
+        let drawn = draw_bezier(&spline, "", &go, &empty_defs(), &mut test_logger);
 
         //Then - should produce output
-        assert!(!test_logger.val.is_empty());
+        assert!(drawn.is_some_and(|(g, _)| is_graph_string(&g)));
+        assert!(test_logger.val.starts_with("arc length: "));
         assert!(test_logger.error_val.is_empty());
     }
 
     #[test]
     fn cubic_bezier_renders() {
         //Given
+        use crate::modules::bezier_curve::{draw_bezier, Spline};
         let mut test_logger = get_test_logger();
         let go = get_graph_options();
-
-        let p1 = Point::new(10.0, 10.0);
-        let p2 = Point::new(30.0, 80.0);
-        let p3 = Point::new(70.0, 80.0);
-        let p4 = Point::new(90.0, 10.0);
+        let spline = Spline {
+            degree: 3,
+            points: vec![
+                Point::new(10.0, 10.0),
+                Point::new(30.0, 80.0),
+                Point::new(70.0, 80.0),
+                Point::new(90.0, 10.0),
+            ],
+        };
 
         //When
-        use crate::modules::bezier_curve::cubic_bezier;
-        cubic_bezier(p1, p2, p3, p4, &go, &mut test_logger);
+        let drawn = draw_bezier(&spline, "", &go, &empty_defs(), &mut test_logger);
 
         //Then
-        assert!(!test_logger.val.is_empty());
+        assert!(drawn.is_some());
         assert!(test_logger.error_val.is_empty());
     }
 
-    // ============================================================================
-    // Error Message Improvement Tests
-    // ============================================================================
+    #[test]
+    fn bezier_overlays_equations_in_graph_coordinates() {
+        //Given
+        use crate::modules::bezier_curve::{draw_bezier, Spline};
+        let mut test_logger = get_test_logger();
+        let go = get_graph_options();
+        let spline = Spline {
+            degree: 2,
+            points: vec![
+                Point::new(-2.0, 0.0),
+                Point::new(0.0, 4.0),
+                Point::new(2.0, 0.0),
+            ],
+        };
+
+        //When
+        let (_, plot) =
+            draw_bezier(&spline, "y = x^2", &go, &empty_defs(), &mut test_logger).unwrap();
+
+        //Then - the equation comes first, then the curve and its polygon
+        assert_eq!(plot.equations, ["y = x^2", "bezier", "control polygon"]);
+        assert_eq!(plot.curves.len(), 3);
+        assert!(plot.layout.x_range.0 < -2.0 && plot.layout.x_range.1 > 2.0);
+        assert!(plot.layout.y_range.1 >= 4.0);
+    }
 
     #[test]
-    fn invalid_command_shows_help_hint() {
+    fn bezier_reports_bad_overlay_equation() {
         //Given
-        let (mut repl, mut test_logger) = get_repl_and_logger();
+        use crate::modules::bezier_curve::{draw_bezier, Spline};
+        let mut test_logger = get_test_logger();
+        let go = get_graph_options();
+        let spline = Spline {
+            degree: 1,
+            points: vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)],
+        };
 
         //When
-        use crate::modules::commands::run_command;
-        run_command("invalidcmd", &mut test_logger, &mut repl);
+        let drawn = draw_bezier(&spline, "y = x +", &go, &empty_defs(), &mut test_logger);
 
         //Then
-        assert!(test_logger.error_val.contains("Type ':h' for help"));
+        assert!(drawn.is_none());
+        assert!(!test_logger.error_val.is_empty());
     }
 
     #[test]
-    fn error_messages_are_capitalized() {
+    fn bezier_arc_length_and_point_at_t() {
+        //Given - a straight 3-4-5 line as two linear segments
+        use crate::modules::bezier_curve::Spline;
+        let spline = Spline {
+            degree: 1,
+            points: vec![
+                Point::new(0.0, 0.0),
+                Point::new(3.0, 4.0),
+                Point::new(6.0, 8.0),
+            ],
+        };
+
+        //When
+        let length = spline.arc_length();
+        let quarter = spline.point_at(0.25);
+        let end = spline.point_at(1.0);
+
+        //Then
+        assert!((length - 10.0).abs() < 1e-3);
+        assert!((quarter.x - 1.5).abs() < 1e-5 && (quarter.y - 2.0).abs() < 1e-5);
+        assert_eq!(end, Point::new(6.0, 8.0));
+    }
+
+    #[test]
+    fn bezier_points_parse_from_pairs_or_editor_output() {
         //Given
-        let (mut repl, mut test_logger) = get_repl_and_logger();
+        use crate::modules::bezier_curve::parse_points;
 
-        //When - remove a binding that doesn't exist
-        bindings::undefine("zz", &mut repl, &mut test_logger);
+        //When
+        let typed = parse_points("0 0, 1 2, 3 -1");
+        let pasted = parse_points("[(0, 0), (1, 2), (3, -1)]");
+        let odd = parse_points("0 0, 1");
+        let single = parse_points("4 4");
 
-        //Then - error message should start with capital letter
-        assert!(test_logger.error_val.starts_with("Nothing"));
+        //Then
+        assert_eq!(typed, pasted);
+        assert_eq!(typed.map(|p| p.len()), Ok(3));
+        assert!(odd.is_err());
+        assert!(single.is_err());
     }
 
-    // ============================================================================
-    // Edge Case Tests
-    // ============================================================================
+    #[test]
+    fn clip_segment_keeps_the_part_inside() {
+        //Given
+        use crate::modules::graphing::clip_segment;
+
+        //When
+        let crossing = clip_segment((-10.0, 5.0), (30.0, 5.0), (20.0, 10.0));
+        let outside = clip_segment((-10.0, -5.0), (-1.0, -1.0), (20.0, 10.0));
+
+        //Then
+        assert_eq!(crossing, Some(((0.0, 5.0), (20.0, 5.0))));
+        assert_eq!(outside, None);
+    }
 
     #[test]
-    fn evaluate_with_extra_whitespace() {
+    fn de_casteljau_matches_endpoints_and_quadratic_midpoint() {
         //Given
-        let mut test_logger = get_test_logger();
+        use crate::modules::bezier_curve::de_casteljau;
+        let control = [
+            Point::new(0.0, 0.0),
+            Point::new(50.0, 100.0),
+            Point::new(100.0, 0.0),
+        ];
 
-        //When - expression with extra spaces
-        simple_evaluate("  2   +   2  ", &mut test_logger);
+        //When
+        let start = de_casteljau(&control, 0.0);
+        let mid = de_casteljau(&control, 0.5);
+        let end = de_casteljau(&control, 1.0);
+
+        //Then - (1-t)²p0 + 2t(1-t)p1 + t²p2 at t = 0.5
+        assert_eq!(start, Point::new(0.0, 0.0));
+        assert_eq!(mid, Point::new(50.0, 50.0));
+        assert_eq!(end, Point::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn de_casteljau_handles_higher_degrees() {
+        //Given - a degree 5 curve with every control point on y = 10
+        use crate::modules::bezier_curve::de_casteljau;
+        let control: Vec<Point> = (0..6).map(|i| Point::new(i as f32 * 10.0, 10.0)).collect();
+
+        //When
+        let p = de_casteljau(&control, 0.3);
+
+        //Then - evenly spaced collinear points give a linear parameterisation
+        assert!((p.x - 15.0).abs() < 1e-4);
+        assert_eq!(p.y, 10.0);
+    }
+
+    #[test]
+    fn spline_segments_share_endpoints() {
+        //Given
+        use crate::modules::bezier_curve::Spline;
+        let spline = Spline::spread(3, 2, (0.0, 200.0), (0.0, 100.0));
+
+        //When
+        let segments: Vec<&[Point]> = spline.segments().collect();
 
         //Then
-        assert_eq!(test_logger.val, "4");
-        assert!(test_logger.error_val.is_empty());
+        assert_eq!(spline.points.len(), 7);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].len(), 4);
+        assert_eq!(segments[0][3], segments[1][0]);
     }
 
     #[test]
-    fn graph_with_whitespace_in_equation() {
+    fn spline_adds_and_removes_segments() {
         //Given
-        let eq_str = "y = x ^ 2";
-        let x_min = -2f32;
-        let x_max = 2f32;
-        let go = get_graph_options();
+        use crate::modules::bezier_curve::Spline;
+        let mut spline = Spline::spread(2, 1, (0.0, 200.0), (0.0, 100.0));
 
         //When
-        let g = graph(eq_str, x_min, x_max, &go, &empty_defs());
+        spline.add_segment((0.0, 199.0), (0.0, 99.0));
+        let added = spline.segment_count();
+        spline.remove_segment();
+        spline.remove_segment();
 
-        //Then - should handle whitespace
-        assert!(g.is_ok());
+        //Then - the last segment is never removed
+        assert_eq!(added, 2);
+        assert_eq!(spline.segment_count(), 1);
+        assert_eq!(spline.points.len(), 3);
+        assert!(spline.points.iter().all(|p| p.x <= 199.0 && p.y <= 99.0));
     }
 
     #[test]
-    fn division_by_zero_in_evaluation() {
+    fn spline_prints_copyable_points() {
         //Given
-        let mut test_logger = get_test_logger();
+        use crate::modules::bezier_curve::Spline;
+        let spline = Spline {
+            degree: 1,
+            points: vec![Point::new(1.0, 2.0), Point::new(3.5, 4.0)],
+        };
 
         //When
-        simple_evaluate("1/0", &mut test_logger);
+        let printed = spline.format_points();
 
-        //Then - should either error or return inf (depending on rusty-maths behavior)
-        // We just verify it doesn't panic
-        assert!(!test_logger.val.is_empty() || !test_logger.error_val.is_empty());
+        //Then
+        assert_eq!(printed, "[(1, 2), (3.5, 4)]");
     }
 
     #[test]
-    fn very_large_numbers() {
+    fn editor_frame_graphs_the_spline_in_its_window() {
         //Given
-        let mut test_logger = get_test_logger();
+        use crate::modules::{bezier_curve::Spline, bezier_editor::editor_frame};
+        let window = ((-10.0, 10.0), (-5.0, 5.0));
+        let spline = Spline::spread(4, 3, window.0, window.1);
 
         //When
-        simple_evaluate("999999*999999", &mut test_logger);
+        let (frame, plot) = editor_frame(&spline, 0, window, &get_graph_options()).unwrap();
 
-        //Then - should handle large numbers
-        assert!(!test_logger.val.is_empty());
-        assert!(test_logger.error_val.is_empty());
+        //Then - graph coordinates with y up, the window kept exactly
+        assert_eq!(spline.points[0], Point::new(-8.0, -2.0));
+        assert_eq!(spline.points[1].y, 2.0);
+        assert_eq!(plot.layout.x_range, window.0);
+        assert_eq!(plot.layout.y_range, window.1);
+        assert_eq!(
+            plot.equations,
+            vec!["bezier", "control polygon", "selected"]
+        );
+        assert_eq!(plot.points, spline.points);
+        assert!(frame
+            .chars()
+            .any(|c| ('\u{2801}'..='\u{28FF}').contains(&c)));
     }
 
     #[test]
-    fn very_small_numbers() {
+    fn flattened_bezier_stays_within_tolerance() {
         //Given
-        let mut test_logger = get_test_logger();
+        use crate::modules::bezier_curve::{de_casteljau, flatten};
+        let control = [
+            Point::new(0.0, 0.0),
+            Point::new(40.0, 300.0),
+            Point::new(260.0, -200.0),
+            Point::new(300.0, 100.0),
+        ];
 
         //When
-        simple_evaluate("0.000001*0.000001", &mut test_logger);
+        let line = flatten(&control, 0.25);
+
+        //Then - every point on the true curve is near some line segment
+        let near = |p: &Point| {
+            line.windows(2).any(|w| {
+                let (dx, dy) = (w[1].x - w[0].x, w[1].y - w[0].y);
+                let t = (((p.x - w[0].x) * dx + (p.y - w[0].y) * dy) / (dx * dx + dy * dy))
+                    .clamp(0.0, 1.0);
+                (p.x - w[0].x - t * dx).hypot(p.y - w[0].y - t * dy) <= 0.26
+            })
+        };
+        assert!((0..=2000).all(|i| near(&de_casteljau(&control, i as f32 / 2000.0))));
+        assert_eq!(line.first(), control.first());
+        assert_eq!(line.last(), control.last());
+    }
+
+    #[test]
+    fn flattening_spends_few_points_on_small_curves() {
+        //Given
+        use crate::modules::bezier_curve::flatten;
+        let tiny = [
+            Point::new(0.0, 0.0),
+            Point::new(0.5, 1.0),
+            Point::new(1.0, 0.0),
+        ];
+        let straight = [
+            Point::new(0.0, 0.0),
+            Point::new(500.0, 500.0),
+            Point::new(1000.0, 1000.0),
+        ];
+
+        //When
+        let tiny_line = flatten(&tiny, 0.25);
+        let straight_line = flatten(&straight, 0.25);
 
         //Then
-        assert!(!test_logger.val.is_empty());
-        assert!(test_logger.error_val.is_empty());
+        assert!(tiny_line.len() <= 3);
+        assert_eq!(straight_line.len(), 2);
     }
 
     #[test]
-    fn negative_numbers_in_expressions() {
+    fn bezier_graph_uses_few_points_for_simple_curves() {
         //Given
+        use crate::modules::bezier_curve::{draw_bezier, Spline};
         let mut test_logger = get_test_logger();
+        let go = get_graph_options();
+        let spline = Spline {
+            degree: 2,
+            points: vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 1.0),
+                Point::new(2.0, 0.0),
+            ],
+        };
 
         //When
-        simple_evaluate("-5*-3", &mut test_logger);
+        let (_, plot) = draw_bezier(&spline, "", &go, &empty_defs(), &mut test_logger).unwrap();
 
-        //Then - should be 15
-        assert_eq!(test_logger.val, "15");
-        assert!(test_logger.error_val.is_empty());
+        //Then - far fewer than one point per thousandth of t
+        let curve = &plot.curves[0];
+        assert!(curve.len() > 8 && curve.len() < 200, "{}", curve.len());
     }
 
     #[test]
-    fn multiple_bindings_in_expression() {
+    fn editor_points_stay_in_the_window() {
         //Given
-        let (mut repl, mut test_logger) = get_repl_and_logger();
-
-        let_line("let a = 5", &mut repl, &mut test_logger);
-        let_line("let b = 3", &mut repl, &mut test_logger);
+        use crate::modules::bezier_editor::clamp_point;
 
         //When
-        evaluate("a*b+a", &mut repl, &mut test_logger);
+        let p = clamp_point(&Point::new(-5.0, 500.0), ((0.0, 100.0), (-1.0, 50.0)));
 
-        //Then - 5*3+5 = 20
-        assert_eq!(test_logger.val, "20");
-        assert!(test_logger.error_val.is_empty());
+        //Then
+        assert_eq!(p, Point::new(0.0, 50.0));
     }
 
     #[test]
-    fn ans_with_multiple_operations() {
+    fn cube_turns_full_circle_back_to_start() {
         //Given
-        let (mut repl, mut test_logger) = get_repl_and_logger();
+        use crate::modules::cube::{cube_points, rotate_points};
+        let start = cube_points(&get_graph_options());
+        let mut points = start.clone();
+
+        //When - sixty 6° turns about each axis in turn
+        for turn in [(6.0, 0.0, 0.0), (0.0, 6.0, 0.0), (0.0, 0.0, 6.0)] {
+            for _ in 0..60 {
+                rotate_points(&mut points, turn.0, turn.1, turn.2);
+            }
+        }
 
-        //When - chain of operations using ans
-        evaluate("10", &mut repl, &mut test_logger);
-        assert_eq!(test_logger.val, "10");
+        //Then
+        for (p, q) in points.iter().zip(&start) {
+            assert!(p.iter().zip(q).all(|(a, b)| (a - b).abs() < 0.05));
+        }
+    }
 
-        evaluate("ans*2", &mut repl, &mut test_logger);
-        assert_eq!(test_logger.val, "20");
+    #[test]
+    fn cube_zoom_scales_about_its_center() {
+        //Given
+        use crate::modules::cube::{cube_points, scale_points};
+        let start = cube_points(&get_graph_options());
+        let mut points = start.clone();
 
-        evaluate("ans+5", &mut repl, &mut test_logger);
+        //When
+        scale_points(&mut points, 2.0);
+        let center = points[8];
+        let edge = points[1][1] - points[0][1];
+        scale_points(&mut points, 0.5);
 
-        //Then - should be 25
-        assert_eq!(test_logger.val, "25");
-        assert!(test_logger.error_val.is_empty());
+        //Then
+        assert_eq!(center, start[8]);
+        assert_eq!(edge, 2.0 * (start[1][1] - start[0][1]));
+        assert_eq!(points, start);
+    }
+
+    // ============================================================================
+    // Wireframe Tests
+    // ============================================================================
+
+    fn lit_dots(frame: &str) -> u32 {
+        frame
+            .chars()
+            .filter(|c| ('\u{2800}'..='\u{28FF}').contains(c))
+            .map(|c| (c as u32 - 0x2800).count_ones())
+            .sum()
+    }
+
+    #[test]
+    fn built_in_meshes_are_closed_solids() {
+        for (name, v, e, f) in [
+            ("tetrahedron", 4, 6, 4),
+            ("cube", 8, 12, 6),
+            ("octahedron", 6, 12, 8),
+            ("icosahedron", 12, 30, 20),
+        ] {
+            //Given
+            let mesh = Mesh::built_in(name).unwrap();
+
+            //Then
+            assert_eq!(
+                (mesh.vertices.len(), mesh.edges.len(), mesh.faces.len()),
+                (v, e, f),
+                "{name}"
+            );
+        }
+        for name in BUILT_INS {
+            let mesh = Mesh::built_in(name).unwrap();
+            assert!(mesh.edges.iter().all(|e| e.faces.len() == 2), "{name}");
+            let radius = mesh
+                .vertices
+                .iter()
+                .map(|v| (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt())
+                .fold(0.0, f32::max);
+            assert!((radius - 1.0).abs() < 1e-4, "{name}");
+        }
     }
 
     #[test]
-    fn empty_equation_in_graph() {
+    fn built_in_faces_point_outwards() {
+        for name in ["tetrahedron", "cube", "octahedron", "icosahedron", "sphere"] {
+            //Given
+            let mesh = Mesh::built_in(name).unwrap();
+
+            //Then - a convex solid's outward normals point away from its centre
+            for face in &mesh.faces {
+                let n = face_normal(face, &mesh.vertices);
+                let v = mesh.vertices[face[0]];
+                assert!(n[0] * v[0] + n[1] * v[1] + n[2] * v[2] > 0.0, "{name}");
+            }
+        }
+    }
+
+    #[test]
+    fn camera_sees_only_the_near_faces() {
         //Given
-        let eq_str = "";
-        let x_min = -1f32;
-        let x_max = 1f32;
-        let go = get_graph_options();
+        let cube = Mesh::built_in("cube").unwrap();
+        let head_on = Camera {
+            yaw: 0.0,
+            pitch: 0.0,
+            ..Camera::default()
+        };
 
         //When
-        let g = graph(eq_str, x_min, x_max, &go, &empty_defs());
+        let facing = |camera: &Camera| {
+            let view: Vec<[f32; 3]> = cube
+                .vertices
+                .iter()
+                .map(|&v| camera.view_point(v))
+                .collect();
+            facing_faces(&cube, &view)
+                .into_iter()
+                .filter(|&f| f)
+                .count()
+        };
 
-        //Then - should return an error
-        assert!(g.is_err());
+        //Then
+        assert_eq!(facing(&head_on), 1);
+        assert_eq!(facing(&Camera::default()), 3);
     }
 
     #[test]
-    fn graph_with_invalid_equation() {
+    fn back_edges_can_be_hidden_dashed_or_shown() {
         //Given
-        let eq_str = "y=invalid";
-        let x_min = -1f32;
-        let x_max = 1f32;
+        let mesh = Mesh::built_in("icosahedron").unwrap();
         let go = get_graph_options();
+        let camera = Camera::default();
 
         //When
-        let g = graph(eq_str, x_min, x_max, &go, &empty_defs());
+        let hidden = lit_dots(&render_mesh(&mesh, &camera, BackEdges::Hidden, &go));
+        let dashed = lit_dots(&render_mesh(&mesh, &camera, BackEdges::Dashed, &go));
+        let shown = lit_dots(&render_mesh(&mesh, &camera, BackEdges::Shown, &go));
 
-        //Then - should return an error
-        assert!(g.is_err());
+        //Then
+        assert!(hidden > 0);
+        assert!(
+            hidden < dashed && dashed < shown,
+            "{hidden} {dashed} {shown}"
+        );
+    }
+
+    #[test]
+    fn camera_inside_the_mesh_still_renders() {
+        //Given
+        let mesh = Mesh::built_in("torus").unwrap();
+        let camera = Camera {
+            distance: 0.3,
+            ..Camera::default()
+        };
+
+        //When
+        let frame = render_mesh(&mesh, &camera, BackEdges::Shown, &get_graph_options());
+
+        //Then
+        assert!(lit_dots(&frame) > 0);
+    }
+
+    #[test]
+    fn obj_files_load_faces_and_lines() {
+        //Given
+        let obj = "# a square and a tail\n\
+                   v 0 0 0\nv 2 0 0\nv 2 2 0\nv 0 2 0\nv 4 4 4\n\
+                   vn 0 0 1\n\
+                   f 1//1 2//1 3//1 -2//1\n\
+                   l 3 5\n";
+
+        //When
+        let mesh = parse_obj(obj).unwrap();
+
+        //Then - four face sides plus one loose line
+        assert_eq!(mesh.faces, vec![vec![0, 1, 2, 3]]);
+        assert_eq!(mesh.edges.len(), 5);
+        assert_eq!(mesh.edges.iter().filter(|e| e.faces.is_empty()).count(), 1);
+    }
+
+    #[test]
+    fn obj_errors_name_the_line() {
+        assert_eq!(
+            parse_obj("v 0 0 0\nv 1 0 0\nf 1 2 9\n").unwrap_err(),
+            "line 3: index out of range: 'f 1 2 9'"
+        );
+        assert_eq!(
+            parse_obj("v 0 0\n").unwrap_err(),
+            "line 1: a vertex needs x, y and z: 'v 0 0'"
+        );
+        assert!(parse_obj("# nothing\n").is_err());
+        assert!(load_mesh("dodecahedron")
+            .unwrap_err()
+            .starts_with("Unknown mesh"));
+    }
+
+    // ============================================================================
+    // Spline Tests
+    // ============================================================================
+
+    fn spline_points() -> Vec<Point> {
+        vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(2.0, 0.0),
+            Point::new(4.0, 3.0),
+        ]
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_every_point() {
+        //Given
+        let points = spline_points();
+
+        //When
+        let curve = to_bezier(SplineKind::CatmullRom, &points).unwrap();
+
+        //Then
+        assert_eq!(curve.segment_count(), 3);
+        for p in &points {
+            let y = y_at(&curve, p.x).unwrap();
+            assert!((y - p.y).abs() < 1e-3, "{} at x = {}", y, p.x);
+        }
+    }
+
+    #[test]
+    fn natural_spline_matches_hand_computed_value() {
+        //Given - M1 = -3 with zero curvature at both ends
+        let points = vec![
+            Point::new(2.0, 0.0),
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 1.0),
+        ];
+
+        //When
+        let curve = to_bezier(SplineKind::Natural, &points).unwrap();
+
+        //Then - points are sorted by x first
+        assert!((y_at(&curve, 0.5).unwrap() - 0.6875).abs() < 1e-4);
+        assert!((y_at(&curve, 1.0).unwrap() - 1.0).abs() < 1e-4);
+        assert_eq!(y_at(&curve, 3.0), None);
+    }
+
+    #[test]
+    fn natural_spline_rejects_repeated_x() {
+        //Given
+        let points = vec![Point::new(1.0, 0.0), Point::new(1.0, 2.0)];
+
+        //When
+        let curve = to_bezier(SplineKind::Natural, &points);
+
+        //Then
+        assert!(curve.unwrap_err().contains("x = 1 appears twice"));
+    }
+
+    #[test]
+    fn b_spline_is_pinned_at_the_ends_and_smooths_the_middle() {
+        //Given
+        let points = vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 6.0),
+            Point::new(2.0, 0.0),
+        ];
+
+        //When
+        let curve = to_bezier(SplineKind::BSpline, &points).unwrap();
+
+        //Then
+        assert_eq!(curve.points.first(), points.first());
+        assert_eq!(curve.points.last(), points.last());
+        let peak = y_at(&curve, 1.0).unwrap();
+        assert!(peak > 2.0 && peak < 6.0, "{peak}");
+    }
+
+    #[test]
+    fn spline_kinds_parse() {
+        assert_eq!(SplineKind::parse(""), Some(SplineKind::CatmullRom));
+        assert_eq!(SplineKind::parse("b-spline"), Some(SplineKind::BSpline));
+        assert_eq!(SplineKind::parse("natural"), Some(SplineKind::Natural));
+        assert_eq!(SplineKind::parse("hermite"), None);
+    }
+
+    #[test]
+    fn csv_points_skip_header_and_blank_lines() {
+        //Given
+        let text = "x,y\n0, 1\n\n2,3,extra\n";
+        let broken = "0,1\n2,oops\n";
+
+        //When
+        let points = read_csv_points(text);
+        let error = read_csv_points(broken);
+
+        //Then
+        assert_eq!(points, Ok(vec![Point::new(0.0, 1.0), Point::new(2.0, 3.0)]));
+        assert_eq!(
+            error,
+            Err("line 2: expected x,y but found '2,oops'".to_string())
+        );
+    }
+
+    // ============================================================================
+    // Matrix Tests
+    // ============================================================================
+
+    fn assert_close(a: &[Vec<f64>], b: &[Vec<f64>]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().flatten().zip(b.iter().flatten()) {
+            assert!((x - y).abs() < 1e-9, "{a:?} != {b:?}");
+        }
+    }
+
+    #[test]
+    fn typed_matrices_are_array_expressions() {
+        //Given
+        let (mut repl, mut logger) = get_repl_and_logger();
+        assert!(let_line("let m = [[1, 2], [3, 4]]", &mut repl, &mut logger));
+
+        //When
+        let literal = typed_matrix("[[2, 1], [1, 3 * 2]]", &repl);
+        let bound = typed_matrix("m", &repl);
+        let row = typed_matrix("[1, 2, 3]", &repl);
+        let ragged = typed_matrix("[[1, 2], [3]]", &repl);
+        let number = typed_matrix("det(m)", &repl);
+
+        //Then
+        assert_eq!(literal, Ok(vec![vec![2.0, 1.0], vec![1.0, 6.0]]));
+        assert_eq!(bound, Ok(vec![vec![1.0, 2.0], vec![3.0, 4.0]]));
+        assert_eq!(row, Ok(vec![vec![1.0, 2.0, 3.0]]));
+        assert!(ragged.unwrap_err().starts_with("[[1, 2], [3]]\n"));
+        assert_eq!(
+            number,
+            Err("'det(m)' is a number, not a matrix".to_string())
+        );
+    }
+
+    #[test]
+    fn read_rows_reads_pasted_rows_and_csv() {
+        //When
+        let pasted = read_rows("1 2 3\n4, 5, 6");
+        let csv = read_rows("a,b\n1,2\n\n3,4\n");
+
+        //Then
+        assert_eq!(pasted, Ok(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]));
+        assert_eq!(csv, Ok(vec![vec![1.0, 2.0], vec![3.0, 4.0]]));
+    }
+
+    #[test]
+    fn read_rows_reports_the_line_at_fault() {
+        //When
+        let ragged = read_rows("x,y\n1,2\n3,4,5");
+        let bad = read_rows("1 2\n3 q");
+        let typo = read_rows("1 2 w");
+        let empty = read_rows("x,y\n\n");
+
+        //Then
+        assert_eq!(
+            ragged,
+            Err("line 3 has 3 numbers but line 2 has 2".to_string())
+        );
+        assert_eq!(bad, Err("line 2: 'q' is not a number".to_string()));
+        assert_eq!(typo, Err("line 1: 'w' is not a number".to_string()));
+        assert_eq!(empty, Err("The matrix is empty".to_string()));
+    }
+
+    #[test]
+    fn multiply_and_transpose() {
+        //Given
+        let a = vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]];
+
+        //When
+        let product = multiply(&a, &transpose(&a)).unwrap();
+        let mismatch = multiply(&a, &a);
+
+        //Then
+        assert_eq!(product, vec![vec![14.0, 32.0], vec![32.0, 77.0]]);
+        assert!(mismatch.is_err());
+    }
+
+    #[test]
+    fn determinant_inverse_and_rank() {
+        //Given
+        let a = vec![
+            vec![0.0, 2.0, 1.0],
+            vec![1.0, 1.0, 0.0],
+            vec![2.0, 0.0, 3.0],
+        ];
+        let singular = vec![vec![1.0, 2.0], vec![2.0, 4.0]];
+
+        //When
+        let det = determinant(&a).unwrap();
+        let inv = inverse(&a).unwrap();
+
+        //Then
+        assert!((det + 8.0).abs() < 1e-12);
+        assert_close(&multiply(&a, &inv).unwrap(), &identity(3));
+        assert_eq!(rank(&a), 3);
+        assert_eq!(rank(&singular), 1);
+        assert_eq!(determinant(&singular).unwrap(), 0.0);
+        assert!(inverse(&singular).is_err());
+    }
+
+    #[test]
+    fn solve_finds_x() {
+        //Given
+        let a = vec![vec![2.0, 1.0], vec![1.0, 3.0]];
+
+        //When
+        let x = solve(&a, &[3.0, 5.0]).unwrap();
+
+        //Then
+        assert_close(&[x], &[vec![0.8, 1.4]]);
+        assert!(solve(&[vec![1.0, 1.0], vec![1.0, 1.0]].to_vec(), &[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn lu_and_qr_rebuild_the_matrix() {
+        //Given
+        let a = vec![
+            vec![0.0, 2.0, 1.0],
+            vec![1.0, 1.0, 0.0],
+            vec![2.0, 0.0, 3.0],
+        ];
+
+        //When
+        let (p, l, u) = lu(&a).unwrap();
+        let (q, r) = qr(&a);
+
+        //Then
+        assert_close(&multiply(&p, &a).unwrap(), &multiply(&l, &u).unwrap());
+        assert!((0..3).all(|i| l[i][i] == 1.0 && (i + 1..3).all(|j| l[i][j] == 0.0)));
+        assert!((0..3).all(|i| (0..i).all(|j| u[i][j] == 0.0 && r[i][j] == 0.0)));
+        assert_close(&multiply(&q, &r).unwrap(), &a);
+        assert_close(&multiply(&transpose(&q), &q).unwrap(), &identity(3));
+    }
+
+    #[test]
+    fn eigenvalues_real_and_complex() {
+        //Given
+        let symmetric = vec![
+            vec![2.0, 1.0, 0.0],
+            vec![1.0, 2.0, 1.0],
+            vec![0.0, 1.0, 2.0],
+        ];
+        let rotation = vec![vec![0.0, -1.0], vec![1.0, 0.0]];
+
+        //When
+        let real = eigenvalues(&symmetric).unwrap();
+        let complex = eigenvalues(&rotation).unwrap();
+
+        //Then
+        let root2 = 2.0_f64.sqrt();
+        for (found, expected) in real.iter().zip([2.0 + root2, 2.0, 2.0 - root2]) {
+            assert!((found.0 - expected).abs() < 1e-9 && found.1 == 0.0);
+        }
+        assert_eq!(complex, vec![(0.0, 1.0), (0.0, -1.0)]);
+        assert_eq!(format_eigenvalue(complex[1], 2), "0 - 1i");
+        assert_eq!(eigenvalues(&identity(4)).unwrap(), vec![(1.0, 0.0); 4]);
+    }
+
+    #[test]
+    fn matrix_string_aligns_columns() {
+        //Given
+        let m = vec![vec![1.0, -2.5], vec![10.0, 0.001]];
+
+        //When
+        let s = make_matrix_string(&m, 2);
+
+        //Then
+        assert_eq!(
+            s,
+            "┌           ┐\n│  1  -2.50 │\n│ 10      0 │\n└           ┘"
+        );
+    }
+
+    // ============================================================================
+    // Vector and Matrix Value Tests
+    // ============================================================================
+
+    #[test]
+    fn array_literals_evaluate_at_the_prompt() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+
+        //When
+        evaluate("[1,2,3] . [4,5,6]", &mut repl, &mut test_logger);
+        let dot = test_logger.val.clone();
+        evaluate("det([[1, 2], [3, 4]])", &mut repl, &mut test_logger);
+        let det = test_logger.val.clone();
+        evaluate("[[2, 0], [0, 3]] * [1, 1]", &mut repl, &mut test_logger);
+
+        //Then
+        assert_eq!(dot, "32");
+        assert_eq!(det, "-2");
+        assert_eq!(repl.defs.value("ans"), Some(-2.0));
+        assert_eq!(test_logger.val, "┌      ┐\n│ 2  3 │\n└      ┘");
+        assert!(test_logger.error_val.is_empty());
+    }
+
+    #[test]
+    fn array_bindings_work_like_values() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+        let_line("let k = 2", &mut repl, &mut test_logger);
+
+        //When
+        let bound = let_line("let v = [1, k, 3]", &mut repl, &mut test_logger);
+        let notice = test_logger.val.clone();
+        evaluate("sqrt(v . v) + norm(v)", &mut repl, &mut test_logger);
+
+        //Then
+        assert!(bound);
+        assert_eq!(notice, "v = [1, 2, 3]");
+        assert_eq!(test_logger.val, "7.48");
+        assert_eq!(repl.defs.value("v"), None);
+    }
+
+    #[test]
+    fn scalar_and_array_bindings_replace_each_other() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+        let_line("let v = 4", &mut repl, &mut test_logger);
+
+        //When
+        let_line("let v = [1, 2]", &mut repl, &mut test_logger);
+        let as_array = test_logger.val.clone();
+        let_line("let v = [1, 2] . [3, 4]", &mut repl, &mut test_logger);
+
+        //Then
+        assert_eq!(as_array, "v = [1, 2]  (was 4)");
+        assert_eq!(test_logger.val, "v = 11  (was [1, 2])");
+        assert_eq!(repl.defs.value("v"), Some(11.0));
+        assert!(repl.arrays.is_empty());
+    }
+
+    #[test]
+    fn array_type_errors_caret_the_operator() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+
+        //When
+        evaluate("[1, 2] + [1, 2, 3]", &mut repl, &mut test_logger);
+        let at_prompt = strip_ansi(&test_logger.error_val);
+        let_line("let w = det([1, 2])", &mut repl, &mut test_logger);
+        let in_let = strip_ansi(&test_logger.error_val);
+
+        //Then
+        assert_eq!(
+            at_prompt,
+            "          ^\nCan't add a 2-vector and a 3-vector"
+        );
+        assert_eq!(
+            in_let,
+            "               ^^^^^^\ndet needs a matrix, not a 2-vector"
+        );
+    }
+
+    #[test]
+    fn array_literal_errors() {
+        //Given
+        let defs = Definitions::new();
+
+        //When
+        let ragged = evaluate_array("[[1, 2], [3]]", &defs, &[]);
+        let unknown = evaluate_array("[1, nope]", &defs, &[]);
+        let unclosed = evaluate_array("[1, 2", &defs, &[]);
+
+        //Then
+        match ragged {
+            Err(ArrayError::Type { message, span }) => {
+                assert_eq!(message, "Row 2 has 1 entries but row 1 has 2");
+                assert_eq!(span, (9, 12));
+            }
+            other => panic!("{other:?}"),
+        }
+        match unknown {
+            Err(ArrayError::Equation(e)) => assert_eq!(e.span.map(|s| s.start), Some(4)),
+            other => panic!("{other:?}"),
+        }
+        assert!(matches!(
+            unclosed,
+            Err(ArrayError::Type { span: (0, 1), .. })
+        ));
+    }
+
+    #[test]
+    fn array_entries_and_pieces_follow_calculator_syntax() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+        let_line("let g(x) = x + 1", &mut repl, &mut test_logger);
+        let_line("let m = [[2, 1], [1, 3]]", &mut repl, &mut test_logger);
+
+        //When
+        let entries = evaluate_array("[2pi, 4 |> g, 2(1 + 1)]", &repl.defs, &repl.arrays);
+        let piped = evaluate_array("det(m) |> g", &repl.defs, &repl.arrays);
+        let chained = evaluate_array("[3, 4] |> norm |> g", &repl.defs, &repl.arrays);
+        let implicit = evaluate_array("2m - m", &repl.defs, &repl.arrays);
+
+        //Then
+        match entries {
+            Ok(Value::Vector(v)) => {
+                assert!((v[0] - std::f64::consts::TAU).abs() < 1e-5);
+                assert_eq!(&v[1..], &[5.0, 4.0]);
+            }
+            other => panic!("{other:?}"),
+        }
+        assert_eq!(piped.unwrap(), Value::Number(6.0));
+        assert_eq!(chained.unwrap(), Value::Number(6.0));
+        assert_eq!(
+            implicit.unwrap(),
+            Value::Matrix(vec![vec![2.0, 1.0], vec![1.0, 3.0]])
+        );
+    }
+
+    #[test]
+    fn array_syntax_the_calculator_lacks_is_rejected() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+        let_line("let m = [[2, 1], [1, 3]]", &mut repl, &mut test_logger);
+        let rejected = |line: &str| match evaluate_array(line, &repl.defs, &repl.arrays) {
+            Err(ArrayError::Type { message, span }) => (message, span),
+            other => panic!("{line}: {other:?}"),
+        };
+
+        //Then rows need commas between them, implicit multiplication by
+        //an array needs them side by side, and pipes need a function
+        assert_eq!(rejected("2 m"), ("Unexpected 'm'".to_string(), (2, 3)));
+        assert_eq!(
+            rejected("[[1, 2] [3, 4]]"),
+            ("Expected ',' or ']'".to_string(), (8, 9))
+        );
+        assert_eq!(
+            rejected("m |> 2"),
+            ("Expected a function name after '|>'".to_string(), (2, 4))
+        );
+        assert_eq!(
+            rejected("m |> sin"),
+            ("sin takes numbers, not a 2×2 matrix".to_string(), (0, 2))
+        );
+    }
+
+    #[test]
+    fn array_bindings_need_finite_entries() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+
+        //When
+        let vector = let_line("let v = [1, 1/0]", &mut repl, &mut test_logger);
+        let vector_error = test_logger.error_val.clone();
+        let matrix = let_line("let m = [[1, 2], [0/0, 4]]", &mut repl, &mut test_logger);
+
+        //Then
+        assert!(!vector && !matrix);
+        assert_eq!(
+            vector_error,
+            "Cannot bind 'v' to [1, inf] — every entry must be a finite number"
+        );
+        assert!(test_logger
+            .error_val
+            .starts_with("Cannot bind 'm' to [[1, 2], [NaN, 4]]"));
+        assert!(repl.arrays.is_empty());
+    }
+
+    #[test]
+    fn array_bindings_persist_and_list() {
+        let path = std::env::temp_dir().join(format!("rmr_arrays_test_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+        repl.bindings_path = Some(path.clone());
+        let_line("let m = [[1, 0.5], [0, 1]]", &mut repl, &mut test_logger);
+
+        //When
+        let saved = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut repl2 = get_repl();
+        repl2.bindings_path = Some(path.clone());
+        bindings::load(&mut repl2, &mut test_logger);
+        let _ = std::fs::remove_file(&path);
+
+        //Then
+        assert_eq!(saved, "let m = [[1, 0.5], [0, 1]]\n");
+        assert_eq!(repl2.arrays, repl.arrays);
+        run_command("undef m", &mut test_logger, &mut repl2);
+        assert_eq!(test_logger.val, "m removed");
+        assert!(repl2.arrays.is_empty());
+    }
+
+    // ============================================================================
+    // Surface Tests
+    // ============================================================================
+
+    #[test]
+    fn surface_equation_swaps_y_for_a_free_letter() {
+        //Given
+        let inputs = ["z = x*y + sin(y)", "x^2 - y^2", "v*y + w"];
+
+        //When
+        let parsed: Vec<SurfaceEquation> = inputs
+            .iter()
+            .map(|s| SurfaceEquation::parse(s).unwrap())
+            .collect();
+
+        //Then - same length, so error spans still point at the right text
+        assert_eq!(parsed[0].display, "z = x*y + sin(y)");
+        assert_eq!(parsed[0].calc, "y = x*v + sin(v)");
+        assert_eq!(parsed[1].display, "z = x^2 - y^2");
+        assert_eq!(parsed[1].calc, "y = x^2 - v^2");
+        assert_eq!(parsed[2].stand_in, "u");
+        assert_eq!(parsed[2].calc, "y = v*u + w");
+    }
+
+    #[test]
+    fn sample_grid_uses_bindings_and_restores_the_stand_in() {
+        //Given
+        let mut repl = get_repl();
+        let mut test_logger = get_test_logger();
+        let_line("let a = 2", &mut repl, &mut test_logger);
+        let_line("let v = 7", &mut repl, &mut test_logger);
+        let eq = SurfaceEquation::parse("a*x + y").unwrap();
+
+        //When
+        let grid = sample_grid(&eq, (0.0, 1.0), (-1.0, 1.0), (2, 3), &mut repl.defs).unwrap();
+
+        //Then
+        assert_eq!(grid.xs, vec![0.0, 1.0]);
+        assert_eq!(grid.ys, vec![-1.0, 0.0, 1.0]);
+        assert_eq!(
+            grid.z,
+            vec![vec![-1.0, 1.0], vec![0.0, 2.0], vec![1.0, 3.0]]
+        );
+        assert_eq!(repl.defs.value("v"), Some(7.0));
+    }
+
+    #[test]
+    fn sample_grid_reports_a_typo_once() {
+        //Given
+        let mut defs = Definitions::new();
+        let eq = SurfaceEquation::parse("x + (y").unwrap();
+
+        //When
+        let result = sample_grid(&eq, (0.0, 1.0), (0.0, 1.0), (4, 4), &mut defs);
+
+        //Then
+        assert!(result.is_err());
+        assert_eq!(defs.value(eq.stand_in), None);
+    }
+
+    #[test]
+    fn contours_of_a_bowl_are_circles() {
+        //Given
+        let mut defs = Definitions::new();
+        let eq = SurfaceEquation::parse("z = x^2 + y^2").unwrap();
+        let grid = sample_grid(&eq, (-3.0, 3.0), (-3.0, 3.0), (41, 41), &mut defs).unwrap();
+
+        //When
+        let path = contour_path(&grid, 4.0);
+
+        //Then - every point sits near radius 2, and the circle goes all round
+        let points: Vec<&Point> = path.iter().filter(|p| p.x.is_finite()).collect();
+        assert!(points.len() > 40);
+        assert!(points.iter().all(|p| (p.x.hypot(p.y) - 2.0).abs() < 0.05));
+        for (x, y) in [(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)] {
+            assert!(points.iter().any(|p| p.x * x + p.y * y > 1.9));
+        }
+    }
+
+    #[test]
+    fn contour_levels_are_round_and_inside_the_range() {
+        //When
+        let levels = contour_levels(0.0, 18.0, 10);
+        let flat = contour_levels(3.0, 3.0, 10);
+
+        //Then
+        assert_eq!(levels, vec![2.0, 4.0, 6.0, 8.0, 10.0, 12.0, 14.0, 16.0]);
+        assert!(flat.is_empty());
+    }
+
+    #[test]
+    fn surface_points_end_with_the_centre() {
+        //Given
+        let mut defs = Definitions::new();
+        let go = get_graph_options();
+        let eq = SurfaceEquation::parse("x + y").unwrap();
+        let grid = sample_grid(&eq, (-1.0, 1.0), (-1.0, 1.0), (5, 5), &mut defs).unwrap();
+
+        //When
+        let points = surface_points(&grid, &go);
+
+        //Then
+        assert_eq!(points.len(), 26);
+        let centre = points[25];
+        assert!((centre[0] - go.width as f32 / 2.0).abs() < 1e-3);
+        assert!((centre[1] - go.height as f32 / 2.0).abs() < 1e-3);
+    }
+
+    // ============================================================================
+    // Error Message Improvement Tests
+    // ============================================================================
+
+    #[test]
+    fn invalid_command_shows_help_hint() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+
+        //When
+        use crate::modules::commands::run_command;
+        run_command("invalidcmd", &mut test_logger, &mut repl);
+
+        //Then
+        assert!(test_logger.error_val.contains("Type ':h' for help"));
+    }
+
+    #[test]
+    fn error_messages_are_capitalized() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+
+        //When - remove a binding that doesn't exist
+        bindings::undefine("zz", &mut repl, &mut test_logger);
+
+        //Then - error message should start with capital letter
+        assert!(test_logger.error_val.starts_with("Nothing"));
+    }
+
+    // ============================================================================
+    // Edge Case Tests
+    // ============================================================================
+
+    #[test]
+    fn evaluate_with_extra_whitespace() {
+        //Given
+        let mut test_logger = get_test_logger();
+
+        //When - expression with extra spaces
+        simple_evaluate("  2   +   2  ", &mut test_logger);
+
+        //Then
+        assert_eq!(test_logger.val, "4");
+        assert!(test_logger.error_val.is_empty());
+    }
+
+    #[test]
+    fn graph_with_whitespace_in_equation() {
+        //Given
+        let eq_str = "y = x ^ 2";
+        let x_min = -2f32;
+        let x_max = 2f32;
+        let go = get_graph_options();
+
+        //When
+        let g = graph(eq_str, x_min, x_max, &go, &empty_defs());
+
+        //Then - should handle whitespace
+        assert!(g.is_ok());
+    }
+
+    #[test]
+    fn division_by_zero_in_evaluation() {
+        //Given
+        let mut test_logger = get_test_logger();
+
+        //When
+        simple_evaluate("1/0", &mut test_logger);
+
+        //Then - should either error or return inf (depending on rusty-maths behavior)
+        // We just verify it doesn't panic
+        assert!(!test_logger.val.is_empty() || !test_logger.error_val.is_empty());
+    }
+
+    #[test]
+    fn very_large_numbers() {
+        //Given
+        let mut test_logger = get_test_logger();
+
+        //When
+        simple_evaluate("999999*999999", &mut test_logger);
+
+        //Then - should handle large numbers
+        assert!(!test_logger.val.is_empty());
+        assert!(test_logger.error_val.is_empty());
+    }
+
+    #[test]
+    fn very_small_numbers() {
+        //Given
+        let mut test_logger = get_test_logger();
+
+        //When
+        simple_evaluate("0.000001*0.000001", &mut test_logger);
+
+        //Then
+        assert!(!test_logger.val.is_empty());
+        assert!(test_logger.error_val.is_empty());
+    }
+
+    #[test]
+    fn negative_numbers_in_expressions() {
+        //Given
+        let mut test_logger = get_test_logger();
+
+        //When
+        simple_evaluate("-5*-3", &mut test_logger);
+
+        //Then - should be 15
+        assert_eq!(test_logger.val, "15");
+        assert!(test_logger.error_val.is_empty());
+    }
+
+    #[test]
+    fn multiple_bindings_in_expression() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+
+        let_line("let a = 5", &mut repl, &mut test_logger);
+        let_line("let b = 3", &mut repl, &mut test_logger);
+
+        //When
+        evaluate("a*b+a", &mut repl, &mut test_logger);
+
+        //Then - 5*3+5 = 20
+        assert_eq!(test_logger.val, "20");
+        assert!(test_logger.error_val.is_empty());
+    }
+
+    #[test]
+    fn ans_with_multiple_operations() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+
+        //When - chain of operations using ans
+        evaluate("10", &mut repl, &mut test_logger);
+        assert_eq!(test_logger.val, "10");
+
+        evaluate("ans*2", &mut repl, &mut test_logger);
+        assert_eq!(test_logger.val, "20");
+
+        evaluate("ans+5", &mut repl, &mut test_logger);
+
+        //Then - should be 25
+        assert_eq!(test_logger.val, "25");
+        assert!(test_logger.error_val.is_empty());
+    }
+
+    #[test]
+    fn empty_equation_in_graph() {
+        //Given
+        let eq_str = "";
+        let x_min = -1f32;
+        let x_max = 1f32;
+        let go = get_graph_options();
+
+        //When
+        let g = graph(eq_str, x_min, x_max, &go, &empty_defs());
+
+        //Then - should return an error
+        assert!(g.is_err());
+    }
+
+    #[test]
+    fn graph_with_invalid_equation() {
+        //Given
+        let eq_str = "y=invalid";
+        let x_min = -1f32;
+        let x_max = 1f32;
+        let go = get_graph_options();
+
+        //When
+        let g = graph(eq_str, x_min, x_max, &go, &empty_defs());
+
+        //Then - should return an error
+        assert!(g.is_err());
+    }
+
+    // ============================================================================
+    // Axis Tick Tests
+    // ============================================================================
+
+    #[test]
+    fn nice_step_picks_1_2_5_multiples() {
+        use crate::modules::graphing::nice_step;
+
+        assert_eq!(nice_step(10.0, 10), 1.0);
+        assert_eq!(nice_step(10.0, 5), 2.0);
+        assert_eq!(nice_step(100.0, 4), 20.0);
+        assert_eq!(nice_step(3.0, 6), 0.5);
+        assert!((nice_step(0.07, 7) - 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn nice_ticks_stay_inside_range() {
+        use crate::modules::graphing::nice_ticks;
+
+        assert_eq!(nice_ticks(-2.5, 2.5, 5), vec![-2.0, -1.0, 0.0, 1.0, 2.0]);
+        // Degenerate ranges produce no ticks rather than a panic or a flood.
+        assert!(nice_ticks(1.0, 1.0, 5).is_empty());
+        assert!(nice_ticks(f32::MAX, f32::MIN, 5).is_empty());
+    }
+
+    #[test]
+    fn format_tick_uses_step_precision() {
+        use crate::modules::graphing::format_tick;
+
+        assert_eq!(format_tick(50.0, 10.0), "50");
+        assert_eq!(format_tick(0.25, 0.05), "0.25");
+        assert_eq!(format_tick(-0.0001, 0.5), "0.0");
+    }
+
+    #[test]
+    fn graph_labels_both_axes() {
+        //Given
+        let go = get_graph_options();
+
+        //When
+        let g = graph("y=x", -5.0, 5.0, &go, &empty_defs()).unwrap();
+
+        //Then - tick marks on the left and bottom edges, with labels
+        let lines: Vec<&str> = g.lines().collect();
+        assert!(lines.iter().any(|l| l.contains('┤')));
+        assert!(lines[lines.len() - 2].contains('┬'));
+        let x_labels = lines[lines.len() - 1];
+        assert!(x_labels.contains("-4") && x_labels.contains('0') && x_labels.contains('4'));
+    }
+
+    // ============================================================================
+    // Log Scale Tests
+    // ============================================================================
+
+    #[test]
+    fn log_y_graph_labels_decades() {
+        //Given
+        let go = GraphOptions {
+            y_scale: Scale::Log,
+            ..get_graph_options()
+        };
+
+        //When
+        let g = graph("y=10^x", 0.0, 4.0, &go, &empty_defs()).unwrap();
+
+        //Then - the y labels are powers of ten
+        assert!(is_graph_string(&g));
+        assert!(g.contains("1┤"));
+        assert!(g.contains("100┤"));
+        assert!(g.contains("1000┤"));
+    }
+
+    #[test]
+    fn log_scales_drop_non_positive_values() {
+        //Given a curve that crosses zero
+        let go = GraphOptions {
+            x_scale: Scale::Log,
+            y_scale: Scale::Log,
+            ..get_graph_options()
+        };
+
+        //When
+        let g = graph("y=x-10", 1.0, 100.0, &go, &empty_defs());
+
+        //Then - the non-positive half is a hole, not a panic
+        assert!(g.is_ok());
+        assert!(is_graph_string(&g.unwrap()));
+    }
+
+    #[test]
+    fn graph_command_rejects_unknown_scale() {
+        use crate::modules::commands::run_command;
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+
+        run_command("g logz", &mut test_logger, &mut repl);
+
+        assert_eq!(
+            test_logger.error_val,
+            "Unknown graph option 'logz'. Valid: logx, logy"
+        );
+    }
+
+    #[test]
+    fn as_cli_tool_log_graph() {
+        //Given
+        let args: Vec<String> = ["rmr", "-g", "y=x^2", "1", "100", "--logx", "--logy"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut test_logger = get_test_logger();
+
+        //When
+        as_cli_tool(&args, &mut test_logger);
+
+        //Then
+        assert!(is_graph_string(&test_logger.val));
+        assert!(test_logger.error_val.is_empty());
+    }
+
+    #[test]
+    fn as_cli_tool_log_x_needs_positive_min() {
+        let args: Vec<String> = ["rmr", "-g", "y=x", "0", "10", "--logx"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut test_logger = get_test_logger();
+
+        as_cli_tool(&args, &mut test_logger);
+
+        assert!(test_logger.val.is_empty());
+        assert_eq!(
+            test_logger.error_val,
+            "x min `0` must be positive on a log x axis"
+        );
+    }
+
+    // ============================================================================
+    // Fixed Y Range Tests
+    // ============================================================================
+
+    fn y_labels(g: &str) -> Vec<String> {
+        g.lines()
+            .filter_map(|l| l.split_once('┤').map(|(label, _)| label.trim().to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn fixed_y_range_is_independent_of_x_window() {
+        //Given a locked y window
+        let go = GraphOptions {
+            y_min: -2.0,
+            y_max: 2.0,
+            y_fixed: true,
+            ..get_graph_options()
+        };
+
+        //When the same function is graphed over different x windows
+        let narrow = graph("y=x^2", -1.0, 1.0, &go, &empty_defs()).unwrap();
+        let wide = graph("y=x^2", -10.0, 10.0, &go, &empty_defs()).unwrap();
+
+        //Then both share the exact y scale
+        assert_eq!(y_labels(&narrow), y_labels(&wide));
+        assert_eq!(y_labels(&narrow).first().map(String::as_str), Some("2.0"));
+        assert!(y_labels(&narrow).contains(&"-1.5".to_string()));
+    }
+
+    #[test]
+    fn as_cli_tool_graph_with_y_range() {
+        let args: Vec<String> = ["rmr", "-g", "y=x", "-5", "5", "--y", "-1", "1"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut test_logger = get_test_logger();
+
+        as_cli_tool(&args, &mut test_logger);
+
+        assert!(is_graph_string(&test_logger.val));
+        assert!(test_logger.error_val.is_empty());
+        assert_eq!(
+            y_labels(&test_logger.val).first().map(String::as_str),
+            Some("1.0")
+        );
+    }
+
+    #[test]
+    fn as_cli_tool_graph_rejects_inverted_y_range() {
+        let args: Vec<String> = ["rmr", "-g", "y=x", "-5", "5", "--y", "1", "-1"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut test_logger = get_test_logger();
+
+        as_cli_tool(&args, &mut test_logger);
+
+        assert!(test_logger.val.is_empty());
+        assert!(test_logger
+            .error_val
+            .starts_with("y min `1` must be less than y max `-1`"));
+    }
+
+    // ============================================================================
+    // Graph Size and Aspect Tests
+    // ============================================================================
+
+    #[test]
+    fn update_height_is_independent_of_width() {
+        use crate::modules::repl::MIN_GRAPH_HEIGHT;
+        let mut repl = get_repl();
+
+        assert!(repl.update_height(200));
+        assert_eq!((repl.width, repl.height), (240, 200));
+
+        assert!(!repl.update_height(MIN_GRAPH_HEIGHT - 1));
+        assert_eq!(repl.height, 200); // unchanged
+    }
+
+    #[test]
+    fn new_width_keeps_a_height_that_was_set() {
+        let mut repl = get_repl();
+
+        //Given
+        assert!(repl.update_dimensions(100));
+        assert_eq!((repl.width, repl.height), (100, 50));
+        assert!(repl.update_height(80));
+
+        //When
+        assert!(repl.update_dimensions(300));
+
+        //Then
+        assert_eq!((repl.width, repl.height), (300, 80));
+    }
+
+    #[test]
+    fn terminal_size_maps_to_braille_cells() {
+        use crate::modules::{
+            common::graph_size_for_terminal,
+            repl::{MIN_GRAPH_HEIGHT, MIN_GRAPH_WIDTH},
+        };
+
+        // 2 cells per column and 4 per row, less the frame and labels.
+        assert_eq!(graph_size_for_terminal(110, 45), (200, 160));
+        // A tiny terminal still yields a renderable graph.
+        assert_eq!(
+            graph_size_for_terminal(3, 2),
+            (MIN_GRAPH_WIDTH, MIN_GRAPH_HEIGHT)
+        );
+    }
+
+    #[test]
+    fn equal_aspect_widens_x_to_match_y() {
+        //Given a unit circle on a 2:1 cell grid
+        let go = GraphOptions {
+            equal_aspect: true,
+            ..get_graph_options()
+        };
+
+        //When
+        let g = graph(
+            "y=sqrt(1-x^2)|y=-sqrt(1-x^2)",
+            -1.0,
+            1.0,
+            &go,
+            &empty_defs(),
+        )
+        .unwrap();
+
+        //Then the padded y span (3) sets x to a span of 6 around 0
+        let x_labels = g.lines().last().unwrap_or_default();
+        assert!(x_labels.contains("-2.5"));
+        assert!(x_labels.contains("2.5"));
+    }
+
+    #[test]
+    fn equal_aspect_widens_y_to_match_x() {
+        //Given a flat line over a wide x window
+        let go = GraphOptions {
+            equal_aspect: true,
+            ..get_graph_options()
+        };
+
+        //When
+        let g = graph("y=0", -20.0, 20.0, &go, &empty_defs()).unwrap();
+
+        //Then y spans half of x's 40 units: -10..10
+        let labels: Vec<&str> = g
+            .lines()
+            .filter_map(|l| l.split_once('┤').map(|(label, _)| label.trim()))
+            .collect();
+        assert!(labels.contains(&"10"));
+        assert!(labels.contains(&"-8"));
+    }
+
+    #[test]
+    fn fit_terminal_resizes_graph_options() {
+        //Given
+        let mut go = get_graph_options();
+
+        //When the terminal reports a resize
+        go.fit_terminal(80, 30);
+
+        //Then the graph fills it and still renders
+        assert_eq!((go.width, go.height), (140, 100));
+        assert!(is_graph_string(
+            &graph("y=x", -1.0, 1.0, &go, &empty_defs()).unwrap()
+        ));
+    }
+
+    // ============================================================================
+    // Interactive Graph Mouse Tests
+    // ============================================================================
+
+    #[test]
+    fn cell_to_point_maps_glyphs_back_to_graph_coordinates() {
+        //Given a graph with a locked -10..10 window on both axes
+        let go = GraphOptions {
+            y_min: -10.0,
+            y_max: 10.0,
+            y_fixed: true,
+            ..get_graph_options()
+        };
+        let (g, plot) = graph_with_plot("y=x", -10.0, 10.0, &go, &empty_defs()).unwrap();
+        let layout = plot.layout;
+
+        //When the middle glyph of the plot area is looked up
+        let col = (layout.gutter + 1 + go.width / 4) as u16;
+        let row = (1 + go.height / 8) as u16;
+        let (x, y) = layout.cell_to_point(col, row).unwrap();
+
+        //Then it is a plot glyph sitting within one glyph of the origin
+        let line: Vec<char> = g.lines().nth(row as usize).unwrap().chars().collect();
+        assert!(('\u{2800}'..='\u{28FF}').contains(&line[col as usize]));
+        assert!(x.abs() < 20.0 / 120.0, "x = {x}");
+        assert!(y.abs() < 20.0 / 30.0, "y = {y}");
+    }
+
+    #[test]
+    fn cell_to_point_outside_the_plot_is_none() {
+        //Given
+        let (_, plot) =
+            graph_with_plot("y=x", -1.0, 1.0, &get_graph_options(), &empty_defs()).unwrap();
+        let layout = plot.layout;
+
+        //Then the frame border, the gutter and beyond the frame map to nothing
+        assert!(layout.cell_to_point(0, 0).is_none());
+        assert!(layout.cell_to_point(0, 5).is_none());
+        assert!(layout.cell_to_point(500, 5).is_none());
+        assert!(layout.cell_to_point(20, 500).is_none());
+    }
+
+    #[test]
+    fn zoom_range_keeps_the_pointer_fixed() {
+        //When
+        let (lo, hi) = zoom_range(Scale::Linear, (0.0, 10.0), 5.0, 0.5);
+        let (log_lo, log_hi) = zoom_range(Scale::Log, (1.0, 100.0), 1.0, 0.5);
+
+        //Then
+        assert_eq!((lo, hi), (2.5, 7.5));
+        assert!((log_lo - 10_f32.sqrt()).abs() < 1e-3);
+        assert!((log_hi - 1000_f32.sqrt()).abs() < 1e-2);
+    }
+
+    #[test]
+    fn shift_range_moves_by_decades_on_log_axes() {
+        //When
+        let (lo, hi) = shift_range(Scale::Log, (1.0, 10.0), 1.0);
+
+        //Then
+        assert!((lo - 10.0).abs() < 1e-3);
+        assert!((hi - 100.0).abs() < 1e-2);
+        assert_eq!(shift_range(Scale::Linear, (-1.0, 1.0), 2.0), (1.0, 3.0));
+    }
+
+    // ============================================================================
+    // Animation Tests
+    // ============================================================================
+
+    fn timeline(frames: usize, playback: Playback) -> Timeline {
+        Timeline {
+            t_min: 0.0,
+            t_max: 1.0,
+            frames,
+            playback,
+        }
+    }
+
+    fn frames_ticked(timeline: &Timeline, ticks: usize) -> Vec<usize> {
+        let mut clock = Clock::default();
+        (0..ticks)
+            .map(|_| {
+                clock.tick(timeline);
+                clock.frame
+            })
+            .collect()
+    }
+
+    #[test]
+    fn timeline_spans_t_range_inclusive() {
+        //Given
+        let tl = timeline(5, Playback::Loop);
+
+        //Then
+        assert_eq!(tl.t_at(0), 0.0);
+        assert_eq!(tl.t_at(2), 0.5);
+        assert_eq!(tl.t_at(4), 1.0);
+        assert_eq!(timeline(1, Playback::Loop).t_at(0), 0.0);
+    }
+
+    #[test]
+    fn clock_playback_modes() {
+        //Then
+        assert_eq!(
+            frames_ticked(&timeline(3, Playback::Loop), 4),
+            vec![1, 2, 0, 1]
+        );
+        assert_eq!(
+            frames_ticked(&timeline(3, Playback::Bounce), 5),
+            vec![1, 2, 1, 0, 1]
+        );
+        assert_eq!(
+            frames_ticked(&timeline(3, Playback::Once), 4),
+            vec![1, 2, 2, 2]
+        );
+    }
+
+    #[test]
+    fn clock_once_stops_at_last_frame() {
+        //Given
+        let tl = timeline(2, Playback::Once);
+        let mut clock = Clock::default();
+
+        //Then
+        assert!(clock.tick(&tl));
+        assert!(!clock.tick(&tl));
+        assert_eq!(clock.frame, 1);
+    }
+
+    #[test]
+    fn clock_step_wraps_only_when_looping() {
+        //Given
+        let mut looping = Clock::default();
+        let mut once = Clock::default();
+
+        //When
+        looping.step(&timeline(3, Playback::Loop), false);
+        once.step(&timeline(3, Playback::Once), false);
+
+        //Then
+        assert_eq!(looping.frame, 2);
+        assert_eq!(once.frame, 0);
+    }
+
+    #[test]
+    fn playback_parse() {
+        assert_eq!(Playback::parse(""), Some(Playback::Loop));
+        assert_eq!(Playback::parse("bounce"), Some(Playback::Bounce));
+        assert_eq!(Playback::parse("once"), Some(Playback::Once));
+        assert_eq!(Playback::parse("sometimes"), None);
+    }
+
+    #[test]
+    fn shadow_restores_user_binding() {
+        //Given the user has their own t
+        let (mut repl, mut logger) = get_repl_and_logger();
+        assert!(let_line("let t = 3", &mut repl, &mut logger));
+        let shadow = Shadow::new(&repl.defs, "t");
+
+        //When an animation binds and restores it
+        repl.defs.define_value("t", 0.5).unwrap();
+        shadow.restore(&mut repl.defs);
+
+        //Then
+        assert_eq!(repl.defs.value("t"), Some(3.0));
+    }
+
+    #[test]
+    fn shadow_removes_parameter_with_no_user_binding() {
+        //Given
+        let mut defs = empty_defs();
+        let shadow = Shadow::new(&defs, "t");
+
+        //When
+        defs.define_value("t", 0.5).unwrap();
+        shadow.restore(&mut defs);
+
+        //Then
+        assert!(!defs.contains("t"));
+    }
+
+    // ============================================================================
+    // Slider Tests
+    // ============================================================================
+
+    #[test]
+    fn sliders_default_to_every_value_binding() {
+        //Given values, a function and ans
+        let (mut repl, mut logger) = get_repl_and_logger();
+        assert!(let_line("let a = 3", &mut repl, &mut logger));
+        assert!(let_line("let g(x) = a * x^2", &mut repl, &mut logger));
+        assert!(let_line("let b = 2", &mut repl, &mut logger));
+        repl.set_ans(9.0);
+
+        //When
+        let sliders = make_sliders("", &repl.defs, 0.5).unwrap();
+
+        //Then only the user's values become sliders
+        let names: Vec<&str> = sliders.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(sliders[0].value, 3.0);
+        assert_eq!(sliders[0].step, 0.5);
+    }
+
+    #[test]
+    fn sliders_reject_functions_and_unknown_names() {
+        //Given
+        let (mut repl, mut logger) = get_repl_and_logger();
+        assert!(let_line("let a = 3", &mut repl, &mut logger));
+        assert!(let_line("let g(x) = a * x^2", &mut repl, &mut logger));
+
+        //Then
+        assert_eq!(make_sliders("a", &repl.defs, 1.0).unwrap().len(), 1);
+        assert!(make_sliders("a, g", &repl.defs, 1.0)
+            .unwrap_err()
+            .contains("function"));
+        assert!(make_sliders("zz", &repl.defs, 1.0)
+            .unwrap_err()
+            .contains("'zz'"));
+        assert!(make_sliders("", &empty_defs(), 1.0).is_err());
+    }
+
+    #[test]
+    fn slider_nudge_stays_on_step_grid() {
+        //Given
+        let defs = {
+            let mut d = empty_defs();
+            d.define_value("a", 3.0).unwrap();
+            d
+        };
+        let mut slider = make_sliders("a", &defs, 0.1).unwrap().remove(0);
+
+        //When
+        for _ in 0..7 {
+            slider.nudge(true);
+        }
+        slider.nudge(false);
+
+        //Then
+        assert_eq!(slider.value, 3.6);
+        assert_eq!(slider.original, 3.0);
+    }
+
+    #[test]
+    fn slider_step_stays_in_range() {
+        //Given
+        let defs = {
+            let mut d = empty_defs();
+            d.define_value("a", 3.0).unwrap();
+            d
+        };
+        let mut slider = make_sliders("a", &defs, 0.1).unwrap().remove(0);
+
+        //When
+        for _ in 0..50 {
+            slider.scale_step(false);
+        }
+        let finest = slider.step;
+        for _ in 0..50 {
+            slider.scale_step(true);
+        }
+
+        //Then
+        assert!(finest > 0.0 && finest < 1e-5, "{finest}");
+        assert!(slider.step.is_finite() && slider.step > 1e5);
+        slider.nudge(true);
+        assert!(slider.value.is_finite());
+    }
+
+    #[test]
+    fn slider_values_reshape_late_bound_functions() {
+        //Given g depends on a
+        let (mut repl, mut logger) = get_repl_and_logger();
+        assert!(let_line("let a = 1", &mut repl, &mut logger));
+        assert!(let_line("let g(x) = a * x", &mut repl, &mut logger));
+        let go = get_graph_options();
+        let before = graph("y=g(x)", -1.0, 1.0, &go, &repl.defs).unwrap();
+
+        //When a slider moves a
+        repl.defs.define_value("a", -1.0).unwrap();
+
+        //Then the curve changes
+        let after = graph("y=g(x)", -1.0, 1.0, &go, &repl.defs).unwrap();
+        assert_ne!(before, after);
+    }
+
+    // ============================================================================
+    // Save Tests
+    // ============================================================================
+
+    #[test]
+    fn strip_ansi_removes_escapes() {
+        //Given a table, which underlines its cells
+        let table = make_table_string(vec![Point::new(1.0, 2.0)]);
+
+        //When
+        let plain = strip_ansi(&table);
+
+        //Then
+        assert!(table.contains('\u{1b}'));
+        assert!(!plain.contains('\u{1b}'));
+        assert!(plain.contains("│1    │ 2     │"));
+    }
+
+    #[test]
+    fn save_txt_writes_last_frame() {
+        let path = std::env::temp_dir().join(format!("rmr_save_test_{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        //Given a repl that has drawn a graph
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+        let g = graph("y=x", -1.0, 1.0, &get_graph_options(), &empty_defs()).unwrap();
+        repl.remember_frame(Some(g.clone()));
+        repl.remember_frame(None);
+
+        //When
+        run_command(
+            &format!("save txt {}", path.display()),
+            &mut test_logger,
+            &mut repl,
+        );
+
+        //Then the file holds the frame
+        let saved = std::fs::read_to_string(&path).unwrap_or_default();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(saved, format!("{g}\n"));
+        assert!(test_logger.val.starts_with("Saved to"));
+    }
+
+    #[test]
+    fn save_needs_a_frame_and_a_known_format() {
+        //Given
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+
+        //When nothing has been drawn
+        run_command("save txt out.txt", &mut test_logger, &mut repl);
+
+        //Then
+        assert!(test_logger.error_val.contains("Nothing to save yet"));
+
+        //When the format is unknown
+        repl.last_frame = Some("frame".to_string());
+        run_command("save bmp out.bmp", &mut test_logger, &mut repl);
+
+        //Then
+        assert!(test_logger.error_val.contains("Unknown format 'bmp'"));
+
+        //When the path is missing
+        run_command("save txt", &mut test_logger, &mut repl);
+
+        //Then
+        assert!(test_logger.error_val.starts_with("Usage: :save"));
+    }
+
+    #[test]
+    fn as_cli_tool_graph_output_file() {
+        let path =
+            std::env::temp_dir().join(format!("rmr_cli_out_test_{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        //Given
+        let args = [
+            "rmr".to_owned(),
+            "-g".to_owned(),
+            "y=x".to_owned(),
+            "-1".to_owned(),
+            "1".to_owned(),
+            "-o".to_owned(),
+            path.display().to_string(),
+        ];
+        let mut test_logger = get_test_logger();
+
+        //When
+        as_cli_tool(&args, &mut test_logger);
+
+        //Then the graph went to the file, not the terminal
+        let saved = std::fs::read_to_string(&path).unwrap_or_default();
+        let _ = std::fs::remove_file(&path);
+        assert!(is_graph_string(saved.trim_end()));
+        assert!(test_logger.val.starts_with("Saved to"));
+    }
+
+    // ============================================================================
+    // PNG Export Tests
+    // ============================================================================
+
+    /// The chunk types of a PNG file, in order, checking each chunk's CRC.
+    fn png_chunks(png: &[u8]) -> Vec<String> {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let mut kinds = Vec::new();
+        let mut i = 8;
+        while i < png.len() {
+            let len = u32::from_be_bytes(png[i..i + 4].try_into().unwrap()) as usize;
+            let body = &png[i + 4..i + 8 + len];
+            let crc = u32::from_be_bytes(png[i + 8 + len..i + 12 + len].try_into().unwrap());
+            assert_eq!(crc32(body), crc);
+            kinds.push(String::from_utf8_lossy(&body[..4]).to_string());
+            i += 12 + len;
+        }
+        kinds
+    }
+
+    #[test]
+    fn crc32_matches_png_reference() {
+        //The CRC every PNG's IEND chunk carries
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+
+    #[test]
+    fn encode_png_writes_valid_chunks() {
+        //Given a 2x2 image
+        let rgb = [255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255];
+
+        //When
+        let png = encode_png(2, 2, &rgb);
+
+        //Then
+        assert_eq!(png_chunks(&png), vec!["IHDR", "IDAT", "IEND"]);
+        assert_eq!(&png[16..24], &[0, 0, 0, 2, 0, 0, 0, 2]);
+    }
+
+    /// Inflates a zlib stream of fixed-Huffman deflate blocks, the only
+    /// kind the encoder writes.
+    fn inflate_fixed(zlib: &[u8]) -> Vec<u8> {
+        let data = &zlib[2..zlib.len() - 4];
+        let mut pos = 0;
+        let mut bit = |n: u32| -> usize {
+            let mut v = 0;
+            for i in 0..n {
+                v |= usize::from(data[pos / 8] >> (pos % 8) & 1) << i;
+                pos += 1;
+            }
+            v
+        };
+        const LENGTH_BASE: [usize; 29] = [
+            3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99,
+            115, 131, 163, 195, 227, 258,
+        ];
+        const DISTANCE_BASE: [usize; 30] = [
+            1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025,
+            1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+        ];
+        let length_extra = |i: usize| if (8..28).contains(&i) { (i - 4) / 4 } else { 0 };
+        let distance_extra = |i: usize| if i >= 4 { (i - 2) / 2 } else { 0 };
+
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let last = bit(1) == 1;
+            assert_eq!(bit(2), 1, "not a fixed-Huffman block");
+            loop {
+                // Codes are stored most-significant bit first.
+                let mut code = 0;
+                for _ in 0..7 {
+                    code = code << 1 | bit(1);
+                }
+                let symbol = if code <= 0x17 {
+                    256 + code
+                } else {
+                    code = code << 1 | bit(1);
+                    match code {
+                        0x30..=0xBF => code - 0x30,
+                        0xC0..=0xC7 => 280 + code - 0xC0,
+                        _ => 144 + (code << 1 | bit(1)) - 0x190,
+                    }
+                };
+                match symbol {
+                    0..=255 => out.push(symbol as u8),
+                    256 => break,
+                    _ => {
+                        let li = symbol - 257;
+                        let len = LENGTH_BASE[li] + bit(length_extra(li) as u32);
+                        let mut di = 0;
+                        for _ in 0..5 {
+                            di = di << 1 | bit(1);
+                        }
+                        let distance = DISTANCE_BASE[di] + bit(distance_extra(di) as u32);
+                        for _ in 0..len {
+                            out.push(out[out.len() - distance]);
+                        }
+                    }
+                }
+            }
+            if last {
+                return out;
+            }
+        }
+    }
+
+    /// The RGB pixels of a PNG written by the encoder: (width, height, rgb).
+    fn png_pixels(png: &[u8]) -> (usize, usize, Vec<u8>) {
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(png[20..24].try_into().unwrap()) as usize;
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let raw = inflate_fixed(&png[41..41 + idat_len]);
+        assert_eq!(raw.len(), (width * 3 + 1) * height);
+        let rgb = raw
+            .chunks(width * 3 + 1)
+            .flat_map(|row| {
+                assert_eq!(row[0], 0, "unfiltered scanline");
+                row[1..].to_vec()
+            })
+            .collect();
+        (width, height, rgb)
+    }
+
+    #[test]
+    fn encode_png_round_trips_through_inflate() {
+        //Given an image whose raw scanlines run past the 32K match window
+        let (width, height) = (200, 100);
+        let rgb: Vec<u8> = (0..width * height * 3)
+            .map(|i| ((i / 3 % 37) * 7 + i / (width * 3 * 40)) as u8)
+            .collect();
+
+        //When
+        let png = encode_png(width as u32, height as u32, &rgb);
+
+        //Then
+        assert_eq!(png_pixels(&png), (width, height, rgb));
+    }
+
+    #[test]
+    fn render_png_pixels_show_background_and_curve() {
+        //Given
+        let (_, plot) =
+            graph_with_plot("y=sin(x)", -5.0, 5.0, &get_graph_options(), &empty_defs()).unwrap();
+
+        //When
+        let (width, _, rgb) = png_pixels(&render_png(&plot, 320, 200));
+        let pixel = |x: usize, y: usize| &rgb[(y * width + x) * 3..(y * width + x) * 3 + 3];
+
+        //Then the corner is background and the curve is drawn in the first
+        //palette colour, fully covered along its middle
+        assert_eq!(pixel(0, 0), &[255, 255, 255]);
+        assert!(rgb.chunks(3).any(|p| p == [31, 120, 181]));
+    }
+
+    #[test]
+    fn render_png_of_graph_has_requested_size() {
+        //Given
+        let (_, plot) = graph_with_plot(
+            "y=sin(x)|y=x",
+            -3.0,
+            3.0,
+            &get_graph_options(),
+            &empty_defs(),
+        )
+        .unwrap();
+
+        //When
+        let png = render_png(&plot, 320, 200);
+
+        //Then
+        assert_eq!(png_chunks(&png), vec!["IHDR", "IDAT", "IEND"]);
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 320);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 200);
+        assert_eq!(colour_key(&plot), "y=sin(x): blue, y=x: orange");
+    }
+
+    #[test]
+    fn parse_size_accepts_width_x_height() {
+        assert_eq!(parse_size("800x600"), Ok((800, 600)));
+        assert!(parse_size("800").is_err());
+        assert!(parse_size("10x10").is_err());
+        assert!(parse_size("wide").is_err());
+    }
+
+    #[test]
+    fn save_png_needs_a_graph() {
+        //Given the last frame was a curve, not a graph
+        let (mut repl, mut test_logger) = get_repl_and_logger();
+        repl.remember_frame(Some("curve".to_string()));
+
+        //When
+        run_command("save png out.png", &mut test_logger, &mut repl);
+
+        //Then
+        assert!(test_logger.error_val.contains("Only graphs"));
+    }
+
+    #[test]
+    fn as_cli_tool_graph_png() {
+        let path =
+            std::env::temp_dir().join(format!("rmr_cli_png_test_{}.png", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        //Given
+        let args: Vec<String> = [
+            "rmr",
+            "-g",
+            "y=x",
+            "-1",
+            "1",
+            "--png",
+            &path.display().to_string(),
+            "--size",
+            "200x150",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let mut test_logger = get_test_logger();
+
+        //When
+        as_cli_tool(&args, &mut test_logger);
+
+        //Then
+        let png = std::fs::read(&path).unwrap_or_default();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(png_chunks(&png), vec!["IHDR", "IDAT", "IEND"]);
+        assert!(test_logger.val.starts_with("Saved 200x150"));
+    }
+
+    #[test]
+    fn as_cli_tool_size_needs_png() {
+        //Given
+        let args: Vec<String> = ["rmr", "-g", "y=x", "-1", "1", "--size", "200x150"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut test_logger = get_test_logger();
+
+        //When
+        as_cli_tool(&args, &mut test_logger);
+
+        //Then
+        assert!(test_logger
+            .error_val
+            .starts_with("--size only applies to --png"));
+    }
+
+    // ============================================================================
+    // Statistics Tests
+    // ============================================================================
+
+    #[test]
+    fn summary_reports_the_usual_statistics() {
+        //Given
+        let data = read_numbers("2, 4 4 4\n5 5 7 9").unwrap();
+
+        //When
+        let s = Summary::of(&data).unwrap();
+
+        //Then
+        assert_eq!(s.count, 8);
+        assert_eq!(s.mean, 5.0);
+        assert_eq!(s.median, 4.5);
+        assert_eq!(s.modes, vec![4.0]);
+        assert_eq!(s.population_variance, 4.0);
+        assert!((s.sample_variance.unwrap() - 32.0 / 7.0).abs() < 1e-12);
+        assert_eq!((s.min, s.q1, s.q3, s.max), (2.0, 4.0, 5.5, 9.0));
+    }
+
+    #[test]
+    fn summary_of_a_single_value_has_no_sample_variance() {
+        //When
+        let s = Summary::of(&[3.0]).unwrap();
+
+        //Then
+        assert_eq!(s.sample_variance, None);
+        assert!(s.modes.is_empty());
+        let lines = s.lines(2);
+        assert!(lines.contains(&("stdev (sample)", "needs two values".to_string())));
+        assert!(lines.contains(&("mode", "none (no value repeats)".to_string())));
+        assert!(Summary::of(&[]).is_none());
+    }
+
+    #[test]
+    fn read_numbers_rejects_words_and_non_finite_values() {
+        assert_eq!(read_numbers("1 x"), Err("'x' is not a number".to_string()));
+        assert_eq!(
+            read_numbers("1 inf"),
+            Err("'inf' is not a number".to_string())
+        );
+        assert!(read_numbers("  ").is_err());
+    }
+
+    #[test]
+    fn csv_column_picks_a_column_by_name_or_number() {
+        //Given
+        let text = "name,height,weight\na,1.5,60\n\nb,,70\nc,1.8,80\n";
+
+        //When
+        let by_name = csv_column(text, "Weight");
+        let by_number = csv_column(text, "2");
+        let words = csv_column(text, "");
+        let missing = csv_column(text, "age");
+
+        //Then
+        assert_eq!(by_name, Ok(vec![60.0, 70.0, 80.0]));
+        assert_eq!(by_number, Ok(vec![1.5, 1.8]));
+        assert_eq!(words, Err("line 2: 'a' is not a number".to_string()));
+        assert_eq!(
+            missing,
+            Err("No column 'age'. Columns: name, height, weight".to_string())
+        );
+        assert_eq!(csv_column("1,2\n3,4", ""), Ok(vec![1.0, 3.0]));
+    }
+
+    #[test]
+    fn histogram_counts_every_value_once() {
+        //Given
+        let data = [0.0, 1.0, 1.5, 2.0, 3.9, 4.0];
+
+        //When
+        let bins = histogram(&data, 4);
+
+        //Then
+        assert_eq!(
+            bins,
+            vec![(0.0, 1.0, 1), (1.0, 2.0, 2), (2.0, 3.0, 1), (3.0, 4.0, 2)]
+        );
+        assert_eq!(histogram(&[5.0, 5.0], 1), vec![(4.5, 5.5, 2)]);
+        assert_eq!(auto_bins(8), 4);
+        assert_eq!(auto_bins(1), 1);
+    }
+
+    #[test]
+    fn box_plot_marks_values_past_the_fences() {
+        //Given
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0, 30.0];
+        let s = Summary::of(&data).unwrap();
+
+        //When
+        let overlay = box_plot_overlay(&data, &s);
+
+        //Then the whisker stops at 5 and 30 is an outlier
+        assert_eq!(overlay.markers, vec![Point::new(30.0, 0.5)]);
+        let (_, path) = &overlay.paths[0];
+        let right = path.iter().map(|p| p.x).filter(|x| x.is_finite());
+        assert_eq!(right.fold(f32::MIN, f32::max), 5.0);
+    }
+
+    #[test]
+    fn stats_draws_a_histogram_and_a_box_plot() {
+        //Given
+        let go = get_graph_options();
+        let data = [1.0, 2.0, 2.0, 3.0, 8.0];
+        let s = Summary::of(&data).unwrap();
+        let defs = Definitions::new();
+        let bars = histogram(&data, auto_bins(data.len()));
+
+        //When
+        let hist = graph_with_overlay("", 0.0, 9.0, &go, &defs, &histogram_overlay(&bars));
+        let box_plot = graph_with_overlay("", 0.0, 9.0, &go, &defs, &box_plot_overlay(&data, &s));
+
+        //Then
+        assert_eq!(
+            bars,
+            vec![
+                (1.0, 2.75, 3),
+                (2.75, 4.5, 1),
+                (4.5, 6.25, 0),
+                (6.25, 8.0, 1)
+            ]
+        );
+        let (g, plot) = hist.unwrap();
+        assert!(g.chars().any(|c| ('\u{2801}'..='\u{28FF}').contains(&c)));
+        assert_eq!(plot.equations, vec!["histogram".to_string()]);
+        // The top of each non-empty bar, at its count; the empty bin has none.
+        let tops: Vec<(f32, f32)> = plot.curves[0]
+            .iter()
+            .filter(|p| p.y > 0.0)
+            .map(|p| (p.x, p.y))
+            .collect();
+        assert_eq!(
+            tops,
+            vec![
+                (1.0, 3.0),
+                (2.75, 3.0),
+                (2.75, 1.0),
+                (4.5, 1.0),
+                (6.25, 1.0),
+                (8.0, 1.0)
+            ]
+        );
+        assert!(plot.layout.y_range.1 >= 3.0);
+
+        let (g, plot) = box_plot.unwrap();
+        assert!(g.chars().any(|c| ('\u{2801}'..='\u{28FF}').contains(&c)));
+        assert_eq!(plot.equations, vec!["box plot".to_string()]);
+    }
+
+    // ============================================================================
+    // Fit Tests
+    // ============================================================================
+
+    fn points(pairs: &[(f32, f32)]) -> Vec<Point> {
+        pairs.iter().map(|&(x, y)| Point::new(x, y)).collect()
+    }
+
+    fn assert_coefficients(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn model_parse_reads_names_and_degrees() {
+        assert_eq!(Model::parse(""), Ok(Model::Linear));
+        assert_eq!(Model::parse("poly 3"), Ok(Model::Polynomial(3)));
+        assert_eq!(Model::parse("Polynomial2"), Ok(Model::Polynomial(2)));
+        assert_eq!(Model::parse("exp"), Ok(Model::Exponential));
+        assert!(Model::parse("poly 0").is_err());
+        assert!(Model::parse("spline").is_err());
+    }
+
+    #[test]
+    fn least_squares_fits_an_overdetermined_system() {
+        //Given
+        let a = vec![
+            vec![1.0, 0.0],
+            vec![1.0, 1.0],
+            vec![1.0, 2.0],
+            vec![1.0, 3.0],
+        ];
+        let b = [2.0, 2.0, 6.0, 6.0];
+
+        //When
+        let x = least_squares(&a, &b).unwrap();
+
+        //Then
+        assert_coefficients(&x, &[1.6, 1.6]);
+        assert!(least_squares(&vec![vec![1.0, 2.0], vec![2.0, 4.0]], &[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn fit_recovers_exact_models() {
+        //Given
+        let line = points(&[(0.0, 1.0), (1.0, 3.0), (2.0, 5.0)]);
+        let parabola = points(&[(-1.0, 2.0), (0.0, 1.0), (1.0, 2.0), (2.0, 5.0)]);
+        let growth = points(&[
+            (0.0, 2.0),
+            (1.0, 2.0 * 1.5_f32.exp()),
+            (2.0, 2.0 * 3.0_f32.exp()),
+        ]);
+        let power = points(&[(1.0, 3.0), (2.0, 12.0), (4.0, 48.0)]);
+        let log = points(&[(1.0, 1.0), (std::f32::consts::E, 3.0), (7.389_056, 5.0)]);
+
+        //When
+        let line = fit(Model::Linear, &line).unwrap();
+        let parabola = fit(Model::Polynomial(2), &parabola).unwrap();
+        let growth = fit(Model::Exponential, &growth).unwrap();
+        let power = fit(Model::Power, &power).unwrap();
+        let log = fit(Model::Logarithmic, &log).unwrap();
+
+        //Then
+        assert_coefficients(&line.coefficients, &[1.0, 2.0]);
+        assert_coefficients(&parabola.coefficients, &[1.0, 0.0, 1.0]);
+        assert_coefficients(&growth.coefficients, &[2.0, 1.5]);
+        assert_coefficients(&power.coefficients, &[3.0, 2.0]);
+        assert_coefficients(&log.coefficients, &[1.0, 2.0]);
+        for f in [line, parabola, growth, power, log] {
+            assert!((f.r_squared - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn fit_takes_high_degrees_at_large_x() {
+        //Given a quartic sampled out to x = 1000, where x⁴ dwarfs the
+        //constant column
+        let quartic = |x: f64| 2.0 + 0.5 * x - 1e-3 * x * x + 1e-6 * x.powi(3) + 1e-9 * x.powi(4);
+        let data: Vec<Point> = (1..=10)
+            .map(|i| {
+                let x = f64::from(i) * 100.0;
+                Point::new(x as f32, quartic(x) as f32)
+            })
+            .collect();
+
+        //When
+        let f = fit(Model::Polynomial(4), &data).unwrap();
+
+        //Then
+        assert!((f.r_squared - 1.0).abs() < 1e-9);
+        for x in [150.0, 550.0, 950.0] {
+            assert!((f.value_at(x) - quartic(x)).abs() < 1e-2, "{x}");
+        }
+    }
+
+    #[test]
+    fn fit_reports_r_squared_for_noisy_data() {
+        //When
+        let f = fit(
+            Model::Linear,
+            &points(&[(0.0, 2.0), (1.0, 2.0), (2.0, 6.0), (3.0, 6.0)]),
+        )
+        .unwrap();
+
+        //Then 1 - 3.2 / 16
+        assert!((f.r_squared - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fit_rejects_data_the_model_cannot_take() {
+        //Given
+        let negative = points(&[(0.0, -1.0), (1.0, 2.0)]);
+        let few = points(&[(0.0, 1.0), (1.0, 2.0)]);
+        let same_x = points(&[(1.0, 1.0), (1.0, 2.0), (1.0, 3.0)]);
+
+        //Then
+        assert_eq!(
+            fit(Model::Exponential, &negative),
+            Err("An exponential fit needs every y above 0".to_string())
+        );
+        assert_eq!(
+            fit(Model::Polynomial(2), &few),
+            Err("A degree-2 fit needs at least 3 points".to_string())
+        );
+        assert!(fit(Model::Linear, &same_x).is_err());
+    }
+
+    #[test]
+    fn fit_expression_writes_signs_between_terms() {
+        //Given
+        let f = fit(
+            Model::Polynomial(2),
+            &points(&[(0.0, -1.0), (1.0, -1.0), (2.0, 1.0)]),
+        )
+        .unwrap();
+
+        //When
+        let shown = f.expression(|v| format!("{v:.1}"));
+
+        //Then y = x^2 - x - 1
+        assert_eq!(shown, "1.0*x^2 - 1.0*x - 1.0");
+    }
+
+    #[test]
+    fn fit_binds_as_a_function() {
+        //Given
+        let (mut repl, mut logger) = get_repl_and_logger();
+        let f = fit(Model::Linear, &points(&[(0.0, 1.0), (1.0, 3.0)])).unwrap();
+        let body = f.expression(|v| v.to_string());
+
+        //When
+        let line = format!("let fit(x) = {body}");
+        let bound = handle_let(&line, &mut repl, &mut logger, LetSource::Replay);
+
+        //Then
+        assert!(bound);
+        assert_eq!(repl.defs.function_body("fit"), Some(body.as_str()));
+    }
+
+    #[test]
+    fn generated_let_lines_are_shown_with_their_errors() {
+        //Given a line built by a command, never typed at the prompt
+        let (mut repl, mut logger) = get_repl_and_logger();
+        let line = "let 1fit(x) = 2*x";
+
+        //When
+        let bound = handle_let(line, &mut repl, &mut logger, LetSource::Generated);
+
+        //Then the error reprints the line it is about
+        assert!(!bound);
+        assert!(logger.error_val.starts_with(&format!("{line}\n")));
+    }
+
+    // ============================================================================
+    // Scatter Tests
+    // ============================================================================
+
+    #[test]
+    fn read_pairs_takes_typed_and_pasted_points() {
+        //When
+        let typed = read_pairs("0 0, 1 2\n(3, -4)");
+        let odd = read_pairs("0 0 1");
+        let word = read_pairs("0 q");
+
+        //Then
+        assert_eq!(
+            typed,
+            Ok(vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 2.0),
+                Point::new(3.0, -4.0)
+            ])
+        );
+        assert_eq!(
+            odd,
+            Err("line 1: each point needs an x and a y".to_string())
+        );
+        assert_eq!(word, Err("line 1: 'q' is not a number".to_string()));
+        assert!(read_pairs("x y").is_err());
+    }
+
+    #[test]
+    fn read_pairs_reads_a_t_table_back() {
+        //Given
+        let table = make_table_string(vec![Point::new(-1.0, 0.5), Point::new(2.0, 4.25)]);
+
+        //When
+        let points = read_pairs(&table);
+
+        //Then
+        assert_eq!(
+            points,
+            Ok(vec![Point::new(-1.0, 0.5), Point::new(2.0, 4.25)])
+        );
+    }
+
+    #[test]
+    fn scatter_dots_are_single_braille_dots() {
+        //Given
+        let go = GraphOptions {
+            y_min: 0.0,
+            y_max: 8.0,
+            y_fixed: true,
+            width: 8,
+            height: 8,
+            ..get_graph_options()
+        };
+        let scatter = |dots: Vec<Point>| {
+            let overlay = Overlay {
+                paths: vec![],
+                markers: vec![],
+                dots,
+            };
+            graph_with_overlay("", 0.0, 8.0, &go, &empty_defs(), &overlay).unwrap()
+        };
+        let set_dots = |g: &str| -> u32 {
+            g.chars()
+                .filter(|c| ('\u{2801}'..='\u{28FF}').contains(c))
+                .map(|c| (c as u32 - 0x2800).count_ones())
+                .sum()
+        };
+
+        //When
+        let (one, _) = scatter(vec![Point::new(5.0, 5.0)]);
+        let (two, plot) = scatter(vec![Point::new(5.0, 5.0), Point::new(3.0, 3.0)]);
+
+        //Then the second point adds exactly one dot
+        assert_eq!(set_dots(&two), set_dots(&one) + 1);
+        assert_eq!(
+            plot.points,
+            vec![Point::new(5.0, 5.0), Point::new(3.0, 3.0)]
+        );
+        assert!(plot.curves.is_empty());
+    }
+
+    #[test]
+    fn scatter_shares_a_graph_with_equations() {
+        //Given
+        let go = get_graph_options();
+        let overlay = Overlay {
+            paths: vec![],
+            markers: vec![],
+            dots: vec![Point::new(0.0, 20.0)],
+        };
+
+        //When
+        let (_, plot) =
+            graph_with_overlay("y = x", -1.0, 1.0, &go, &empty_defs(), &overlay).unwrap();
+
+        //Then the window stretches to show the point
+        assert_eq!(plot.equations, vec!["y = x".to_string()]);
+        assert!(plot.layout.y_range.1 >= 20.0);
+    }
+
+    // ============================================================================
+    // FFT Tests
+    // ============================================================================
+
+    /// A WAV file around `data`, with an odd-length chunk before it that
+    /// must be skipped along with its pad byte.
+    fn wav_bytes(format: u16, channels: u16, rate: u32, bits: u16, data: &[u8]) -> Vec<u8> {
+        let block = channels * bits / 8;
+        let mut body = b"WAVEfmt ".to_vec();
+        body.extend(16u32.to_le_bytes());
+        body.extend(format.to_le_bytes());
+        body.extend(channels.to_le_bytes());
+        body.extend(rate.to_le_bytes());
+        body.extend((rate * u32::from(block)).to_le_bytes());
+        body.extend(block.to_le_bytes());
+        body.extend(bits.to_le_bytes());
+        body.extend(b"LIST");
+        body.extend(3u32.to_le_bytes());
+        body.extend(b"abc\0");
+        body.extend(b"data");
+        body.extend((data.len() as u32).to_le_bytes());
+        body.extend(data);
+        let mut file = b"RIFF".to_vec();
+        file.extend((body.len() as u32).to_le_bytes());
+        file.extend(body);
+        file
+    }
+
+    fn tone(rate: u32, seconds: f64, parts: &[(f64, f64)]) -> Vec<f64> {
+        (0..(f64::from(rate) * seconds) as usize)
+            .map(|i| {
+                let t = i as f64 / f64::from(rate);
+                parts
+                    .iter()
+                    .map(|(hz, a)| a * (2.0 * std::f64::consts::PI * hz * t).sin())
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_wav_mixes_stereo_pcm_to_mono() {
+        //Given left and right frames of 16-bit samples
+        let data: Vec<u8> = [16384i16, 0, -32768, -32768]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+
+        //When
+        let wav = parse_wav(&wav_bytes(1, 2, 8000, 16, &data)).unwrap();
+
+        //Then
+        assert_eq!(wav.sample_rate, 8000);
+        assert_eq!(wav.channels, 2);
+        assert_eq!(wav.samples, vec![0.25, -1.0]);
+    }
+
+    #[test]
+    fn parse_wav_reads_8_and_24_bit_pcm_and_float() {
+        //When
+        let eight = parse_wav(&wav_bytes(1, 1, 8000, 8, &[128, 255, 0])).unwrap();
+        let twenty_four = parse_wav(&wav_bytes(1, 1, 8000, 24, &[0, 0, 0x40, 0, 0, 0xC0])).unwrap();
+        let float = parse_wav(&wav_bytes(3, 1, 8000, 32, &(-0.5f32).to_le_bytes())).unwrap();
+
+        //Then
+        assert_eq!(eight.samples, vec![0.0, 127.0 / 128.0, -1.0]);
+        assert_eq!(twenty_four.samples, vec![0.5, -0.5]);
+        assert_eq!(float.samples, vec![-0.5]);
+    }
+
+    #[test]
+    fn parse_wav_rejects_other_files_and_formats() {
+        assert_eq!(
+            parse_wav(b"ID3 not a wav file"),
+            Err("Not a WAV file (no RIFF/WAVE header)".to_string())
+        );
+        assert_eq!(
+            parse_wav(&wav_bytes(2, 1, 8000, 4, &[0, 0])),
+            Err("Only PCM and float WAV files can be read, not format 2 at 4 bits".to_string())
+        );
+        assert_eq!(
+            parse_wav(&wav_bytes(1, 1, 8000, 16, &[])),
+            Err("The WAV file has no samples".to_string())
+        );
+    }
+
+    #[test]
+    fn fft_matches_the_direct_transform() {
+        //Given
+        let input = [1.0, 2.0, 0.0, -1.0, 3.0, 0.5, -2.0, 1.0];
+        let (mut re, mut im) = (input.to_vec(), vec![0.0; 8]);
+
+        //When
+        fft(&mut re, &mut im);
+
+        //Then
+        for k in 0..8 {
+            let (mut dft_re, mut dft_im) = (0.0, 0.0);
+            for (n, x) in input.iter().enumerate() {
+                let angle = -2.0 * std::f64::consts::PI * (k * n) as f64 / 8.0;
+                dft_re += x * angle.cos();
+                dft_im += x * angle.sin();
+            }
+            assert!((re[k] - dft_re).abs() < 1e-9 && (im[k] - dft_im).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn stft_finds_the_frequencies_and_levels_of_a_chord() {
+        //Given
+        let rate = 44_100;
+        let samples = tone(rate, 1.0, &[(440.0, 0.5), (1000.0, 0.25)]);
+        let frame_len = frame_length(rate);
+
+        //When
+        let spectrum = stft(&samples, frame_len).nth(3).unwrap();
+        let peaks = dominant_frequencies(&spectrum, f64::from(rate) / frame_len as f64);
+
+        //Then
+        assert_eq!(frame_len, 4096);
+        assert_eq!(peaks.len(), 2);
+        assert!((peaks[0].0 - 440.0).abs() < 0.5, "{peaks:?}");
+        assert!((peaks[1].0 - 1000.0).abs() < 0.5, "{peaks:?}");
+        assert!((to_db(peaks[0].1) - to_db(0.5)).abs() < 0.5, "{peaks:?}");
+        assert!((to_db(peaks[1].1) - to_db(0.25)).abs() < 0.5, "{peaks:?}");
+    }
+
+    #[test]
+    fn segments_follow_the_sound_over_time() {
+        //Given half a second of 440 Hz, then half a second of 880 Hz
+        let rate = 8000;
+        let mut samples = tone(rate, 0.5, &[(440.0, 0.8)]);
+        samples.extend(tone(rate, 0.5, &[(880.0, 0.8)]));
+        let wav = Wav {
+            sample_rate: rate,
+            channels: 1,
+            samples,
+        };
+        let frame_len = frame_length(rate);
+
+        //When
+        let (segments, spectrum) = analyse(&wav, frame_len, 0.25);
+
+        //Then
+        let loudest: Vec<f64> = segments.iter().map(|s| s.peaks[0].0.round()).collect();
+        assert_eq!(loudest, vec![440.0, 440.0, 880.0, 880.0]);
+        assert_eq!((segments[3].start, segments[3].end), (0.75, 1.0));
+        let bin_hz = f64::from(rate) / frame_len as f64;
+        let mut overall: Vec<f64> = dominant_frequencies(&spectrum, bin_hz)
+            .iter()
+            .map(|p| p.0.round())
+            .collect();
+        overall.sort_by(f64::total_cmp);
+        assert_eq!(overall, vec![440.0, 880.0]);
+    }
+
+    #[test]
+    fn segment_lengths_keep_a_frame_in_every_row() {
+        //Given 512-sample frames at 8 kHz start 32 ms apart
+        let shortest = shortest_segment(8000, 512);
+
+        //Then
+        assert_eq!(shortest, 0.032);
+        assert_eq!(auto_segment_length(1.0, shortest), 0.1);
+        assert_eq!(auto_segment_length(180.0, shortest), 10.0);
+        assert_eq!(auto_segment_length(1.0, 0.128), 0.25);
+        assert_eq!(check_segment_length(0.05, 40.0, shortest), Ok(()));
+        assert_eq!(
+            check_segment_length(1e-9, 60.0, shortest),
+            Err("The segment length must be at least 0.032 s, the time between frames".to_string())
+        );
+        assert_eq!(
+            check_segment_length(0.05, 3600.0, shortest),
+            Err("That gives over 1000 rows; use at least 3.600 s".to_string())
+        );
     }
 }
@@ -0,0 +1,235 @@
+//! Full-screen viewer shared by the animated and interactive modes.
+//!
+//! Opening a [`Viewer`] switches to the terminal's alternate screen in raw
+//! mode, so frames are redrawn in place instead of by moving the cursor up
+//! over scrollback, and nothing is left behind once it closes. The bottom
+//! row is a status bar; `?` toggles a help overlay listing the mode's keys.
+//!
+//! The terminal is restored when the viewer is dropped, and a panic hook
+//! restores it too — release builds abort on panic, so no destructor runs.
+
+use std::io::{self, Stdout, Write};
+use std::panic::PanicHookInfo;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{
+        poll, read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent,
+        KeyEventKind, KeyModifiers, MouseEvent,
+    },
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{
+        self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+
+use crate::modules::common::GraphOptions;
+
+/// What a mode should do after [`Viewer::next_event`].
+pub(crate) enum ViewerEvent {
+    /// `q`, Esc or Ctrl-C: close the viewer.
+    Quit,
+    /// The screen changed size or the help overlay toggled: render again.
+    Redraw,
+    /// The timeout passed with no input: advance any animation.
+    Tick,
+    /// Any other key, for the mode to interpret.
+    Key(KeyEvent),
+    /// Mouse input; only sent after [`Viewer::capture_mouse`].
+    Mouse(MouseEvent),
+}
+
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+pub(crate) struct Viewer {
+    stdout: Stdout,
+    /// The hook installed before the viewer opened, put back on close.
+    previous_hook: Option<Arc<PanicHook>>,
+    /// (keys, action) pairs shown by the `?` overlay.
+    help: &'static [(&'static str, &'static str)],
+    show_help: bool,
+    cols: u16,
+    rows: u16,
+}
+
+impl Viewer {
+    pub(crate) fn open(help: &'static [(&'static str, &'static str)]) -> io::Result<Self> {
+        let (cols, rows) = terminal::size()?;
+
+        let previous_hook = Arc::new(std::panic::take_hook());
+        let chained = Arc::clone(&previous_hook);
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            chained(info);
+        }));
+
+        // Built before the terminal changes, so a failure below still
+        // restores it and the previous hook on drop.
+        let mut viewer = Viewer {
+            stdout: io::stdout(),
+            previous_hook: Some(previous_hook),
+            help,
+            show_help: false,
+            cols,
+            rows,
+        };
+        enable_raw_mode()?;
+        execute!(viewer.stdout, EnterAlternateScreen, Hide)?;
+        Ok(viewer)
+    }
+
+    /// Starts reporting clicks, drags and the scroll wheel as
+    /// [`ViewerEvent::Mouse`]. Capture ends when the viewer closes.
+    pub(crate) fn capture_mouse(&mut self) -> io::Result<()> {
+        execute!(self.stdout, EnableMouseCapture)
+    }
+
+    /// `base` shrunk, if needed, to fit the screen above the status bar.
+    pub(crate) fn fit(&self, base: &GraphOptions) -> GraphOptions {
+        let mut fitted = *base;
+        fitted.fit_terminal(self.cols, self.rows);
+        GraphOptions {
+            width: base.width.min(fitted.width),
+            height: base.height.min(fitted.height),
+            ..*base
+        }
+    }
+
+    /// Waits up to `timeout` (forever if `None`) for input. Quit keys, `?`
+    /// and resizes are handled here; other keys go back to the mode. Input
+    /// nobody acts on (key releases, focus changes, pastes) is skipped
+    /// without cutting the wait short, so ticks keep their pace.
+    pub(crate) fn next_event(&mut self, timeout: Option<Duration>) -> ViewerEvent {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        loop {
+            if let Some(deadline) = deadline {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() || !poll(left).unwrap_or(false) {
+                    return ViewerEvent::Tick;
+                }
+            }
+            match read() {
+                Ok(Event::Resize(cols, rows)) => {
+                    (self.cols, self.rows) = (cols, rows);
+                    return ViewerEvent::Redraw;
+                }
+                // Key releases and repeats only arrive on some platforms.
+                Ok(Event::Key(k)) if k.kind == KeyEventKind::Press => {
+                    return match k.code {
+                        KeyCode::Char('q') | KeyCode::Esc => ViewerEvent::Quit,
+                        KeyCode::Char('c') if k.modifiers.contains(KeyModifiers::CONTROL) => {
+                            ViewerEvent::Quit
+                        }
+                        KeyCode::Char('?') => {
+                            self.show_help = !self.show_help;
+                            ViewerEvent::Redraw
+                        }
+                        _ => ViewerEvent::Key(k),
+                    };
+                }
+                Ok(Event::Mouse(m)) => return ViewerEvent::Mouse(m),
+                Ok(_) => {}
+                Err(_) => return ViewerEvent::Quit,
+            }
+        }
+    }
+
+    /// Draws `frame` from the top-left corner with `status` on the bottom
+    /// row, and the help overlay on top when it is toggled on.
+    pub(crate) fn draw(&mut self, frame: &str, status: &str) {
+        let _ = self.queue_frame(frame, status);
+        let _ = self.stdout.flush();
+    }
+
+    fn queue_frame(&mut self, frame: &str, status: &str) -> io::Result<()> {
+        let status_row = self.rows.saturating_sub(1);
+
+        for (i, line) in frame.lines().take(status_row as usize).enumerate() {
+            queue!(
+                self.stdout,
+                MoveTo(0, i as u16),
+                Print(line),
+                Clear(ClearType::UntilNewLine)
+            )?;
+        }
+        queue!(self.stdout, Clear(ClearType::FromCursorDown))?;
+
+        let width = self.cols as usize;
+        let status = format!(" {status}  ? help  q quit");
+        let status: String = status.chars().take(width).collect();
+        queue!(
+            self.stdout,
+            MoveTo(0, status_row),
+            SetAttribute(Attribute::Reverse),
+            Print(format!("{status:<width$}")),
+            SetAttribute(Attribute::Reset)
+        )?;
+
+        if self.show_help {
+            self.queue_help()?;
+        }
+        Ok(())
+    }
+
+    fn queue_help(&mut self) -> io::Result<()> {
+        let key_width = self.help.iter().map(|(k, _)| k.chars().count()).max();
+        let key_width = key_width.unwrap_or(0).max("?".len());
+        let mut lines: Vec<String> = self
+            .help
+            .iter()
+            .map(|(keys, action)| format!("{keys:<key_width$}  {action}"))
+            .collect();
+        lines.push(format!("{:<key_width$}  toggle this help", "?"));
+        lines.push(format!("{:<key_width$}  close", "q"));
+
+        let inner = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let bar = "─".repeat(inner + 2);
+
+        queue!(self.stdout, MoveTo(2, 1), Print(format!("┌{bar}┐")))?;
+        for (i, line) in lines.iter().enumerate() {
+            queue!(
+                self.stdout,
+                MoveTo(2, 2 + i as u16),
+                Print(format!("│ {line:<inner$} │"))
+            )?;
+        }
+        queue!(
+            self.stdout,
+            MoveTo(2, 2 + lines.len() as u16),
+            Print(format!("└{bar}┘"))
+        )?;
+        Ok(())
+    }
+}
+
+impl Drop for Viewer {
+    fn drop(&mut self) {
+        restore_terminal();
+        // Back to the hook from before; ours only makes sense while open.
+        // Dropping ours releases its share, so the original comes back
+        // as it was.
+        drop(std::panic::take_hook());
+        if let Some(previous) = self.previous_hook.take() {
+            match Arc::try_unwrap(previous) {
+                Ok(hook) => std::panic::set_hook(hook),
+                Err(shared) => std::panic::set_hook(Box::new(move |info| shared(info))),
+            }
+        }
+    }
+}
+
+/// Leaves the alternate screen, mouse capture and raw mode. Errors are ignored: this runs
+/// on the way out, often while something else has already gone wrong.
+fn restore_terminal() {
+    let _ = execute!(
+        io::stdout(),
+        DisableMouseCapture,
+        Show,
+        LeaveAlternateScreen
+    );
+    let _ = disable_raw_mode();
+}
@@ -0,0 +1,286 @@
+//! `:wf`: a wireframe viewer for any mesh, seen through a perspective
+//! camera that orbits it. Edges go through the same braille pipeline as
+//! everything else: `draw_line` into a cell matrix, then `get_braille`.
+
+use std::{fmt::Write, path::Path, time::Duration};
+
+use crossterm::event::KeyCode;
+
+use crate::modules::{
+    commands::open_viewer,
+    common::{draw_line, get_braille, make_cell_matrix, CellMatrix, CharMatrix, GraphOptions},
+    graphing::clip_segment,
+    inputs::read_user_input,
+    logger::Logger,
+    mesh::{dot, face_normal, parse_obj, Mesh, BUILT_INS},
+    viewer::ViewerEvent,
+};
+
+// Delay between frames while the mesh spins
+const FRAME_DELAY: Duration = Duration::from_millis(30);
+// Degrees the camera orbits per tick while spinning
+const SPIN_STEP: f32 = 1.5;
+// Degrees per arrow press
+const ORBIT_STEP: f32 = 6.0;
+// Camera distance change per + or - press
+const DOLLY_STEP: f32 = 1.15;
+const MIN_DISTANCE: f32 = 1.2;
+const MAX_DISTANCE: f32 = 20.0;
+// Nothing closer to the camera than this is drawn
+const NEAR: f32 = 0.05;
+// Dots on, then off, along a dashed edge
+const DASH: f32 = 2.0;
+const DEFAULT_MESH: &str = "icosahedron";
+
+const HELP: &[(&str, &str)] = &[
+    ("← → ↑ ↓", "orbit the camera"),
+    ("+ -", "move closer / farther"),
+    ("space", "pause / resume spinning"),
+    ("b", "back edges: hidden / dashed / shown"),
+    ("r", "reset the camera"),
+];
+
+/// How edges on the far side of the mesh are drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BackEdges {
+    Hidden,
+    Dashed,
+    Shown,
+}
+
+impl BackEdges {
+    fn next(self) -> Self {
+        match self {
+            BackEdges::Hidden => BackEdges::Dashed,
+            BackEdges::Dashed => BackEdges::Shown,
+            BackEdges::Shown => BackEdges::Hidden,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            BackEdges::Hidden => "back edges hidden",
+            BackEdges::Dashed => "back edges dashed",
+            BackEdges::Shown => "back edges shown",
+        }
+    }
+}
+
+/// A camera orbiting the origin, looking at it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Camera {
+    /// Degrees around the vertical axis
+    pub yaw: f32,
+    /// Degrees above the horizontal
+    pub pitch: f32,
+    /// From the origin, in mesh units (the mesh fits a unit sphere)
+    pub distance: f32,
+    /// Vertical field of view, in degrees
+    pub fov: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            yaw: 30.0,
+            pitch: 20.0,
+            distance: 3.5,
+            fov: 45.0,
+        }
+    }
+}
+
+impl Camera {
+    /// `p` in camera space: x right, y up, z the distance in front.
+    pub(crate) fn view_point(&self, p: [f32; 3]) -> [f32; 3] {
+        let (sin_yaw, cos_yaw) = self.yaw.to_radians().sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.to_radians().sin_cos();
+        let x = p[0] * cos_yaw - p[2] * sin_yaw;
+        let z = p[0] * sin_yaw + p[2] * cos_yaw;
+        let y = p[1] * cos_pitch - z * sin_pitch;
+        let z = p[1] * sin_pitch + z * cos_pitch;
+        [x, y, z + self.distance]
+    }
+
+    /// Camera-space `p` on a `width`×`height` dot grid, rows counting down.
+    fn project(&self, p: [f32; 3], (width, height): (f32, f32)) -> (f32, f32) {
+        let focal = 1.0 / (self.fov.to_radians() / 2.0).tan();
+        // Braille dots are square, so one scale serves both axes.
+        let scale = width.min(height) / 2.0 * focal;
+        (
+            width / 2.0 + p[0] / p[2] * scale,
+            height / 2.0 - p[1] / p[2] * scale,
+        )
+    }
+
+    fn orbit(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = (self.yaw + yaw).rem_euclid(360.0);
+        self.pitch = (self.pitch + pitch).clamp(-89.0, 89.0);
+    }
+}
+
+/// Draws `mesh` as `camera` sees it. An edge is on the back when every
+/// face it belongs to faces away; loose edges are always drawn.
+pub(crate) fn render_mesh(
+    mesh: &Mesh,
+    camera: &Camera,
+    back: BackEdges,
+    go: &GraphOptions,
+) -> String {
+    let mut matrix: CellMatrix = make_cell_matrix(go);
+    let size = (go.width as f32, go.height as f32);
+
+    let view: Vec<[f32; 3]> = mesh
+        .vertices
+        .iter()
+        .map(|&v| camera.view_point(v))
+        .collect();
+    let facing = facing_faces(mesh, &view);
+
+    for edge in &mesh.edges {
+        let front = edge.faces.is_empty() || edge.faces.iter().any(|&f| facing[f]);
+        if !front && back == BackEdges::Hidden {
+            continue;
+        }
+        let Some((a, b)) = clip_near(view[edge.a], view[edge.b]) else {
+            continue;
+        };
+        let a = camera.project(a, size);
+        let b = camera.project(b, size);
+        if front || back == BackEdges::Shown {
+            line(&mut matrix, a, b, size);
+        } else {
+            dashed_line(&mut matrix, a, b, size);
+        }
+    }
+
+    let braille_chars: CharMatrix = get_braille(go, &matrix);
+    braille_chars.iter().fold(String::new(), |mut acc, s| {
+        // Writing to String never fails, safe to ignore
+        let _ = writeln!(acc, "{}", s.iter().collect::<String>());
+        acc
+    })
+}
+
+/// Whether each face of the mesh (given in camera space as `view`) faces
+/// the camera, which sits at the origin of its own space.
+pub(crate) fn facing_faces(mesh: &Mesh, view: &[[f32; 3]]) -> Vec<bool> {
+    mesh.faces
+        .iter()
+        .map(|face| dot(face_normal(face, view), view[face[0]]) < 0.0)
+        .collect()
+}
+
+/// The part of a camera-space segment in front of the near plane.
+fn clip_near(a: [f32; 3], b: [f32; 3]) -> Option<([f32; 3], [f32; 3])> {
+    let cut = |inside: [f32; 3], outside: [f32; 3]| {
+        let t = (NEAR - inside[2]) / (outside[2] - inside[2]);
+        [0, 1, 2].map(|k| inside[k] + t * (outside[k] - inside[k]))
+    };
+    match (a[2] >= NEAR, b[2] >= NEAR) {
+        (true, true) => Some((a, b)),
+        (true, false) => Some((a, cut(a, b))),
+        (false, true) => Some((cut(b, a), b)),
+        (false, false) => None,
+    }
+}
+
+fn line(matrix: &mut CellMatrix, a: (f32, f32), b: (f32, f32), size: (f32, f32)) {
+    if let Some((a, b)) = clip_segment(a, b, size) {
+        let cell = |v: f32| v.round() as usize;
+        draw_line(matrix, cell(a.0), cell(a.1), cell(b.0), cell(b.1));
+    }
+}
+
+fn dashed_line(matrix: &mut CellMatrix, a: (f32, f32), b: (f32, f32), size: (f32, f32)) {
+    let length = (b.0 - a.0).hypot(b.1 - a.1);
+    let dashes = (length / (2.0 * DASH)).ceil() as usize;
+    let at = |d: f32| {
+        let t = (d / length).min(1.0);
+        (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1))
+    };
+    for i in 0..dashes {
+        let start = i as f32 * 2.0 * DASH;
+        line(matrix, at(start), at(start + DASH), size);
+    }
+}
+
+/// A built-in by name, or an OBJ file by path.
+pub(crate) fn load_mesh(answer: &str) -> Result<(String, Mesh), String> {
+    let name = if answer.is_empty() {
+        DEFAULT_MESH
+    } else {
+        answer
+    };
+    if let Some(mesh) = Mesh::built_in(name) {
+        return Ok((name.to_string(), mesh));
+    }
+    if !name.to_ascii_lowercase().ends_with(".obj") {
+        return Err(format!(
+            "Unknown mesh '{name}'. Valid: {}, or an .obj file",
+            BUILT_INS.join(", ")
+        ));
+    }
+    let path = Path::new(name);
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Could not read {name}: {e}"))?;
+    let mesh = parse_obj(&text).map_err(|e| format!("{name}: {e}"))?;
+    let file = path
+        .file_name()
+        .map_or(name.to_string(), |f| f.to_string_lossy().into_owned());
+    Ok((file, mesh))
+}
+
+/// Runs the viewer and returns the frame it was showing when closed.
+pub(crate) fn wireframe(l: &mut impl Logger, go: &GraphOptions) -> Option<String> {
+    let (name, mesh) = loop {
+        let answer = read_user_input(&format!(
+            "mesh ({}, or an .obj file; blank = {DEFAULT_MESH}): ",
+            BUILT_INS.join(", ")
+        ))
+        .ok()?;
+        match load_mesh(&answer) {
+            Ok(loaded) => break loaded,
+            Err(e) => l.eprint(&e),
+        }
+    };
+
+    let mut viewer = open_viewer(HELP, l)?;
+    let mut camera = Camera::default();
+    let mut back = BackEdges::Hidden;
+    let mut spinning = true;
+
+    loop {
+        let view = viewer.fit(go);
+        let frame = render_mesh(&mesh, &camera, back, &view);
+        let status = format!(
+            "{name}   {} vertices, {} edges   {}   {}",
+            mesh.vertices.len(),
+            mesh.edges.len(),
+            back.name(),
+            if spinning { "spinning" } else { "paused" }
+        );
+        viewer.draw(&frame, &status);
+
+        match viewer.next_event(spinning.then_some(FRAME_DELAY)) {
+            ViewerEvent::Quit => return Some(frame),
+            ViewerEvent::Tick => camera.orbit(SPIN_STEP, 0.0),
+            ViewerEvent::Key(k) => match k.code {
+                KeyCode::Left => camera.orbit(ORBIT_STEP, 0.0),
+                KeyCode::Right => camera.orbit(-ORBIT_STEP, 0.0),
+                KeyCode::Up => camera.orbit(0.0, ORBIT_STEP),
+                KeyCode::Down => camera.orbit(0.0, -ORBIT_STEP),
+                KeyCode::Char('+') | KeyCode::Char('=') => {
+                    camera.distance = (camera.distance / DOLLY_STEP).max(MIN_DISTANCE)
+                }
+                KeyCode::Char('-') => {
+                    camera.distance = (camera.distance * DOLLY_STEP).min(MAX_DISTANCE)
+                }
+                KeyCode::Char(' ') => spinning = !spinning,
+                KeyCode::Char('b') => back = back.next(),
+                KeyCode::Char('r') => camera = Camera::default(),
+                _ => {}
+            },
+            ViewerEvent::Redraw | ViewerEvent::Mouse(_) => {}
+        }
+    }
+}